@@ -0,0 +1,195 @@
+// Local REST API on the overlay HTTP server for scripts and wincount-cli.
+// GET /api/state stays open for overlays; the routes here need the API token in an
+// X-Api-Token header. It is kept apart from the overlay token, which every OBS link
+// carries, so an overlay URL cannot change the counter.
+//
+//   POST /api/win/increment?n=1    POST /api/win/decrement?n=1
+//   POST /api/win/set?value=5      POST /api/goal/set?value=20
+//   GET  /api/presets              GET  /api/presets/export?name=Ranked
+//   POST /api/presets/load?name=Ranked
+
+use std::sync::OnceLock;
+use tauri::Manager;
+use tokio::sync::broadcast;
+
+use crate::live_events::{self, CounterAction};
+use crate::overlay::{self, HttpReply};
+use crate::{labels, presets, SharedWinState, WinState};
+
+const TOKEN_FILE: &str = "win_count_api_token";
+
+// Set once the app is up; the API answers 503 before that
+static APP: OnceLock<tauri::AppHandle> = OnceLock::new();
+static TOKEN: OnceLock<String> = OnceLock::new();
+
+#[derive(Debug, PartialEq)]
+pub enum ApiRoute {
+    Action(CounterAction),
+    ListPresets,
+    ExportPreset(String),
+    LoadPreset(String),
+}
+
+pub fn init(app: tauri::AppHandle) {
+    let _ = APP.set(app);
+    // Written now so wincount-cli finds it before the first call
+    api_token();
+}
+
+pub fn api_token() -> &'static str {
+    TOKEN.get_or_init(|| overlay::load_or_create_token(TOKEN_FILE, "API token"))
+}
+
+fn is_authorized(request: &str, expected: &str) -> bool {
+    overlay::header_value(request, "x-api-token")
+        .map(|token| overlay::tokens_match(token, expected))
+        .unwrap_or(false)
+}
+
+fn json_reply(status: &'static str, body: String) -> HttpReply {
+    HttpReply { status, content_type: "application/json", body: body.into_bytes(), location: None }
+}
+
+fn json_error(status: &'static str, message: &str) -> HttpReply {
+    json_reply(status, serde_json::json!({ "error": message }).to_string())
+}
+
+// Decode %XX escapes and '+' in a query value
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() && bytes[i + 1].is_ascii_hexdigit() && bytes[i + 2].is_ascii_hexdigit() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("0");
+                out.push(u8::from_str_radix(hex, 16).unwrap_or(0));
+                i += 2;
+            }
+            byte => out.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+fn param(query: &str, key: &str) -> Option<String> {
+    overlay::query_param(query, key).map(percent_decode)
+}
+
+// Only the syntax is checked here; the preset's bounds are checked when the action applies
+fn number(query: &str, key: &str) -> Result<Option<i32>, String> {
+    match param(query, key) {
+        None => Ok(None),
+        Some(text) => text.trim().parse::<i32>()
+            .map(Some)
+            .map_err(|_| format!("'{}' must be a whole number", key)),
+    }
+}
+
+pub fn parse_route(method: &str, path: &str, query: &str) -> Result<ApiRoute, (&'static str, String)> {
+    let bad = |e: String| ("400 Bad Request", e);
+    let name = || param(query, "name").filter(|n| !n.is_empty()).ok_or_else(|| bad("Missing 'name'".to_string()));
+    let step = || match number(query, "n").map_err(bad)?.unwrap_or(1) {
+        n if n > 0 => Ok(n),
+        _ => Err(bad("'n' must be at least 1".to_string())),
+    };
+    let value = || number(query, "value").map_err(bad)?.ok_or_else(|| bad("Missing 'value'".to_string()));
+
+    match (method, path.trim_end_matches('/')) {
+        ("POST", "/api/win/increment") => Ok(ApiRoute::Action(CounterAction::Increase { amount: step()? })),
+        ("POST", "/api/win/decrement") => Ok(ApiRoute::Action(CounterAction::Decrease { amount: step()? })),
+        ("POST", "/api/win/set") => Ok(ApiRoute::Action(CounterAction::SetWin { value: value()? })),
+        ("POST", "/api/goal/set") => Ok(ApiRoute::Action(CounterAction::SetGoal { value: value()? })),
+        ("GET", "/api/presets") => Ok(ApiRoute::ListPresets),
+        ("GET", "/api/presets/export") => Ok(ApiRoute::ExportPreset(name()?)),
+        ("POST", "/api/presets/load") => Ok(ApiRoute::LoadPreset(name()?)),
+        _ => Err(("404 Not Found", "Unknown API route".to_string())),
+    }
+}
+
+// Handle an authenticated API request; `target` is the request path with its query
+pub fn response(request: &str, target: &str) -> HttpReply {
+    let method = request.split_whitespace().next().unwrap_or("");
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    if !is_authorized(request, api_token()) {
+        return json_error("401 Unauthorized", "Missing or invalid API token");
+    }
+    let route = match parse_route(method, path, query) {
+        Ok(route) => route,
+        Err((status, message)) => return json_error(status, &message),
+    };
+    let Some(app) = APP.get() else {
+        return json_error("503 Service Unavailable", "App is still starting");
+    };
+    let state = app.state::<SharedWinState>();
+    let broadcast_tx = app.state::<broadcast::Sender<WinState>>();
+
+    match route {
+        ApiRoute::Action(action) => {
+            println!("🔌 API: {:?}", action);
+            if let Err(e) = live_events::apply_action(app, &state, &broadcast_tx, action) {
+                return json_error("422 Unprocessable Entity", &e);
+            }
+            let current = state.lock().unwrap().clone();
+            json_reply("200 OK", labels::state_json(&current))
+        }
+        ApiRoute::ListPresets => json_reply("200 OK", serde_json::to_string(&presets::store().list()).unwrap_or_default()),
+        ApiRoute::ExportPreset(name) => match presets::store().get(&name) {
+            Some(preset) => json_reply("200 OK", serde_json::to_string_pretty(preset).unwrap_or_default()),
+            None => json_error("404 Not Found", &format!("Preset '{}' not found", name)),
+        },
+        ApiRoute::LoadPreset(name) => match crate::load_preset_by_name(app, &state, &broadcast_tx, &name) {
+            Ok(_) => {
+                let current = state.lock().unwrap().clone();
+                json_reply("200 OK", labels::state_json(&current))
+            }
+            Err(e) => json_error("404 Not Found", &e),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn routes_map_to_actions() {
+        assert_eq!(parse_route("POST", "/api/win/increment", ""), Ok(ApiRoute::Action(CounterAction::Increase { amount: 1 })));
+        assert_eq!(parse_route("POST", "/api/win/decrement", "n=3"), Ok(ApiRoute::Action(CounterAction::Decrease { amount: 3 })));
+        assert_eq!(parse_route("POST", "/api/win/set", "value=-2&token=x"), Ok(ApiRoute::Action(CounterAction::SetWin { value: -2 })));
+        assert_eq!(parse_route("POST", "/api/goal/set/", "value=20"), Ok(ApiRoute::Action(CounterAction::SetGoal { value: 20 })));
+        assert_eq!(parse_route("POST", "/api/presets/load", "name=Ranked%20Solo"), Ok(ApiRoute::LoadPreset("Ranked Solo".to_string())));
+        assert_eq!(parse_route("GET", "/api/presets/export", "name=%E0%B8%81+1"), Ok(ApiRoute::ExportPreset("ก 1".to_string())));
+        assert_eq!(parse_route("GET", "/api/presets", ""), Ok(ApiRoute::ListPresets));
+    }
+
+    #[test]
+    fn rejects_bad_requests() {
+        assert_eq!(parse_route("POST", "/api/win/increment", "n=0").unwrap_err().0, "400 Bad Request");
+        assert_eq!(parse_route("POST", "/api/win/set", "").unwrap_err().0, "400 Bad Request");
+        assert_eq!(parse_route("POST", "/api/goal/set", "value=99999999999").unwrap_err().0, "400 Bad Request");
+        assert_eq!(parse_route("POST", "/api/presets/load", "").unwrap_err().0, "400 Bad Request");
+        assert_eq!(parse_route("GET", "/api/win/increment", "").unwrap_err().0, "404 Not Found");
+    }
+
+    #[test]
+    fn requires_the_api_token_header() {
+        let token = "0123456789abcdef0123456789abcdef";
+        let request = |headers: &str| format!("POST /api/win/increment HTTP/1.1\r\nHost: 127.0.0.1:777\r\n{}\r\n", headers);
+        assert!(is_authorized(&request(&format!("X-Api-Token: {}\r\n", token)), token));
+        assert!(is_authorized(&request(&format!("x-api-token:{}\r\n", token)), token));
+        assert!(!is_authorized(&request("X-Api-Token: wrong\r\n"), token));
+        // The overlay token header is not enough
+        assert!(!is_authorized(&request(&format!("X-Overlay-Token: {}\r\n", token)), token));
+        assert!(!is_authorized(&request(""), token));
+    }
+
+    #[test]
+    fn decodes_query_values() {
+        assert_eq!(percent_decode("a%20b+c"), "a b c");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz"), "%zz");
+    }
+}
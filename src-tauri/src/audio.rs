@@ -0,0 +1,288 @@
+// Audio import checks: detect the container from magic bytes, enforce size and
+// duration limits, and measure loudness so increase/decrease sounds can be
+// played back at a matching volume.
+
+use serde::ser::{SerializeMap, Serializer};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+pub const MAX_SOUND_BYTES: u64 = 5 * 1024 * 1024;
+pub const MAX_SOUND_DURATION_MS: u64 = 10_000;
+
+// Loudness every clip is normalized towards, and how far the gain may move it
+const TARGET_RMS_DBFS: f32 = -16.0;
+const MAX_GAIN_DB: f32 = 12.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioFormat {
+    Mp3,
+    Wav,
+    Ogg,
+    M4a,
+}
+
+impl AudioFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            AudioFormat::Mp3 => "mp3",
+            AudioFormat::Wav => "wav",
+            AudioFormat::Ogg => "ogg",
+            AudioFormat::M4a => "m4a",
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            AudioFormat::Mp3 => "audio/mpeg",
+            AudioFormat::Wav => "audio/wav",
+            AudioFormat::Ogg => "audio/ogg",
+            AudioFormat::M4a => "audio/mp4",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioAnalysis {
+    pub format: AudioFormat,
+    pub duration_ms: u64,
+    pub peak_dbfs: f32,
+    pub rms_dbfs: f32,
+    // Gain to apply on playback to reach the shared target loudness
+    pub normalization_gain_db: f32,
+}
+
+#[derive(Debug)]
+pub enum AudioImportError {
+    TooLarge { size: u64, max: u64 },
+    UnsupportedFormat,
+    TooLong { duration_ms: u64, max_ms: u64 },
+    Undecodable(String),
+    Silent,
+    Storage(String),
+}
+
+impl AudioImportError {
+    fn kind(&self) -> &'static str {
+        match self {
+            AudioImportError::TooLarge { .. } => "too_large",
+            AudioImportError::UnsupportedFormat => "unsupported_format",
+            AudioImportError::TooLong { .. } => "too_long",
+            AudioImportError::Undecodable(_) => "undecodable",
+            AudioImportError::Silent => "silent",
+            AudioImportError::Storage(_) => "storage",
+        }
+    }
+}
+
+impl fmt::Display for AudioImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AudioImportError::TooLarge { size, max } => write!(f, "Sound file is too large ({:.1} MB, max {:.1} MB)", *size as f64 / 1048576.0, *max as f64 / 1048576.0),
+            AudioImportError::UnsupportedFormat => write!(f, "Unsupported sound file, expected MP3, WAV, OGG or M4A"),
+            AudioImportError::TooLong { duration_ms, max_ms } => write!(f, "Sound is too long ({:.1}s, max {:.1}s)", *duration_ms as f64 / 1000.0, *max_ms as f64 / 1000.0),
+            AudioImportError::Undecodable(reason) => write!(f, "Sound file could not be decoded: {}", reason),
+            AudioImportError::Silent => write!(f, "Sound file contains only silence"),
+            AudioImportError::Storage(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+// Sent to the UI as { kind, message } so it can branch on `kind` and show `message`
+impl Serialize for AudioImportError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(2))?;
+        map.serialize_entry("kind", self.kind())?;
+        map.serialize_entry("message", &self.to_string())?;
+        map.end()
+    }
+}
+
+impl From<String> for AudioImportError {
+    fn from(message: String) -> Self {
+        AudioImportError::Storage(message)
+    }
+}
+
+pub fn detect_format(data: &[u8]) -> Option<AudioFormat> {
+    if data.len() < 12 {
+        return None;
+    }
+    if &data[0..4] == b"RIFF" && &data[8..12] == b"WAVE" {
+        return Some(AudioFormat::Wav);
+    }
+    if &data[0..4] == b"OggS" {
+        return Some(AudioFormat::Ogg);
+    }
+    if &data[4..8] == b"ftyp" && [b"M4A ", b"M4B ", b"mp41", b"mp42", b"isom", b"iso2"].contains(&&[data[8], data[9], data[10], data[11]]) {
+        return Some(AudioFormat::M4a);
+    }
+    // ID3v2 tag, or a bare MPEG audio frame sync
+    if &data[0..3] == b"ID3" || (data[0] == 0xFF && data[1] & 0xE0 == 0xE0) {
+        return Some(AudioFormat::Mp3);
+    }
+    None
+}
+
+fn to_dbfs(amplitude: f32) -> f32 {
+    20.0 * amplitude.max(1e-9).log10()
+}
+
+// Validate an uploaded sound and measure it. Decodes the whole clip once.
+pub fn analyze(data: &[u8]) -> Result<AudioAnalysis, AudioImportError> {
+    let size = data.len() as u64;
+    if size > MAX_SOUND_BYTES {
+        return Err(AudioImportError::TooLarge { size, max: MAX_SOUND_BYTES });
+    }
+    let format = detect_format(data).ok_or(AudioImportError::UnsupportedFormat)?;
+
+    let stream = MediaSourceStream::new(Box::new(std::io::Cursor::new(data.to_vec())), Default::default());
+    let mut hint = Hint::new();
+    hint.with_extension(format.extension());
+    let probed = symphonia::default::get_probe()
+        .format(&hint, stream, &FormatOptions::default(), &MetadataOptions::default())
+        .map_err(|e| AudioImportError::Undecodable(e.to_string()))?;
+    let mut reader = probed.format;
+
+    let track = reader.tracks()
+        .iter()
+        .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or_else(|| AudioImportError::Undecodable("no audio track".to_string()))?;
+    let track_id = track.id;
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .map_err(|e| AudioImportError::Undecodable(e.to_string()))?;
+
+    let mut frames: u64 = 0;
+    let mut sample_rate: u64 = 0;
+    let mut peak: f32 = 0.0;
+    let mut sum_squares: f64 = 0.0;
+    let mut sample_count: u64 = 0;
+
+    loop {
+        let packet = match reader.next_packet() {
+            Ok(packet) => packet,
+            Err(SymphoniaError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(AudioImportError::Undecodable(e.to_string())),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            // A damaged frame is skipped, like players do
+            Err(SymphoniaError::DecodeError(_)) => continue,
+            Err(e) => return Err(AudioImportError::Undecodable(e.to_string())),
+        };
+
+        let spec = *decoded.spec();
+        sample_rate = spec.rate as u64;
+        frames += decoded.frames() as u64;
+        if sample_rate > 0 && frames * 1000 / sample_rate > MAX_SOUND_DURATION_MS {
+            return Err(AudioImportError::TooLong { duration_ms: frames * 1000 / sample_rate, max_ms: MAX_SOUND_DURATION_MS });
+        }
+
+        let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+        buffer.copy_interleaved_ref(decoded);
+        for &sample in buffer.samples() {
+            peak = peak.max(sample.abs());
+            sum_squares += (sample as f64) * (sample as f64);
+            sample_count += 1;
+        }
+    }
+
+    if sample_rate == 0 || sample_count == 0 {
+        return Err(AudioImportError::Undecodable("no audio frames".to_string()));
+    }
+    if peak == 0.0 {
+        return Err(AudioImportError::Silent);
+    }
+
+    let rms = (sum_squares / sample_count as f64).sqrt() as f32;
+    let rms_dbfs = to_dbfs(rms);
+    let peak_dbfs = to_dbfs(peak);
+    // Never boost past 0 dBFS peak
+    let gain = (TARGET_RMS_DBFS - rms_dbfs).clamp(-MAX_GAIN_DB, MAX_GAIN_DB).min(-peak_dbfs);
+
+    Ok(AudioAnalysis {
+        format,
+        duration_ms: frames * 1000 / sample_rate,
+        peak_dbfs,
+        rms_dbfs,
+        normalization_gain_db: gain,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A mono 16-bit PCM WAV holding `samples`
+    fn wav(rate: u32, samples: &[i16]) -> Vec<u8> {
+        let data_len = (samples.len() * 2) as u32;
+        let mut out = Vec::new();
+        out.extend_from_slice(b"RIFF");
+        out.extend_from_slice(&(36 + data_len).to_le_bytes());
+        out.extend_from_slice(b"WAVEfmt ");
+        out.extend_from_slice(&16u32.to_le_bytes());
+        out.extend_from_slice(&1u16.to_le_bytes());
+        out.extend_from_slice(&1u16.to_le_bytes());
+        out.extend_from_slice(&rate.to_le_bytes());
+        out.extend_from_slice(&(rate * 2).to_le_bytes());
+        out.extend_from_slice(&2u16.to_le_bytes());
+        out.extend_from_slice(&16u16.to_le_bytes());
+        out.extend_from_slice(b"data");
+        out.extend_from_slice(&data_len.to_le_bytes());
+        for sample in samples {
+            out.extend_from_slice(&sample.to_le_bytes());
+        }
+        out
+    }
+
+    fn tone(count: usize) -> Vec<i16> {
+        (0..count).map(|i| ((i as f32 * 0.1).sin() * 8000.0) as i16).collect()
+    }
+
+    #[test]
+    fn detects_formats_by_magic_bytes() {
+        assert_eq!(detect_format(&wav(8000, &[0; 4])), Some(AudioFormat::Wav));
+        assert_eq!(detect_format(b"OggS\0\x02\0\0\0\0\0\0"), Some(AudioFormat::Ogg));
+        assert_eq!(detect_format(b"\0\0\0\x20ftypM4A \0\0"), Some(AudioFormat::M4a));
+        assert_eq!(detect_format(b"ID3\x04\0\0\0\0\0\0\0\0"), Some(AudioFormat::Mp3));
+        assert_eq!(detect_format(b"<html>not a sound</html>"), None);
+        assert_eq!(detect_format(b"RIFF"), None);
+    }
+
+    #[test]
+    fn analyzes_a_valid_wav() {
+        let analysis = analyze(&wav(8000, &tone(8000))).unwrap();
+        assert_eq!(analysis.format, AudioFormat::Wav);
+        assert_eq!(analysis.duration_ms, 1000);
+        assert!(analysis.peak_dbfs < 0.0 && analysis.rms_dbfs < analysis.peak_dbfs);
+        assert!(analysis.normalization_gain_db <= -analysis.peak_dbfs);
+    }
+
+    #[test]
+    fn rejects_bad_uploads() {
+        let mut huge = wav(8000, &[]);
+        huge.resize(MAX_SOUND_BYTES as usize + 1, 0);
+        assert!(matches!(analyze(&huge), Err(AudioImportError::TooLarge { .. })));
+        assert!(matches!(analyze(b"this is not audio at all"), Err(AudioImportError::UnsupportedFormat)));
+        assert!(matches!(analyze(&wav(8000, &[0; 8000])), Err(AudioImportError::Silent)));
+        assert!(matches!(analyze(&wav(8000, &tone(8000 * 11))), Err(AudioImportError::TooLong { .. })));
+    }
+
+    #[test]
+    fn errors_serialize_with_a_kind() {
+        let json = serde_json::to_value(AudioImportError::Silent).unwrap();
+        assert_eq!(json["kind"], "silent");
+        assert_eq!(json["message"], "Sound file contains only silence");
+    }
+}
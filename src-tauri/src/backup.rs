@@ -0,0 +1,441 @@
+// Rolling backups of user data. Each snapshot is a timestamped folder under
+// <app data>/backups holding copies of the data files plus a manifest with
+// their checksums. Restores validate every file before swapping it in. Secrets
+// (passwords, tokens, webhook URLs, the license crypto secret) are left out of
+// snapshots and taken from the live files on restore.
+
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::Emitter;
+use tokio::sync::broadcast;
+
+use crate::{chat, history, live_events, obs, osc, overlay_instances, settings, sounds, text_output, webhooks};
+use crate::{get_app_data_dir, get_app_data_file, get_state_path, load_state, presets, register_hotkeys_dynamically, write_atomic, SharedWinState, WinState};
+
+const BACKUP_DIR: &str = "backups";
+const MANIFEST_FILE: &str = "manifest.json";
+
+// Snapshot every hour, keep the newest MAX_BACKUPS
+const BACKUP_INTERVAL: u64 = 60 * 60; // วินาที
+const MAX_BACKUPS: usize = 24;
+
+// One backup or restore at a time
+static BACKUP_LOCK: Mutex<()> = Mutex::new(());
+
+// Every user data file that belongs in a snapshot. Each module declares its own
+// DataFile next to the code that writes it; `every_data_file_is_registered` fails
+// when a module writes a file that is missing here and not in LOCAL_ONLY.
+const DATA_FILES: [DataFile; 14] = [
+    crate::STATE_DATA_FILE,
+    presets::DATA_FILE,
+    crate::HOTKEYS_DATA_FILE,
+    crate::LICENSE_DATA_FILE,
+    history::DATA_FILE,
+    sounds::DATA_FILE,
+    overlay_instances::DATA_FILE,
+    text_output::DATA_FILE,
+    live_events::DATA_FILE,
+    chat::DATA_FILE,
+    webhooks::DATA_FILE,
+    obs::DATA_FILE,
+    osc::DATA_FILE,
+    settings::DATA_FILE,
+];
+
+// Files in the data folder that are never backed up: access tokens stay on this
+// machine, the ports file only describes the running app, and the portable
+// markers live next to the executable
+#[cfg(test)]
+const LOCAL_ONLY: [&str; 7] = ["win_count_overlay_token", "win_count_api_token", "win_count_ports.json", "wincount-portable.txt", "wincount-migrate.txt", MANIFEST_FILE, "theme.json"];
+
+type Transform = fn(&[u8]) -> Result<Vec<u8>, String>;
+
+// A user data file as seen by backups
+pub struct DataFile {
+    pub name: &'static str,
+    // Live location when it is not <app data>/<name>
+    path: Option<fn() -> PathBuf>,
+    // Checks a backed-up copy before it replaces the live file
+    validate: fn(&[u8]) -> Result<(), String>,
+    // Copy written to the backup: secrets are left out
+    backup_copy: Transform,
+    // Copy written back on restore: left-out secrets come from the live file
+    restored_copy: Transform,
+}
+
+fn parses_as<T: DeserializeOwned>(data: &[u8]) -> Result<(), String> {
+    serde_json::from_slice::<T>(data).map(|_| ()).map_err(|e| e.to_string())
+}
+
+fn unchanged(data: &[u8]) -> Result<Vec<u8>, String> {
+    Ok(data.to_vec())
+}
+
+impl DataFile {
+    // A JSON file that must parse as `T`
+    pub const fn json<T: DeserializeOwned>(name: &'static str) -> Self {
+        Self::checked(name, parses_as::<T>)
+    }
+
+    pub const fn checked(name: &'static str, validate: fn(&[u8]) -> Result<(), String>) -> Self {
+        Self { name, path: None, validate, backup_copy: unchanged, restored_copy: unchanged }
+    }
+
+    pub const fn at(self, path: fn() -> PathBuf) -> Self {
+        Self { path: Some(path), ..self }
+    }
+
+    pub const fn with_secrets(self, backup_copy: Transform, restored_copy: Transform) -> Self {
+        Self { backup_copy, restored_copy, ..self }
+    }
+
+    fn live_path(&self) -> Result<PathBuf, String> {
+        match self.path {
+            Some(path) => Ok(path()),
+            None => get_app_data_file(self.name),
+        }
+    }
+}
+
+fn data_file(name: &str) -> Option<&'static DataFile> {
+    const FILES: &[DataFile] = &DATA_FILES;
+    FILES.iter().find(|file| file.name == name)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupFile {
+    pub name: String,
+    pub sha256: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupInfo {
+    pub id: String,
+    pub created_at: DateTime<Utc>,
+    pub reason: String,
+    pub files: Vec<BackupFile>,
+}
+
+fn backups_root() -> Result<PathBuf, String> {
+    let root = get_app_data_dir()?.join(BACKUP_DIR);
+    fs::create_dir_all(&root).map_err(|e| format!("Failed to create backups directory: {}", e))?;
+    Ok(root)
+}
+
+// (name inside the backup, live path) of every file to snapshot
+fn backup_sources() -> Vec<(String, PathBuf)> {
+    let mut sources: Vec<(String, PathBuf)> = DATA_FILES.iter()
+        .filter_map(|file| Some((file.name.to_string(), file.live_path().ok()?)))
+        .collect();
+
+    // Custom sound metadata from before the sound library (sound_<type>_metadata.json)
+    if let Ok(entries) = get_app_data_dir().and_then(|dir| fs::read_dir(dir).map_err(|e| e.to_string())) {
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with("sound_") && name.ends_with("_metadata.json") {
+                sources.push((name, entry.path()));
+            }
+        }
+    }
+    sources
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+fn read_manifest(dir: &Path) -> Option<BackupInfo> {
+    let json = fs::read_to_string(dir.join(MANIFEST_FILE)).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+// Newest first
+pub fn list_backups() -> Result<Vec<BackupInfo>, String> {
+    list_in(&backups_root()?)
+}
+
+fn list_in(root: &Path) -> Result<Vec<BackupInfo>, String> {
+    let mut backups: Vec<BackupInfo> = fs::read_dir(root)
+        .map_err(|e| format!("Failed to read backups directory: {}", e))?
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| read_manifest(&entry.path()))
+        .collect();
+    backups.sort_by_key(|b| std::cmp::Reverse(b.created_at));
+    Ok(backups)
+}
+
+// Snapshot the current data files. Returns None when nothing changed since the
+// latest snapshot and `force` is off.
+pub fn create_backup(reason: &str, force: bool) -> Result<Option<BackupInfo>, String> {
+    let _guard = BACKUP_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    snapshot(&backups_root()?, backup_sources(), reason, force)
+}
+
+fn snapshot(root: &Path, sources: Vec<(String, PathBuf)>, reason: &str, force: bool) -> Result<Option<BackupInfo>, String> {
+    let mut contents = Vec::new();
+    for (name, path) in sources {
+        let Ok(data) = fs::read(&path) else {
+            continue;
+        };
+        let data = match data_file(&name) {
+            Some(file) => (file.backup_copy)(&data).unwrap_or(data),
+            None => data,
+        };
+        contents.push((name, data));
+    }
+    if contents.is_empty() {
+        return Ok(None);
+    }
+
+    let files: Vec<BackupFile> = contents.iter()
+        .map(|(name, data)| BackupFile { name: name.clone(), sha256: sha256_hex(data), size: data.len() as u64 })
+        .collect();
+
+    if !force {
+        if let Some(latest) = list_in(root)?.into_iter().next() {
+            let same = |a: &[BackupFile], b: &[BackupFile]| {
+                a.len() == b.len() && a.iter().all(|f| b.iter().any(|g| g.name == f.name && g.sha256 == f.sha256))
+            };
+            if same(&latest.files, &files) {
+                return Ok(None);
+            }
+        }
+    }
+
+    let created_at = Utc::now();
+    let id = created_at.format("%Y%m%d-%H%M%S-%3f").to_string();
+    let dir = root.join(&id);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create backup folder: {}", e))?;
+    for (name, data) in &contents {
+        fs::write(dir.join(name), data).map_err(|e| format!("Failed to write backup file {}: {}", name, e))?;
+    }
+
+    let info = BackupInfo { id, created_at, reason: reason.to_string(), files };
+    let manifest = serde_json::to_string_pretty(&info).map_err(|e| format!("Failed to serialize backup manifest: {}", e))?;
+    // The manifest goes last so a half-written snapshot is never listed
+    write_atomic(&dir.join(MANIFEST_FILE), manifest.as_bytes())
+        .map_err(|e| format!("Failed to write backup manifest: {}", e))?;
+
+    prune_backups(root)?;
+    println!("🗄️ Created backup {} ({} files, {})", info.id, info.files.len(), reason);
+    Ok(Some(info))
+}
+
+fn prune_backups(root: &Path) -> Result<(), String> {
+    for old in list_in(root)?.into_iter().skip(MAX_BACKUPS) {
+        let _ = fs::remove_dir_all(root.join(&old.id));
+        println!("🧹 Removed old backup {}", old.id);
+    }
+    Ok(())
+}
+
+// Check that a backed-up file is intact and parses as what it claims to be.
+// Returns the data to restore, with left-out secrets filled in.
+fn validate_file(name: &str, data: &[u8], expected: &BackupFile) -> Result<Vec<u8>, String> {
+    if sha256_hex(data) != expected.sha256 {
+        return Err(format!("Backup file {} is corrupted (checksum mismatch)", name));
+    }
+    let restored = match data_file(name) {
+        Some(file) => {
+            let restored = (file.restored_copy)(data)?;
+            (file.validate)(&restored).map(|_| restored)
+        }
+        // Sound metadata from before the sound library, or a file this version no longer writes
+        None => parses_as::<serde_json::Value>(data).map(|_| data.to_vec()),
+    };
+    restored.map_err(|e| format!("Backup file {} is invalid: {}", name, e))
+}
+
+// Read and validate every file of a snapshot
+fn read_snapshot(dir: &Path, info: &BackupInfo) -> Result<Vec<(String, Vec<u8>)>, String> {
+    let mut validated = Vec::new();
+    for file in &info.files {
+        // Names come from the manifest; plain file names only
+        if file.name.is_empty() || file.name.contains(['/', '\\', ':']) || file.name.contains("..") {
+            return Err(format!("Backup file name '{}' is not allowed", file.name));
+        }
+        let data = fs::read(dir.join(&file.name)).map_err(|e| format!("Failed to read backup file {}: {}", file.name, e))?;
+        validated.push((file.name.clone(), validate_file(&file.name, &data, file)?));
+    }
+    Ok(validated)
+}
+
+pub fn restore_backup(id: &str, app: &tauri::AppHandle, state: &SharedWinState, broadcast_tx: &broadcast::Sender<WinState>) -> Result<(), String> {
+    // Only ids that appear in the listing are accepted, so `id` cannot point outside the backups folder
+    let info = list_backups()?
+        .into_iter()
+        .find(|b| b.id == id)
+        .ok_or_else(|| format!("Backup '{}' not found", id))?;
+
+    // Read and validate everything before touching the live files
+    let validated = read_snapshot(&backups_root()?.join(&info.id), &info)?;
+
+    // Keep the current data restorable in case this restore was a mistake
+    create_backup(&format!("before restoring {}", info.id), true)?;
+
+    let _guard = BACKUP_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let sources: HashMap<String, PathBuf> = backup_sources().into_iter().collect();
+    for (name, data) in &validated {
+        let target = match sources.get(name) {
+            Some(path) => path.clone(),
+            // Sound metadata that no longer exists locally
+            None => get_app_data_file(name)?,
+        };
+        write_atomic(&target, data).map_err(|e| format!("Failed to restore {}: {}", name, e))?;
+    }
+    // A snapshot from before the sound library carries only the old per-slot
+    // metadata; drop the live library so it is migrated again from those files
+    let has_library = validated.iter().any(|(name, _)| name == "sound_library.json");
+    if !has_library && validated.iter().any(|(name, _)| name.starts_with("sound_")) {
+        let _ = get_app_data_file("sound_library.json").map(fs::remove_file);
+    }
+    drop(_guard);
+
+    // Reload in place: state, presets, hotkeys
+    {
+        let mut s = state.lock().unwrap();
+        *s = load_state(&get_state_path());
+        let mut store = presets::store();
+        store.reload()?;
+        sounds::reload();
+        sounds::apply_preset_sounds(&store.get(&s.current_preset).map(|p| p.sounds.clone()).unwrap_or_default());
+        drop(store);
+        text_output::reload_config();
+        live_events::reload_config();
+        chat::reload_config();
+        obs::reload_config();
+        osc::reload_config();
+        webhooks::reload_config();
+        settings::reload();
+        let _ = app.emit("state-updated", s.clone());
+        let _ = broadcast_tx.send(s.clone());
+    }
+    register_hotkeys_dynamically(app, state, broadcast_tx)?;
+
+    println!("♻️ Restored backup {} ({} files)", info.id, validated.len());
+    Ok(())
+}
+
+pub fn start_backup_scheduler() {
+    std::thread::spawn(move || {
+        loop {
+            if let Err(e) = create_backup("scheduled", false) {
+                println!("⚠️ Scheduled backup failed: {}", e);
+            }
+            std::thread::sleep(std::time::Duration::from_secs(BACKUP_INTERVAL));
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(test: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("wincount_backup_test_{}_{}", test, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn history_json(count: i32) -> Vec<u8> {
+        format!(r#"[{{"preset":"Default","win":{},"goal":10,"reason":"manual","archived_at":"2026-01-01T00:00:00Z"}}]"#, count).into_bytes()
+    }
+
+    #[test]
+    fn every_data_file_is_registered() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+        for entry in fs::read_dir(dir).unwrap().flatten() {
+            let source = fs::read_to_string(entry.path()).unwrap_or_default();
+            for pattern in ["_FILE: &str = \"", "get_app_data_file(\""] {
+                for (at, _) in source.match_indices(pattern) {
+                    let rest = &source[at + pattern.len()..];
+                    let name = &rest[..rest.find('"').unwrap()];
+                    assert!(
+                        data_file(name).is_some() || LOCAL_ONLY.contains(&name),
+                        "{} writes {}, which is neither in DATA_FILES nor LOCAL_ONLY", entry.path().display(), name
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn unchanged_data_is_not_snapshotted_again() {
+        let dir = temp_dir("unchanged");
+        let history = dir.join("win_count_history.json");
+        fs::write(&history, history_json(1)).unwrap();
+        let sources = || vec![("win_count_history.json".to_string(), history.clone())];
+        let root = dir.join(BACKUP_DIR);
+        fs::create_dir_all(&root).unwrap();
+
+        assert!(snapshot(&root, sources(), "test", false).unwrap().is_some());
+        assert!(snapshot(&root, sources(), "test", false).unwrap().is_none());
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        assert!(snapshot(&root, sources(), "test", true).unwrap().is_some());
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        fs::write(&history, history_json(2)).unwrap();
+        assert!(snapshot(&root, sources(), "test", false).unwrap().is_some());
+        assert_eq!(list_in(&root).unwrap().len(), 3);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn secrets_are_left_out_of_snapshots() {
+        let dir = temp_dir("secrets");
+        let obs = dir.join("win_count_obs.json");
+        fs::write(&obs, r#"{"enabled":true,"host":"localhost","port":4455,"password":"hunter2","rules":[]}"#).unwrap();
+        let root = dir.join(BACKUP_DIR);
+        fs::create_dir_all(&root).unwrap();
+
+        let info = snapshot(&root, vec![("win_count_obs.json".to_string(), obs)], "test", false).unwrap().unwrap();
+        let copy = fs::read_to_string(root.join(&info.id).join("win_count_obs.json")).unwrap();
+        assert!(!copy.contains("hunter2"));
+        assert!(copy.contains("localhost"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn validation_checks_checksum_and_schema() {
+        let good = history_json(1);
+        let expected = BackupFile { name: "win_count_history.json".to_string(), sha256: sha256_hex(&good), size: good.len() as u64 };
+        assert_eq!(validate_file("win_count_history.json", &good, &expected).unwrap(), good);
+
+        let tampered = history_json(2);
+        assert!(validate_file("win_count_history.json", &tampered, &expected).unwrap_err().contains("checksum mismatch"));
+
+        let wrong = br#"{"not":"a history"}"#;
+        let expected = BackupFile { sha256: sha256_hex(wrong), ..expected };
+        assert!(validate_file("win_count_history.json", wrong, &expected).unwrap_err().contains("is invalid"));
+
+        // Files this version does not know only need to be JSON
+        let legacy = br#"{"file":"win.mp3"}"#;
+        let expected = BackupFile { name: "sound_win_metadata.json".to_string(), sha256: sha256_hex(legacy), size: legacy.len() as u64 };
+        assert!(validate_file("sound_win_metadata.json", legacy, &expected).is_ok());
+        let empty = BackupFile { name: "win_count_license.json".to_string(), sha256: sha256_hex(b""), size: 0 };
+        assert!(validate_file("win_count_license.json", b"", &empty).is_err());
+    }
+
+    #[test]
+    fn snapshots_with_unsafe_names_are_refused() {
+        let dir = temp_dir("names");
+        for name in ["../win_count_history.json", "sub/file.json", "sub\\file.json", "C:file.json", ""] {
+            let info = BackupInfo {
+                id: "test".to_string(),
+                created_at: Utc::now(),
+                reason: "test".to_string(),
+                files: vec![BackupFile { name: name.to_string(), sha256: String::new(), size: 0 }],
+            };
+            assert!(read_snapshot(&dir, &info).unwrap_err().contains("is not allowed"), "{}", name);
+        }
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
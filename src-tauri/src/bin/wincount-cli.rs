@@ -0,0 +1,255 @@
+// Command line client for a running Win Count app. Talks to the local API on the
+// overlay HTTP server and streams changes from the overlay WebSocket. Output is JSON
+// on stdout; errors are {"error": "..."} on stderr with exit code 1.
+
+use futures_util::StreamExt;
+use serde_json::Value;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use tokio_tungstenite::tungstenite::Message;
+use win_count_by_artywoof::data_dir::{self, Ports, DEFAULT_HTTP_PORT, DEFAULT_WS_PORT};
+
+const TOKEN_FILE: &str = "win_count_api_token";
+
+const USAGE: &str = "Usage: wincount-cli [--port N] [--ws-port N] [--token T] <command>
+
+Commands:
+  get                     Current state
+  inc [n]                 Add n wins (default 1)
+  dec [n]                 Remove n wins (default 1)
+  set <value>             Set the win count
+  goal <value>            Set the goal
+  preset list             All presets
+  preset load <name>      Switch to a preset
+  preset export <name>    Print a preset as JSON
+  watch                   Print the state on every change, one JSON object per line
+
+Ports default to the ones the running app reports in its data folder.
+Environment: WINCOUNT_PORT, WINCOUNT_WS_PORT, WINCOUNT_TOKEN, WINCOUNT_DATA_DIR";
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Get,
+    Inc(i32),
+    Dec(i32),
+    Set(i32),
+    Goal(i32),
+    PresetList,
+    PresetLoad(String),
+    PresetExport(String),
+    Watch,
+    Help,
+}
+
+#[derive(Debug, PartialEq)]
+struct Options {
+    http_port: u16,
+    ws_port: u16,
+    token: Option<String>,
+    command: Command,
+}
+
+fn parse_number(text: Option<&String>, what: &str) -> Result<i32, String> {
+    let text = text.ok_or_else(|| format!("Missing {}", what))?;
+    // The app checks the value against the active preset's bounds
+    text.parse::<i32>().map_err(|_| format!("Invalid {} '{}': expected a whole number", what, text))
+}
+
+fn parse_step(text: Option<&String>) -> Result<i32, String> {
+    match text {
+        None => Ok(1),
+        Some(_) => parse_number(text, "step").and_then(|n| if n > 0 { Ok(n) } else { Err("Step must be at least 1".to_string()) }),
+    }
+}
+
+// Flags win over the environment, which wins over the ports the app reported
+fn parse_args(args: &[String], env: impl Fn(&str) -> Option<String>, reported: Option<Ports>) -> Result<Options, String> {
+    let port_from = |text: &str, what: &str| text.parse::<u16>().ok().filter(|p| *p > 0).ok_or_else(|| format!("Invalid {} '{}'", what, text));
+    let reported = reported.unwrap_or(Ports { http_port: DEFAULT_HTTP_PORT, ws_port: DEFAULT_WS_PORT });
+    let mut http_port = env("WINCOUNT_PORT").map(|p| port_from(&p, "WINCOUNT_PORT")).transpose()?.unwrap_or(reported.http_port);
+    let mut ws_port = env("WINCOUNT_WS_PORT").map(|p| port_from(&p, "WINCOUNT_WS_PORT")).transpose()?.unwrap_or(reported.ws_port);
+    let mut token = env("WINCOUNT_TOKEN").filter(|t| !t.is_empty());
+
+    let mut rest = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--port" => http_port = port_from(iter.next().ok_or("Missing value for --port")?, "--port")?,
+            "--ws-port" => ws_port = port_from(iter.next().ok_or("Missing value for --ws-port")?, "--ws-port")?,
+            "--token" => token = Some(iter.next().ok_or("Missing value for --token")?.clone()),
+            "-h" | "--help" => rest.push("help".to_string()),
+            _ => rest.push(arg.clone()),
+        }
+    }
+
+    let command = match rest.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        ["get"] => Command::Get,
+        ["inc"] | ["inc", _] => Command::Inc(parse_step(rest.get(1))?),
+        ["dec"] | ["dec", _] => Command::Dec(parse_step(rest.get(1))?),
+        ["set", _] => Command::Set(parse_number(rest.get(1), "value")?),
+        ["goal", _] => Command::Goal(parse_number(rest.get(1), "goal")?),
+        ["preset", "list"] => Command::PresetList,
+        ["preset", "load", name] => Command::PresetLoad(name.to_string()),
+        ["preset", "export", name] => Command::PresetExport(name.to_string()),
+        ["watch"] => Command::Watch,
+        [] | ["help"] => Command::Help,
+        _ => return Err(format!("Unknown command '{}'. Run wincount-cli help", rest.join(" "))),
+    };
+    Ok(Options { http_port, ws_port, token, command })
+}
+
+// The app's data folder: WINCOUNT_DATA_DIR, the portable folder if the app uses
+// one, or the per-user folder
+fn data_folder() -> Option<PathBuf> {
+    data_dir::resolve(std::env::var_os("WINCOUNT_DATA_DIR").filter(|d| !d.is_empty()).map(PathBuf::from))
+}
+
+// The API token the app stores next to its other data
+fn stored_token() -> Option<String> {
+    let path = data_folder()?.join(TOKEN_FILE);
+    std::fs::read_to_string(path).ok().map(|t| t.trim().to_string()).filter(|t| !t.is_empty())
+}
+
+async fn call(options: &Options, method: reqwest::Method, path: &str, query: &[(&str, String)]) -> Result<Value, String> {
+    let url = format!("http://127.0.0.1:{}{}", options.http_port, path);
+    let mut request = reqwest::Client::new().request(method, &url).query(query);
+    if let Some(token) = options.token.clone().or_else(stored_token) {
+        request = request.header("X-Api-Token", token);
+    }
+    let response = request.send()
+        .await
+        .map_err(|e| format!("Cannot reach Win Count on port {} (is it running?): {}", options.http_port, e))?;
+    let status = response.status();
+    let body: Value = response.json().await.map_err(|e| format!("Unexpected response: {}", e))?;
+    if status.is_success() {
+        Ok(body)
+    } else {
+        Err(body.get("error").and_then(Value::as_str).map(str::to_string).unwrap_or_else(|| format!("HTTP {}", status)))
+    }
+}
+
+async fn watch(options: &Options) -> Result<(), String> {
+    let url = format!("ws://127.0.0.1:{}/", options.ws_port);
+    let (mut ws, _) = tokio_tungstenite::connect_async(url.as_str())
+        .await
+        .map_err(|e| format!("Cannot reach Win Count on port {} (is it running?): {}", options.ws_port, e))?;
+    while let Some(message) = ws.next().await {
+        match message.map_err(|e| format!("Connection lost: {}", e))? {
+            Message::Text(text) => {
+                // State payloads have no "type"; sound cues and overlay configs do
+                let Ok(value) = serde_json::from_str::<Value>(&text) else {
+                    continue;
+                };
+                if value.get("type").is_none() {
+                    println!("{}", value);
+                }
+            }
+            Message::Close(_) => break,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+async fn run(options: Options) -> Result<Option<Value>, String> {
+    use reqwest::Method;
+    let result = match options.command {
+        Command::Get => call(&options, Method::GET, "/api/state", &[]).await?,
+        Command::Inc(n) => call(&options, Method::POST, "/api/win/increment", &[("n", n.to_string())]).await?,
+        Command::Dec(n) => call(&options, Method::POST, "/api/win/decrement", &[("n", n.to_string())]).await?,
+        Command::Set(value) => call(&options, Method::POST, "/api/win/set", &[("value", value.to_string())]).await?,
+        Command::Goal(value) => call(&options, Method::POST, "/api/goal/set", &[("value", value.to_string())]).await?,
+        Command::PresetList => call(&options, Method::GET, "/api/presets", &[]).await?,
+        Command::PresetLoad(ref name) => call(&options, Method::POST, "/api/presets/load", &[("name", name.clone())]).await?,
+        Command::PresetExport(ref name) => call(&options, Method::GET, "/api/presets/export", &[("name", name.clone())]).await?,
+        Command::Watch => {
+            watch(&options).await?;
+            return Ok(None);
+        }
+        Command::Help => {
+            println!("{}", USAGE);
+            return Ok(None);
+        }
+    };
+    Ok(Some(result))
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let reported = data_folder().and_then(|dir| data_dir::read_ports(&dir));
+    let outcome = match parse_args(&args, |key| std::env::var(key).ok(), reported) {
+        Ok(options) => run(options).await,
+        Err(e) => Err(e),
+    };
+    match outcome {
+        Ok(Some(value)) => {
+            println!("{}", serde_json::to_string_pretty(&value).unwrap_or_default());
+            ExitCode::SUCCESS
+        }
+        Ok(None) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", serde_json::json!({ "error": e }));
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        parse_args(&args, |_| None, None)
+    }
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(parse(&["get"]).unwrap().command, Command::Get);
+        assert_eq!(parse(&["inc"]).unwrap().command, Command::Inc(1));
+        assert_eq!(parse(&["dec", "5"]).unwrap().command, Command::Dec(5));
+        assert_eq!(parse(&["set", "-3"]).unwrap().command, Command::Set(-3));
+        assert_eq!(parse(&["goal", "20"]).unwrap().command, Command::Goal(20));
+        assert_eq!(parse(&["preset", "load", "Ranked Solo"]).unwrap().command, Command::PresetLoad("Ranked Solo".to_string()));
+        assert_eq!(parse(&["preset", "export", "Ranked"]).unwrap().command, Command::PresetExport("Ranked".to_string()));
+        assert_eq!(parse(&[]).unwrap().command, Command::Help);
+
+        let options = parse(&["--port", "7777", "watch", "--token", "abc"]).unwrap();
+        assert_eq!((options.http_port, options.ws_port, options.token.as_deref()), (7777, DEFAULT_WS_PORT, Some("abc")));
+        assert_eq!(options.command, Command::Watch);
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        assert!(parse(&["inc", "0"]).is_err());
+        assert!(parse(&["set"]).is_err());
+        assert!(parse(&["set", "many"]).is_err());
+        assert!(parse(&["goal", "99999999999"]).is_err());
+        assert_eq!(parse(&["goal", "20000"]).unwrap().command, Command::Goal(20000));
+        assert!(parse(&["preset"]).is_err());
+        assert!(parse(&["--port", "0", "get"]).is_err());
+        assert!(parse(&["fly"]).is_err());
+    }
+
+    #[test]
+    fn environment_sets_defaults() {
+        let options = parse_args(&["get".to_string()], |key| match key {
+            "WINCOUNT_PORT" => Some("8000".to_string()),
+            "WINCOUNT_TOKEN" => Some("t".to_string()),
+            _ => None,
+        }, None).unwrap();
+        assert_eq!((options.http_port, options.token.as_deref()), (8000, Some("t")));
+    }
+
+    #[test]
+    fn reported_ports_are_the_fallback() {
+        let reported = Some(Ports { http_port: 8777, ws_port: 8779 });
+        let options = parse_args(&["get".to_string()], |_| None, reported).unwrap();
+        assert_eq!((options.http_port, options.ws_port), (8777, 8779));
+
+        let args: Vec<String> = ["--ws-port", "9000", "get"].iter().map(|a| a.to_string()).collect();
+        let options = parse_args(&args, |key| (key == "WINCOUNT_PORT").then(|| "8000".to_string()), reported).unwrap();
+        assert_eq!((options.http_port, options.ws_port), (8000, 9000));
+    }
+}
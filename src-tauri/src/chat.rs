@@ -0,0 +1,454 @@
+// Chat commands over IRC (Twitch chat or any IRC server) so moderators can fix the
+// count while the streamer's hands are busy: "!win +1", "!win -2", "!win 5",
+// "!win reset", "!setgoal 20". Only listed users (and the channel owner) are
+// obeyed, each with a cooldown. Settings live in win_count_chat.json.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::sync::broadcast;
+
+use crate::backup::DataFile;
+use crate::integration::{self, ConfigFile, ConnectionStatus, StatusCell};
+use crate::live_events::{self, CounterAction};
+use crate::{SharedWinState, WinState};
+
+const CONFIG_FILE: &str = "win_count_chat.json";
+// The token stays out of backups
+pub const DATA_FILE: DataFile = DataFile::json::<ChatConfig>(CONFIG_FILE).with_secrets(backup_copy, restored_copy);
+pub const DEFAULT_HOST: &str = "irc.chat.twitch.tv";
+pub const DEFAULT_PORT: u16 = 6667;
+// Twitch accepts this nick family for read-only anonymous logins
+pub const ANONYMOUS_NICK: &str = "justinfan12345";
+pub const MAX_ALLOWED_USERS: usize = 100;
+const MAX_LINE_LEN: usize = 8192;
+
+static CONFIG: ConfigFile<ChatConfig> = ConfigFile::new(CONFIG_FILE, "chat settings");
+
+static STATUS: StatusCell = StatusCell::new();
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_host")]
+    pub host: String,
+    #[serde(default = "default_port")]
+    pub port: u16,
+    // Empty for an anonymous read-only login
+    #[serde(default)]
+    pub nick: String,
+    // "oauth:..." token for Twitch, or the server password
+    #[serde(default)]
+    pub password: String,
+    #[serde(default)]
+    pub channel: String,
+    #[serde(default)]
+    pub allowed_users: Vec<String>,
+    #[serde(default = "default_cooldown")]
+    pub cooldown_secs: u64,
+}
+
+fn default_host() -> String {
+    DEFAULT_HOST.to_string()
+}
+
+fn default_port() -> u16 {
+    DEFAULT_PORT
+}
+
+fn default_cooldown() -> u64 {
+    3
+}
+
+impl Default for ChatConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: default_host(),
+            port: default_port(),
+            nick: String::new(),
+            password: String::new(),
+            channel: String::new(),
+            allowed_users: Vec::new(),
+            cooldown_secs: default_cooldown(),
+        }
+    }
+}
+
+impl ChatConfig {
+    fn channel_name(&self) -> String {
+        self.channel.trim().trim_start_matches('#').to_lowercase()
+    }
+}
+
+// One line from the server, e.g. "@tags :nick!nick@host PRIVMSG #chan :!win +1"
+#[derive(Debug, PartialEq)]
+pub struct IrcLine<'a> {
+    pub nick: Option<&'a str>,
+    pub command: &'a str,
+    pub params: Vec<&'a str>,
+}
+
+pub fn parse_line(line: &str) -> Option<IrcLine<'_>> {
+    let mut rest = line.trim_end_matches(['\r', '\n']);
+    // IRCv3 tags are not needed for commands
+    if rest.starts_with('@') {
+        rest = rest.split_once(' ')?.1;
+    }
+    let mut nick = None;
+    if let Some(prefixed) = rest.strip_prefix(':') {
+        let (prefix, after) = prefixed.split_once(' ')?;
+        nick = Some(prefix.split('!').next().unwrap_or(prefix));
+        rest = after;
+    }
+    let (middle, trailing) = match rest.split_once(" :") {
+        Some((middle, trailing)) => (middle, Some(trailing)),
+        None => (rest, None),
+    };
+    let mut words = middle.split(' ').filter(|w| !w.is_empty());
+    let command = words.next()?;
+    let mut params: Vec<&str> = words.collect();
+    params.extend(trailing);
+    Some(IrcLine { nick, command, params })
+}
+
+// The counter command in a chat message: None when the message is not a command,
+// Err when it is one but malformed
+pub fn parse_command(message: &str) -> Option<Result<CounterAction, String>> {
+    let mut words = message.split_whitespace();
+    let command = words.next()?.to_lowercase();
+    let argument = words.next();
+    // Bounds are checked when the action applies
+    let number = |text: &str| text.parse::<i32>().ok().filter(|n| (-1_000_000..=1_000_000).contains(n));
+
+    let action = match (command.as_str(), argument) {
+        ("!win", Some(arg)) if arg.eq_ignore_ascii_case("reset") => Ok(CounterAction::ResetWin),
+        ("!win", Some(arg)) => match (arg.strip_prefix('+'), arg.strip_prefix('-'), number(arg)) {
+            (Some(_), _, Some(n)) if n > 0 => Ok(CounterAction::Increase { amount: n }),
+            (_, Some(_), Some(n)) if n < 0 => Ok(CounterAction::Decrease { amount: -n }),
+            (None, None, Some(n)) => Ok(CounterAction::SetWin { value: n }),
+            _ => Err(format!("Invalid win change '{}'", arg)),
+        },
+        ("!setgoal", Some(arg)) => number(arg)
+            .map(|value| CounterAction::SetGoal { value })
+            .ok_or_else(|| format!("Invalid goal '{}'", arg)),
+        ("!win", None) => Err("Usage: !win +N | -N | N | reset".to_string()),
+        ("!setgoal", None) => Err("Usage: !setgoal N".to_string()),
+        _ => return None,
+    };
+    Some(action)
+}
+
+// Who may run commands and how often
+pub struct CommandGate {
+    allowed: Vec<String>,
+    cooldown: Duration,
+    last_used: HashMap<String, Instant>,
+}
+
+impl CommandGate {
+    pub fn new(config: &ChatConfig) -> Self {
+        let mut allowed: Vec<String> = config.allowed_users.iter().map(|u| u.trim().to_lowercase()).collect();
+        // The broadcaster can always use their own commands
+        allowed.push(config.channel_name());
+        Self { allowed, cooldown: Duration::from_secs(config.cooldown_secs), last_used: HashMap::new() }
+    }
+
+    pub fn admit(&mut self, user: &str, now: Instant) -> bool {
+        let user = user.to_lowercase();
+        if !self.allowed.contains(&user) {
+            return false;
+        }
+        if let Some(last) = self.last_used.get(&user) {
+            if now.duration_since(*last) < self.cooldown {
+                return false;
+            }
+        }
+        self.last_used.insert(user, now);
+        true
+    }
+}
+
+pub fn validate(config: &ChatConfig) -> Result<(), String> {
+    if config.host.trim().is_empty() || config.host.contains(char::is_whitespace) {
+        return Err("Enter the chat server host".to_string());
+    }
+    if config.port == 0 {
+        return Err("Chat server port must be between 1 and 65535".to_string());
+    }
+    let channel = config.channel_name();
+    if config.enabled && channel.is_empty() {
+        return Err("Enter the chat channel to join".to_string());
+    }
+    let valid_name = |name: &str| name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid_name(&channel) || !valid_name(config.nick.trim()) {
+        return Err("Channel and nick may only contain letters, digits and '_'".to_string());
+    }
+    if config.allowed_users.len() > MAX_ALLOWED_USERS {
+        return Err(format!("At most {} allowed users", MAX_ALLOWED_USERS));
+    }
+    if let Some(user) = config.allowed_users.iter().find(|u| u.trim().is_empty() || !valid_name(u.trim())) {
+        return Err(format!("Invalid username '{}'", user));
+    }
+    if config.password.contains(['\r', '\n']) {
+        return Err("Password cannot contain line breaks".to_string());
+    }
+    if config.cooldown_secs > 3600 {
+        return Err("Cooldown must be at most 3600 seconds".to_string());
+    }
+    Ok(())
+}
+
+// Settings as shown in the UI, with the token masked
+pub fn config() -> ChatConfig {
+    let config = CONFIG.get();
+    ChatConfig { password: integration::mask_secret(&config.password), ..config }
+}
+
+// Save settings from the UI; a masked token keeps the stored one
+pub fn set_config(mut new_config: ChatConfig) -> Result<(), String> {
+    new_config.password = integration::unmask_secret(new_config.password, &CONFIG.get().password);
+    validate(&new_config)?;
+    CONFIG.save(new_config)
+}
+
+pub fn reload_config() {
+    CONFIG.reload();
+}
+
+// The settings file as stored in backups: the token stays on this machine
+fn backup_copy(data: &[u8]) -> Result<Vec<u8>, String> {
+    let config: ChatConfig = serde_json::from_slice(data).map_err(|e| format!("Invalid chat settings: {}", e))?;
+    serde_json::to_vec_pretty(&ChatConfig { password: String::new(), ..config })
+        .map_err(|e| format!("Failed to serialize chat settings: {}", e))
+}
+
+// A backed-up settings file about to be restored, given back the current token
+fn restored_copy(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut config: ChatConfig = serde_json::from_slice(data).map_err(|e| format!("Invalid chat settings: {}", e))?;
+    if config.password.is_empty() {
+        config.password = CONFIG.get().password;
+    }
+    serde_json::to_vec_pretty(&config)
+        .map_err(|e| format!("Failed to serialize chat settings: {}", e))
+}
+
+pub fn status() -> ConnectionStatus {
+    STATUS.get()
+}
+
+// Next line from the server without its line ending. Lines are read at most
+// MAX_LINE_LEN bytes at a time, so a server that never ends a line cannot make us
+// buffer without limit; such a line is an error and drops the connection.
+async fn read_line<R: AsyncBufRead + Unpin>(reader: &mut R, buffer: &mut Vec<u8>) -> Result<Option<String>, String> {
+    buffer.clear();
+    let read = (&mut *reader).take(MAX_LINE_LEN as u64 + 1)
+        .read_until(b'\n', buffer)
+        .await
+        .map_err(|e| format!("Chat connection lost: {}", e))?;
+    if read == 0 {
+        return Ok(None);
+    }
+    if buffer.len() > MAX_LINE_LEN {
+        return Err(format!("Chat server sent a line longer than {} bytes", MAX_LINE_LEN));
+    }
+    let line = String::from_utf8_lossy(buffer);
+    Ok(Some(line.trim_end_matches(['\r', '\n']).to_string()))
+}
+
+// One connection: log in, join the channel and hand admitted commands to `apply`
+// until the server closes the connection
+pub async fn run_session(config: &ChatConfig, mut apply: impl FnMut(&str, CounterAction)) -> Result<(), String> {
+    let address = format!("{}:{}", config.host.trim(), config.port);
+    let stream = TcpStream::connect(&address)
+        .await
+        .map_err(|e| format!("Failed to connect to {}: {}", address, e))?;
+    let (reader, mut writer) = stream.into_split();
+
+    let nick = match config.nick.trim() {
+        "" => ANONYMOUS_NICK.to_string(),
+        nick => nick.to_lowercase(),
+    };
+    let channel = config.channel_name();
+    let mut login = String::new();
+    if !config.password.is_empty() {
+        login.push_str(&format!("PASS {}\r\n", config.password));
+    }
+    login.push_str(&format!("NICK {}\r\nJOIN #{}\r\n", nick, channel));
+    writer.write_all(login.as_bytes()).await.map_err(|e| format!("Failed to log in to chat: {}", e))?;
+    println!("💬 Chat connected to {} as {}, joining #{}", address, nick, channel);
+    STATUS.set(true, None);
+
+    let mut gate = CommandGate::new(config);
+    let mut reader = BufReader::new(reader);
+    let mut buffer = Vec::new();
+    while let Some(line) = read_line(&mut reader, &mut buffer).await? {
+        let Some(message) = parse_line(&line) else {
+            continue;
+        };
+        match message.command {
+            "PING" => {
+                let pong = format!("PONG :{}\r\n", message.params.first().copied().unwrap_or_default());
+                writer.write_all(pong.as_bytes()).await.map_err(|e| format!("Chat connection lost: {}", e))?;
+            }
+            "NOTICE" if message.params.last().is_some_and(|text| text.contains("authentication failed")) => {
+                return Err("Chat login failed, check the nick and OAuth token".to_string());
+            }
+            "PRIVMSG" => {
+                let (Some(user), [target, text]) = (message.nick, message.params.as_slice()) else {
+                    continue;
+                };
+                if !target.eq_ignore_ascii_case(&format!("#{}", channel)) {
+                    continue;
+                }
+                match parse_command(text) {
+                    Some(Ok(action)) if gate.admit(user, Instant::now()) => apply(user, action),
+                    Some(Err(e)) => println!("💬 Ignored chat command from {}: {}", user, e),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+async fn supervise(app: tauri::AppHandle, state: SharedWinState, broadcast_tx: broadcast::Sender<WinState>) {
+    integration::supervise("Chat", &CONFIG, &STATUS, |config| config.enabled, |config| {
+        let (app, state, broadcast_tx) = (app.clone(), state.clone(), broadcast_tx.clone());
+        async move {
+            run_session(&config, |user, action| {
+                println!("💬 {} ran {:?}", user, action);
+                if let Err(e) = live_events::apply_action(&app, &state, &broadcast_tx, action) {
+                    println!("⚠️ Chat command from {} skipped: {}", user, e);
+                }
+            }).await
+        }
+    }).await;
+}
+
+pub fn start(app: tauri::AppHandle, state: SharedWinState, broadcast_tx: broadcast::Sender<WinState>) {
+    std::thread::spawn(move || {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(supervise(app, state, broadcast_tx));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_irc_lines() {
+        let line = parse_line("@badges=moderator/1 :mod_anna!mod_anna@mod_anna.tmi.twitch.tv PRIVMSG #streamer :!win +1\r\n").unwrap();
+        assert_eq!(line, IrcLine { nick: Some("mod_anna"), command: "PRIVMSG", params: vec!["#streamer", "!win +1"] });
+        let ping = parse_line("PING :tmi.twitch.tv").unwrap();
+        assert_eq!(ping, IrcLine { nick: None, command: "PING", params: vec!["tmi.twitch.tv"] });
+    }
+
+    #[test]
+    fn command_grammar() {
+        assert_eq!(parse_command("!win +1"), Some(Ok(CounterAction::Increase { amount: 1 })));
+        assert_eq!(parse_command("!WIN -3"), Some(Ok(CounterAction::Decrease { amount: 3 })));
+        assert_eq!(parse_command("!win 7"), Some(Ok(CounterAction::SetWin { value: 7 })));
+        assert_eq!(parse_command("!win reset"), Some(Ok(CounterAction::ResetWin)));
+        assert_eq!(parse_command("!setgoal 20 please"), Some(Ok(CounterAction::SetGoal { value: 20 })));
+        assert!(matches!(parse_command("!win +0"), Some(Err(_))));
+        assert!(matches!(parse_command("!win lots"), Some(Err(_))));
+        assert!(matches!(parse_command("!setgoal 9999999"), Some(Err(_))));
+        assert_eq!(parse_command("gg !win +1"), None);
+        assert_eq!(parse_command(""), None);
+    }
+
+    #[test]
+    fn gate_checks_users_and_cooldown() {
+        let config = ChatConfig { channel: "#Streamer".to_string(), allowed_users: vec!["Mod_Anna".to_string()], cooldown_secs: 5, ..ChatConfig::default() };
+        let mut gate = CommandGate::new(&config);
+        let now = Instant::now();
+        assert!(gate.admit("mod_anna", now));
+        assert!(!gate.admit("mod_anna", now + Duration::from_secs(2)));
+        assert!(gate.admit("mod_anna", now + Duration::from_secs(6)));
+        assert!(gate.admit("streamer", now));
+        assert!(!gate.admit("random_viewer", now));
+    }
+
+    #[tokio::test]
+    async fn reads_lines_up_to_the_limit() {
+        let long = format!("PING :{}\r\n", "x".repeat(MAX_LINE_LEN));
+        let input = format!("PING :a\r\nPING :b\n{}PING :c\r\n", long);
+        let mut reader = BufReader::new(input.as_bytes());
+        let mut buffer = Vec::new();
+        assert_eq!(read_line(&mut reader, &mut buffer).await, Ok(Some("PING :a".to_string())));
+        assert_eq!(read_line(&mut reader, &mut buffer).await, Ok(Some("PING :b".to_string())));
+        assert!(read_line(&mut reader, &mut buffer).await.is_err());
+        assert!(buffer.len() <= MAX_LINE_LEN + 1);
+
+        let mut reader = BufReader::new(&b"PING :end"[..]);
+        assert_eq!(read_line(&mut reader, &mut buffer).await, Ok(Some("PING :end".to_string())));
+        assert_eq!(read_line(&mut reader, &mut buffer).await, Ok(None));
+    }
+
+    #[test]
+    fn backups_leave_the_token_out() {
+        let config = ChatConfig { password: "oauth:secret".to_string(), channel: "streamer".to_string(), ..ChatConfig::default() };
+        let data = serde_json::to_vec(&config).unwrap();
+        let copy: ChatConfig = serde_json::from_slice(&backup_copy(&data).unwrap()).unwrap();
+        assert_eq!(copy, ChatConfig { password: String::new(), ..config });
+        assert!(backup_copy(b"not json").is_err());
+    }
+
+    #[tokio::test]
+    async fn runs_commands_from_a_local_irc_server() {
+        use tokio::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            let mut lines = BufReader::new(reader).lines();
+            let mut received = Vec::new();
+            while let Some(line) = lines.next_line().await.unwrap() {
+                let joined = line.starts_with("JOIN");
+                received.push(line);
+                if joined {
+                    break;
+                }
+            }
+            writer.write_all(b"PING :local.test\r\n").await.unwrap();
+            received.push(lines.next_line().await.unwrap().unwrap());
+            for line in [
+                ":mod!mod@local PRIVMSG #streamer :!win +2\r\n",
+                ":mod!mod@local PRIVMSG #streamer :!win +2\r\n",
+                ":viewer!viewer@local PRIVMSG #streamer :!win reset\r\n",
+                ":streamer!streamer@local PRIVMSG #streamer :!setgoal 20\r\n",
+                ":mod!mod@local PRIVMSG #other :!setgoal 1\r\n",
+            ] {
+                writer.write_all(line.as_bytes()).await.unwrap();
+            }
+            received
+        });
+
+        let config = ChatConfig {
+            enabled: true,
+            host: "127.0.0.1".to_string(),
+            port,
+            channel: "streamer".to_string(),
+            allowed_users: vec!["mod".to_string()],
+            cooldown_secs: 60,
+            ..ChatConfig::default()
+        };
+        let mut applied = Vec::new();
+        run_session(&config, |user, action| applied.push((user.to_string(), action))).await.unwrap();
+        let received = server.await.unwrap();
+
+        assert_eq!(received, vec![format!("NICK {}", ANONYMOUS_NICK), "JOIN #streamer".to_string(), "PONG :local.test".to_string()]);
+        assert_eq!(applied, vec![
+            ("mod".to_string(), CounterAction::Increase { amount: 2 }),
+            ("streamer".to_string(), CounterAction::SetGoal { value: 20 }),
+        ]);
+    }
+}
@@ -0,0 +1,78 @@
+// Where the app keeps its data, shared with wincount-cli: the per-user folder, or
+// a portable folder named by a marker file next to the executable. The running
+// app also leaves the ports of its servers there so the CLI can find them.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const APP_DIR: &str = "Win Count by ArtYWoof";
+pub const MARKER_FILE: &str = "wincount-portable.txt";
+pub const DEFAULT_DIR: &str = "data";
+// Written by the app whenever a server binds; runtime only, never backed up
+pub const PORTS_FILE: &str = "win_count_ports.json";
+
+pub const DEFAULT_HTTP_PORT: u16 = 777;
+pub const DEFAULT_WS_PORT: u16 = 779;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ports {
+    pub http_port: u16,
+    pub ws_port: u16,
+}
+
+pub fn exe_dir() -> Option<PathBuf> {
+    std::env::current_exe().ok()?.parent().map(Path::to_path_buf)
+}
+
+// Per-user folder used when not portable
+pub fn system_dir() -> Option<PathBuf> {
+    Some(dirs::data_local_dir()?.join(APP_DIR))
+}
+
+// The portable data folder if `exe_dir` holds the marker file
+pub fn portable_dir_in(exe_dir: &Path) -> Option<PathBuf> {
+    let marker = fs::read_to_string(exe_dir.join(MARKER_FILE)).ok()?;
+    let name = marker.lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .unwrap_or(DEFAULT_DIR);
+    Some(exe_dir.join(name))
+}
+
+// The data folder: `explicit` (--data-dir / WINCOUNT_DATA_DIR), then the portable
+// folder next to the executable, then the per-user folder
+pub fn resolve(explicit: Option<PathBuf>) -> Option<PathBuf> {
+    explicit
+        .or_else(|| exe_dir().and_then(|exe| portable_dir_in(&exe)))
+        .or_else(system_dir)
+}
+
+// Ports the running app last reported in `data_dir`
+pub fn read_ports(data_dir: &Path) -> Option<Ports> {
+    let json = fs::read_to_string(data_dir.join(PORTS_FILE)).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn marker_names_the_folder() {
+        let root = std::env::temp_dir().join(format!("wincount_data_dir_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        assert_eq!(portable_dir_in(&root), None);
+        fs::write(root.join(MARKER_FILE), "").unwrap();
+        assert_eq!(portable_dir_in(&root), Some(root.join("data")));
+        fs::write(root.join(MARKER_FILE), "# where the data lives\n  WinCountData \n").unwrap();
+        assert_eq!(portable_dir_in(&root), Some(root.join("WinCountData")));
+        assert_eq!(resolve(Some(root.clone())), Some(root.clone()));
+
+        assert_eq!(read_ports(&root), None);
+        fs::write(root.join(PORTS_FILE), r#"{"http_port":8777,"ws_port":8779}"#).unwrap();
+        assert_eq!(read_ports(&root), Some(Ports { http_port: 8777, ws_port: 8779 }));
+        let _ = fs::remove_dir_all(&root);
+    }
+}
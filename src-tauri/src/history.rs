@@ -0,0 +1,119 @@
+// Win history: counter values archived before they are reset
+// (scheduled resets today), stored in win_count_history.json.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::backup::DataFile;
+use crate::{get_app_data_file, write_atomic};
+
+const HISTORY_FILE: &str = "win_count_history.json";
+pub const DATA_FILE: DataFile = DataFile::json::<Vec<HistoryEntry>>(HISTORY_FILE);
+
+// Oldest entries are dropped beyond this many
+const MAX_HISTORY_ENTRIES: usize = 1000;
+
+// Serializes appends so two resets cannot overwrite each other's entry
+static HISTORY_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub preset: String,
+    pub win: i32,
+    pub goal: i32,
+    pub reason: String,
+    pub archived_at: DateTime<Utc>,
+}
+
+// A missing file is an empty history. An unparsable one is moved aside
+// (.json.corrupt) and reported so it is never overwritten.
+fn read_entries(path: &Path) -> Result<Vec<HistoryEntry>, String> {
+    let json = match fs::read_to_string(path) {
+        Ok(json) => json,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read history: {}", e)),
+    };
+    serde_json::from_str(&json).map_err(|e| {
+        let corrupt_path = path.with_extension("json.corrupt");
+        let _ = fs::rename(path, &corrupt_path);
+        format!("Failed to parse history ({}), moved to {:?}", e, corrupt_path)
+    })
+}
+
+fn append_to(path: &Path, entries: &[HistoryEntry]) -> Result<(), String> {
+    let mut history = read_entries(path)?;
+    history.extend_from_slice(entries);
+    if history.len() > MAX_HISTORY_ENTRIES {
+        let excess = history.len() - MAX_HISTORY_ENTRIES;
+        history.drain(..excess);
+    }
+
+    let json = serde_json::to_string_pretty(&history)
+        .map_err(|e| format!("Failed to serialize history: {}", e))?;
+    write_atomic(path, json.as_bytes())
+        .map_err(|e| format!("Failed to save history: {}", e))
+}
+
+// Fails without writing when the existing history cannot be read
+pub fn append(entries: &[HistoryEntry]) -> Result<(), String> {
+    let _guard = HISTORY_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    append_to(&get_app_data_file(HISTORY_FILE)?, entries)
+}
+
+// Newest first, optionally only for one preset
+pub fn list(preset: Option<&str>, limit: usize) -> Vec<HistoryEntry> {
+    let _guard = HISTORY_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let entries = get_app_data_file(HISTORY_FILE).and_then(|path| read_entries(&path)).unwrap_or_else(|e| {
+        println!("⚠️ {}", e);
+        Vec::new()
+    });
+    entries
+        .into_iter()
+        .rev()
+        .filter(|e| preset.map(|name| e.preset == name).unwrap_or(true))
+        .take(limit)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(win: i32) -> HistoryEntry {
+        HistoryEntry { preset: "Ranked".to_string(), win, goal: 10, reason: "daily".to_string(), archived_at: Utc::now() }
+    }
+
+    #[test]
+    fn appends_and_keeps_the_newest() {
+        let path = std::env::temp_dir().join(format!("wincount_history_test_append_{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+        append_to(&path, &[entry(1), entry(2)]).unwrap();
+        append_to(&path, &[entry(3)]).unwrap();
+        let wins: Vec<i32> = read_entries(&path).unwrap().iter().map(|e| e.win).collect();
+        assert_eq!(wins, [1, 2, 3]);
+
+        let many: Vec<HistoryEntry> = (0..MAX_HISTORY_ENTRIES as i32).map(entry).collect();
+        append_to(&path, &many).unwrap();
+        let history = read_entries(&path).unwrap();
+        assert_eq!((history.len(), history[0].win), (MAX_HISTORY_ENTRIES, 0));
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn corrupt_history_fails_the_archive_and_is_kept() {
+        let path = std::env::temp_dir().join(format!("wincount_history_test_corrupt_{}.json", std::process::id()));
+        let corrupt = path.with_extension("json.corrupt");
+        fs::write(&path, "[{\"preset\": ").unwrap();
+        assert!(append_to(&path, &[entry(5)]).is_err());
+        assert!(!path.exists());
+        assert_eq!(fs::read_to_string(&corrupt).unwrap(), "[{\"preset\": ");
+        // The next archive starts a fresh file next to the kept one
+        append_to(&path, &[entry(5)]).unwrap();
+        assert_eq!(read_entries(&path).unwrap().len(), 1);
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_file(&corrupt);
+    }
+}
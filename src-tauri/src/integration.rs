@@ -0,0 +1,195 @@
+// Plumbing shared by the integrations (live events, chat, OSC, webhooks): settings
+// kept in a JSON file and published on a watch channel so workers restart when they
+// change, a connection status for the UI and a reconnect loop with backoff.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::future::Future;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+use crate::{get_app_data_file, write_atomic};
+
+const MAX_BACKOFF_SECS: u64 = 60;
+
+// Shown instead of a stored secret; sending it back keeps the stored value
+pub const MASKED_SECRET: &str = "••••••••";
+
+pub fn mask_secret(secret: &str) -> String {
+    if secret.is_empty() { String::new() } else { MASKED_SECRET.to_string() }
+}
+
+// The secret to store when the UI sends `new` for a field currently holding `current`
+pub fn unmask_secret(new: String, current: &str) -> String {
+    if new == MASKED_SECRET { current.to_string() } else { new }
+}
+
+// A settings file loaded on first use; `subscribe` sees every saved change
+pub struct ConfigFile<T> {
+    file: &'static str,
+    // e.g. "chat settings", for error messages
+    what: &'static str,
+    tx: OnceLock<watch::Sender<T>>,
+}
+
+impl<T> ConfigFile<T> {
+    pub const fn new(file: &'static str, what: &'static str) -> Self {
+        Self { file, what, tx: OnceLock::new() }
+    }
+}
+
+impl<T> ConfigFile<T>
+where
+    T: Clone + Default + PartialEq + Serialize + DeserializeOwned,
+{
+    fn read(&self) -> T {
+        get_app_data_file(self.file)
+            .ok()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    fn channel(&self) -> &watch::Sender<T> {
+        self.tx.get_or_init(|| watch::channel(self.read()).0)
+    }
+
+    pub fn get(&self) -> T {
+        self.channel().borrow().clone()
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<T> {
+        self.channel().subscribe()
+    }
+
+    // Save and publish already validated settings
+    pub fn save(&self, config: T) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(&config)
+            .map_err(|e| format!("Failed to serialize {}: {}", self.what, e))?;
+        write_atomic(&get_app_data_file(self.file)?, json.as_bytes())
+            .map_err(|e| format!("Failed to save {}: {}", self.what, e))?;
+        self.channel().send_replace(config);
+        Ok(())
+    }
+
+    // Pick up the file again (after a restore); subscribers only wake if it changed
+    pub fn reload(&self) {
+        self.channel().send_if_modified(|current| {
+            let fresh = self.read();
+            let changed = *current != fresh;
+            *current = fresh;
+            changed
+        });
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectionStatus {
+    pub connected: bool,
+    pub last_error: Option<String>,
+}
+
+pub struct StatusCell(Mutex<ConnectionStatus>);
+
+impl StatusCell {
+    pub const fn new() -> Self {
+        Self(Mutex::new(ConnectionStatus { connected: false, last_error: None }))
+    }
+
+    pub fn get(&self) -> ConnectionStatus {
+        self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+    }
+
+    pub fn set(&self, connected: bool, last_error: Option<String>) {
+        *self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = ConnectionStatus { connected, last_error };
+    }
+}
+
+// Keep one connection running while `enabled` holds for the settings: `session` runs
+// until it ends, is restarted right away when the settings change and retried
+// with exponential backoff after it fails or closes
+pub async fn supervise<T, F, Fut>(name: &str, config: &ConfigFile<T>, status: &StatusCell, enabled: fn(&T) -> bool, mut session: F)
+where
+    T: Clone + Default + PartialEq + Serialize + DeserializeOwned,
+    F: FnMut(T) -> Fut,
+    Fut: Future<Output = Result<(), String>>,
+{
+    let mut config_rx = config.subscribe();
+    let mut backoff = 1;
+    loop {
+        let current = config_rx.borrow_and_update().clone();
+        if !enabled(&current) {
+            status.set(false, None);
+            if config_rx.changed().await.is_err() {
+                return;
+            }
+            continue;
+        }
+
+        let started = Instant::now();
+        let outcome = tokio::select! {
+            result = session(current) => Some(result),
+            _ = config_rx.changed() => None,
+        };
+        match outcome {
+            None => {
+                println!("🔄 {} settings changed, reconnecting", name);
+                backoff = 1;
+                continue;
+            }
+            Some(Ok(())) => status.set(false, Some("Connection closed".to_string())),
+            Some(Err(e)) => {
+                println!("⚠️ {}", e);
+                status.set(false, Some(e));
+            }
+        }
+
+        // A connection that stayed up for a while starts the backoff over
+        if started.elapsed() > Duration::from_secs(MAX_BACKOFF_SECS) {
+            backoff = 1;
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_secs(backoff)) => {}
+            _ = config_rx.changed() => {}
+        }
+        backoff = (backoff * 2).min(MAX_BACKOFF_SECS);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn masked_secrets_round_trip() {
+        assert_eq!(mask_secret(""), "");
+        assert_eq!(mask_secret("oauth:abc"), MASKED_SECRET);
+        assert_eq!(unmask_secret(MASKED_SECRET.to_string(), "oauth:abc"), "oauth:abc");
+        assert_eq!(unmask_secret("oauth:new".to_string(), "oauth:abc"), "oauth:new");
+        assert_eq!(unmask_secret(String::new(), "oauth:abc"), "");
+    }
+
+    #[tokio::test]
+    async fn restarts_sessions_on_changes() {
+        static CONFIG: ConfigFile<u32> = ConfigFile::new("wincount_integration_test.json", "test settings");
+        static STATUS: StatusCell = StatusCell::new();
+        CONFIG.channel().send_replace(1);
+
+        let (started_tx, mut started_rx) = tokio::sync::mpsc::unbounded_channel();
+        let supervisor = tokio::spawn(supervise("Test", &CONFIG, &STATUS, |value| *value > 0, move |value| {
+            let _ = started_tx.send(value);
+            std::future::pending()
+        }));
+        assert_eq!(started_rx.recv().await, Some(1));
+        CONFIG.channel().send_replace(2);
+        assert_eq!(started_rx.recv().await, Some(2));
+        // Disabled: nothing runs and the status clears
+        CONFIG.channel().send_replace(0);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(started_rx.try_recv().is_err());
+        assert!(!STATUS.get().connected);
+        supervisor.abort();
+    }
+}
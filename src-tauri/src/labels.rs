@@ -0,0 +1,176 @@
+// Text templates for overlay labels, e.g. "{win}/{goal} 👑" or "Wins: {win} ({remaining} to go)".
+// Templates are stored per preset and rendered here so overlays and plain text
+// sources get finished strings in the WebSocket payload and from /api/state.
+
+use std::collections::BTreeMap;
+
+use crate::{presets, WinState};
+
+pub const MAX_LABELS: usize = 20;
+pub const MAX_TEMPLATE_LEN: usize = 200;
+
+pub const PLACEHOLDERS: [&str; 5] = ["win", "goal", "remaining", "percent", "preset"];
+
+// Win as a percentage of the goal, 0 when there is no positive goal
+fn percent(state: &WinState) -> i64 {
+    if state.goal <= 0 {
+        return 0;
+    }
+    (state.win as f64 * 100.0 / state.goal as f64).round() as i64
+}
+
+fn value(name: &str, state: &WinState) -> Option<String> {
+    Some(match name {
+        "win" => state.win.to_string(),
+        "goal" => state.goal.to_string(),
+        "remaining" => (state.goal - state.win).max(0).to_string(),
+        "percent" => percent(state).to_string(),
+        "preset" => state.current_preset.clone(),
+        _ => return None,
+    })
+}
+
+enum Part<'a> {
+    Text(&'a str),
+    Placeholder(&'a str),
+}
+
+// Split a template into literal text and placeholder names. "{{" and "}}" are literal braces.
+fn parse(template: &str) -> Result<Vec<Part<'_>>, String> {
+    let mut parts = Vec::new();
+    let mut rest = template;
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix("{{") {
+            parts.push(Part::Text("{"));
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("}}") {
+            parts.push(Part::Text("}"));
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix('{') {
+            let end = after.find('}').ok_or_else(|| "Unclosed '{' in label template".to_string())?;
+            parts.push(Part::Placeholder(after[..end].trim()));
+            rest = &after[end + 1..];
+        } else if rest.starts_with('}') {
+            return Err("Unmatched '}' in label template, use '}}' for a literal brace".to_string());
+        } else {
+            let end = rest.find(['{', '}']).unwrap_or(rest.len());
+            parts.push(Part::Text(&rest[..end]));
+            rest = &rest[end..];
+        }
+    }
+    Ok(parts)
+}
+
+pub fn validate(template: &str) -> Result<(), String> {
+    if template.chars().count() > MAX_TEMPLATE_LEN {
+        return Err(format!("Label template is too long (max {} characters)", MAX_TEMPLATE_LEN));
+    }
+    validate_placeholders(template, &[])
+}
+
+// Check that every placeholder is a known one or one of `extra`
+pub fn validate_placeholders(template: &str, extra: &[&str]) -> Result<(), String> {
+    for part in parse(template)? {
+        if let Part::Placeholder(name) = part {
+            if !PLACEHOLDERS.contains(&name) && !extra.contains(&name) {
+                let known: Vec<String> = PLACEHOLDERS.iter().chain(extra).map(|p| format!("{{{}}}", p)).collect();
+                return Err(format!("Unknown placeholder '{{{}}}', use one of: {}", name, known.join(", ")));
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn validate_all(labels: &BTreeMap<String, String>) -> Result<(), String> {
+    if labels.len() > MAX_LABELS {
+        return Err(format!("A preset can have at most {} labels", MAX_LABELS));
+    }
+    for (name, template) in labels {
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err(format!("Invalid label name '{}': use letters, digits, '-' and '_'", name));
+        }
+        validate(template).map_err(|e| format!("{}: {}", name, e))?;
+    }
+    Ok(())
+}
+
+pub fn render(template: &str, state: &WinState) -> String {
+    render_with(template, state, &[])
+}
+
+// Render with additional placeholders, e.g. {event} in webhook templates
+pub fn render_with(template: &str, state: &WinState, extra: &[(&str, &str)]) -> String {
+    // Stored templates are validated; show a broken one as written rather than half rendered
+    let Ok(parts) = parse(template) else {
+        return template.to_string();
+    };
+    parts.into_iter()
+        .map(|part| match part {
+            Part::Text(text) => text.to_string(),
+            Part::Placeholder(name) => extra.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, v)| v.to_string())
+                .or_else(|| value(name, state))
+                .unwrap_or_default(),
+        })
+        .collect()
+}
+
+// Rendered labels of the state's active preset
+pub fn labels_for(state: &WinState) -> BTreeMap<String, String> {
+    let templates = presets::store()
+        .get(&state.current_preset)
+        .map(|p| p.labels.clone())
+        .unwrap_or_default();
+    templates.iter()
+        .map(|(name, template)| (name.clone(), render(template, state)))
+        .collect()
+}
+
+// State as sent to overlays and /api/state: the WinState fields plus "labels"
+pub fn state_json(state: &WinState) -> String {
+    let mut payload = serde_json::to_value(state).unwrap_or_default();
+    if let Some(object) = payload.as_object_mut() {
+        object.insert("labels".to_string(), serde_json::json!(labels_for(state)));
+    }
+    payload.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_placeholders() {
+        let s = WinState::sample(7, 10);
+        assert_eq!(render("{win}/{goal} 👑", &s), "7/10 👑");
+        assert_eq!(render("Wins: {win} ({remaining} to go)", &s), "Wins: 7 (3 to go)");
+        assert_eq!(render("{ preset }: {percent}%", &s), "Ranked: 70%");
+        assert_eq!(render("{{win}} = {win}", &s), "{win} = 7");
+    }
+
+    #[test]
+    fn derived_values_stay_sane() {
+        assert_eq!(render("{remaining}", &WinState::sample(12, 10)), "0");
+        assert_eq!(render("{percent}", &WinState::sample(5, 0)), "0");
+        assert_eq!(render("{percent}", &WinState::sample(-5, 10)), "-50");
+        assert_eq!(render("{percent}", &WinState::sample(1, 3)), "33");
+    }
+
+    #[test]
+    fn validation_catches_mistakes() {
+        assert!(validate("{win}/{goal}").is_ok());
+        assert!(validate("{wins}").is_err());
+        // Losses are not tracked, so there is no win rate
+        assert!(validate("{winrate}%").is_err());
+        assert!(validate("{win").is_err());
+        assert!(validate("win}").is_err());
+        assert!(validate(&"x".repeat(MAX_TEMPLATE_LEN + 1)).is_err());
+
+        let mut labels = BTreeMap::new();
+        labels.insert("main".to_string(), "{win}".to_string());
+        assert!(validate_all(&labels).is_ok());
+        labels.insert("bad name".to_string(), "{win}".to_string());
+        assert!(validate_all(&labels).is_err());
+    }
+}
@@ -0,0 +1,266 @@
+// Command-line arguments of a launch, e.g. `--preset "Ranked"` or `--inc 1`.
+// They apply at startup, and a second launch forwards them to the running
+// instance (through the single-instance plugin) instead of starting another one.
+// Startup-only options (ports, data folder, window mode) are read once in `run()`
+// from flags or WINCOUNT_* environment variables; flags win over the environment,
+// and both win over settings.json for this run.
+
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use tauri::Manager;
+use tokio::sync::broadcast;
+
+use crate::live_events::{self, CounterAction};
+use crate::{SharedWinState, WinState};
+
+#[derive(Debug, Default, PartialEq)]
+pub struct LaunchArgs {
+    pub preset: Option<String>,
+    pub inc: Option<i32>,
+    pub dec: Option<i32>,
+}
+
+impl LaunchArgs {
+    pub fn is_empty(&self) -> bool {
+        *self == LaunchArgs::default()
+    }
+}
+
+fn step(flag: &str, value: Option<String>) -> Result<i32, String> {
+    let value = value.ok_or_else(|| format!("Missing value for {}", flag))?;
+    value.trim().parse::<i32>()
+        .ok()
+        .filter(|n| (1..=10000).contains(n))
+        .ok_or_else(|| format!("Invalid value for {}: '{}' (1-10000)", flag, value))
+}
+
+// Parse the arguments after the program name. Unknown arguments are skipped:
+// the OS and installers sometimes add their own.
+pub fn parse(args: &[String]) -> Result<LaunchArgs, String> {
+    let mut parsed = LaunchArgs::default();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        // Accept both "--flag value" and "--flag=value"
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+        let mut value = || inline.clone().or_else(|| iter.next().cloned());
+        match flag {
+            "--preset" => {
+                let name = value().filter(|n| !n.trim().is_empty()).ok_or("Missing value for --preset")?;
+                parsed.preset = Some(name);
+            }
+            "--inc" => parsed.inc = Some(step(flag, value())?),
+            "--dec" => parsed.dec = Some(step(flag, value())?),
+            _ => {}
+        }
+    }
+    Ok(parsed)
+}
+
+static STARTUP: OnceLock<StartupOptions> = OnceLock::new();
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StartupOptions {
+    // Start hidden in the tray
+    pub minimized: bool,
+    // Server-only: the window is never shown, only the tray's Quit is offered
+    pub headless: bool,
+    pub no_hotkeys: bool,
+    // Override the ports from settings.json for this run
+    pub http_port: Option<u16>,
+    pub ws_port: Option<u16>,
+    // Replaces the per-user data folder, e.g. for a portable install
+    pub data_dir: Option<PathBuf>,
+    // WINCOUNT_PRESET; --preset itself is a launch argument
+    pub preset: Option<String>,
+}
+
+fn port(what: &str, value: Option<String>) -> Result<u16, String> {
+    let value = value.ok_or_else(|| format!("Missing value for {}", what))?;
+    value.trim().parse::<u16>()
+        .ok()
+        .filter(|p| *p > 0)
+        .ok_or_else(|| format!("Invalid port for {}: '{}'", what, value))
+}
+
+fn is_truthy(value: &str) -> bool {
+    matches!(value.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes" | "on")
+}
+
+// Parse startup options from the arguments after the program name and the environment.
+// Launch arguments (--preset, --inc, --dec) and unknown arguments are left to `parse`.
+pub fn parse_startup(args: &[String], env: impl Fn(&str) -> Option<String>) -> Result<StartupOptions, String> {
+    let mut options = StartupOptions::default();
+    let flag_env = |key: &str| env(key).is_some_and(|v| is_truthy(&v));
+    options.minimized = flag_env("WINCOUNT_MINIMIZED");
+    options.headless = flag_env("WINCOUNT_HEADLESS");
+    options.no_hotkeys = flag_env("WINCOUNT_NO_HOTKEYS");
+    if let Some(value) = env("WINCOUNT_PORT") {
+        options.http_port = Some(port("WINCOUNT_PORT", Some(value))?);
+    }
+    if let Some(value) = env("WINCOUNT_WS_PORT") {
+        options.ws_port = Some(port("WINCOUNT_WS_PORT", Some(value))?);
+    }
+    options.data_dir = env("WINCOUNT_DATA_DIR").filter(|d| !d.trim().is_empty()).map(PathBuf::from);
+    options.preset = env("WINCOUNT_PRESET").filter(|n| !n.trim().is_empty());
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+        let mut value = || inline.clone().or_else(|| iter.next().cloned());
+        match flag {
+            "--minimized" => options.minimized = true,
+            "--headless" => options.headless = true,
+            "--no-hotkeys" => options.no_hotkeys = true,
+            "--port" => options.http_port = Some(port(flag, value())?),
+            "--ws-port" => options.ws_port = Some(port(flag, value())?),
+            "--data-dir" => {
+                let dir = value().filter(|d| !d.trim().is_empty()).ok_or("Missing value for --data-dir")?;
+                options.data_dir = Some(PathBuf::from(dir));
+            }
+            // Skip the value so a preset named like a flag is not misread
+            "--preset" | "--inc" | "--dec" if inline.is_none() => {
+                iter.next();
+            }
+            _ => {}
+        }
+    }
+    if let (Some(http), Some(ws)) = (options.http_port, options.ws_port) {
+        if http == ws {
+            return Err(format!("The HTTP and WebSocket ports must differ (both {})", http));
+        }
+    }
+    Ok(options)
+}
+
+// Read the startup options of this process; call first thing in `run()`
+pub fn init_startup() -> &'static StartupOptions {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let options = parse_startup(&args, |key| std::env::var(key).ok()).unwrap_or_else(|e| {
+        println!("⚠️ Ignoring startup options: {}", e);
+        StartupOptions::default()
+    });
+    let port_text = |p: Option<u16>| p.map(|p| p.to_string()).unwrap_or_else(|| "settings".to_string());
+    println!(
+        "🚀 Startup: ports {}/{}, minimized={}, headless={}, hotkeys={}, data dir {}",
+        port_text(options.http_port),
+        port_text(options.ws_port),
+        options.minimized,
+        options.headless,
+        !options.no_hotkeys,
+        options.data_dir.as_ref().map(|d| d.display().to_string()).unwrap_or_else(|| "default".to_string()),
+    );
+    STARTUP.get_or_init(|| options)
+}
+
+// Startup options of this process (defaults until `init_startup` runs)
+pub fn startup() -> &'static StartupOptions {
+    STARTUP.get_or_init(StartupOptions::default)
+}
+
+// Apply launch arguments to the running app: switch preset first, then change the count
+pub fn apply(app: &tauri::AppHandle, args: &LaunchArgs) {
+    let state = app.state::<SharedWinState>();
+    let broadcast_tx = app.state::<broadcast::Sender<WinState>>();
+    if let Some(name) = &args.preset {
+        match crate::load_preset_by_name(app, &state, &broadcast_tx, name) {
+            Ok(_) => println!("🚀 Launch argument: loaded preset '{}'", name),
+            Err(e) => println!("⚠️ Launch argument --preset: {}", e),
+        }
+    }
+    if let Some(amount) = args.inc {
+        if let Err(e) = live_events::apply_action(app, &state, &broadcast_tx, CounterAction::Increase { amount }) {
+            println!("⚠️ Launch argument --inc: {}", e);
+        }
+    }
+    if let Some(amount) = args.dec {
+        if let Err(e) = live_events::apply_action(app, &state, &broadcast_tx, CounterAction::Decrease { amount }) {
+            println!("⚠️ Launch argument --dec: {}", e);
+        }
+    }
+}
+
+// Called in the running instance when the app is launched again
+pub fn on_second_instance(app: &tauri::AppHandle, argv: Vec<String>) {
+    println!("🔁 Second launch detected, focusing the running window");
+    if startup().headless {
+        println!("🖥️ Headless mode, keeping the window hidden");
+    } else if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+    match parse(argv.get(1..).unwrap_or_default()) {
+        Ok(args) if !args.is_empty() => apply(app, &args),
+        Ok(_) => {}
+        Err(e) => println!("⚠️ Ignoring forwarded arguments: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_all(args: &[&str]) -> Result<LaunchArgs, String> {
+        parse(&args.iter().map(|a| a.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn parses_flags() {
+        assert_eq!(
+            parse_all(&["--preset", "Ranked Solo", "--inc", "1"]).unwrap(),
+            LaunchArgs { preset: Some("Ranked Solo".to_string()), inc: Some(1), dec: None }
+        );
+        assert_eq!(parse_all(&["--dec=2", "--preset=Casual"]).unwrap(), LaunchArgs { preset: Some("Casual".to_string()), inc: None, dec: Some(2) });
+        assert!(parse_all(&["-psn_0_12345"]).unwrap().is_empty());
+        assert!(parse_all(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_bad_values() {
+        assert!(parse_all(&["--inc"]).is_err());
+        assert!(parse_all(&["--inc", "0"]).is_err());
+        assert!(parse_all(&["--dec", "lots"]).is_err());
+        assert!(parse_all(&["--preset", ""]).is_err());
+    }
+
+    fn startup_from(args: &[&str], env: &[(&str, &str)]) -> Result<StartupOptions, String> {
+        let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        parse_startup(&args, |key| env.iter().find(|(k, _)| *k == key).map(|(_, v)| v.to_string()))
+    }
+
+    #[test]
+    fn parses_startup_options() {
+        assert_eq!(startup_from(&[], &[]).unwrap(), StartupOptions::default());
+        let options = startup_from(&["--minimized", "--port=8777", "--ws-port", "8779", "--preset", "--headless", "--no-hotkeys"], &[]).unwrap();
+        assert!(options.minimized && options.no_hotkeys && !options.headless);
+        assert_eq!((options.http_port, options.ws_port), (Some(8777), Some(8779)));
+
+        let options = startup_from(&["--data-dir", "D:/WinCount/data", "--inc", "1"], &[]).unwrap();
+        assert_eq!(options.data_dir, Some(PathBuf::from("D:/WinCount/data")));
+        // The launch arguments next to them still parse
+        assert_eq!(parse_all(&["--data-dir", "D:/WinCount/data", "--inc", "1"]).unwrap().inc, Some(1));
+    }
+
+    #[test]
+    fn environment_is_overridden_by_flags() {
+        let env = [("WINCOUNT_HEADLESS", "1"), ("WINCOUNT_PORT", "9777"), ("WINCOUNT_PRESET", "Ranked"), ("WINCOUNT_NO_HOTKEYS", "no")];
+        let options = startup_from(&["--port", "9778"], &env).unwrap();
+        assert!(options.headless && !options.no_hotkeys);
+        assert_eq!((options.http_port, options.preset.as_deref()), (Some(9778), Some("Ranked")));
+    }
+
+    #[test]
+    fn rejects_bad_startup_options() {
+        assert!(startup_from(&["--port", "0"], &[]).is_err());
+        assert!(startup_from(&["--ws-port", "70000"], &[]).is_err());
+        assert!(startup_from(&["--port", "8000", "--ws-port=8000"], &[]).is_err());
+        assert!(startup_from(&["--data-dir"], &[]).is_err());
+        assert!(startup_from(&[], &[("WINCOUNT_WS_PORT", "x")]).is_err());
+    }
+}
@@ -0,0 +1,2 @@
+// Code shared by the app and the wincount-cli binary
+pub mod data_dir;
@@ -0,0 +1,463 @@
+// TikTok LIVE events (gifts, likes, follows, chat) turned into counter actions.
+// Events come from a pluggable source; the built-in one reads the local WebSocket
+// JSON feed of TikFinity or a tiktok-live-connector forwarder. A rule table in
+// win_count_live_events.json maps events to actions on the win count.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::future::Future;
+use std::pin::Pin;
+use tokio::sync::{broadcast, mpsc};
+
+use crate::backup::DataFile;
+use crate::integration::{self, ConfigFile, ConnectionStatus, StatusCell};
+use crate::{SharedWinState, WinState};
+
+const CONFIG_FILE: &str = "win_count_live_events.json";
+pub const DATA_FILE: DataFile = DataFile::json::<LiveEventsConfig>(CONFIG_FILE);
+pub const DEFAULT_URL: &str = "ws://127.0.0.1:21213/";
+pub const MAX_RULES: usize = 50;
+
+// Config changes restart the running source
+static CONFIG: ConfigFile<LiveEventsConfig> = ConfigFile::new(CONFIG_FILE, "live event settings");
+
+static STATUS: StatusCell = StatusCell::new();
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LiveEvent {
+    Gift { user: String, gift: String, count: u32 },
+    Like { user: String, count: u32 },
+    Follow { user: String },
+    Chat { user: String, comment: String },
+}
+
+pub type SourceFuture = Pin<Box<dyn Future<Output = Result<(), String>> + Send>>;
+
+// A stream of live events. `run` sends events until the stream ends or fails;
+// dropping the future disconnects.
+pub trait LiveEventSource: Send {
+    fn describe(&self) -> String;
+    fn run(self: Box<Self>, events: mpsc::UnboundedSender<LiveEvent>) -> SourceFuture;
+}
+
+// Local WebSocket carrying one JSON event per message (TikFinity, tiktok-live-connector forwarders)
+pub struct WebSocketJsonSource {
+    pub url: String,
+}
+
+impl LiveEventSource for WebSocketJsonSource {
+    fn describe(&self) -> String {
+        self.url.clone()
+    }
+
+    fn run(self: Box<Self>, events: mpsc::UnboundedSender<LiveEvent>) -> SourceFuture {
+        Box::pin(async move {
+            use futures_util::StreamExt;
+            use tokio_tungstenite::tungstenite::Message;
+
+            let (mut ws, _) = tokio_tungstenite::connect_async(self.url.as_str())
+                .await
+                .map_err(|e| format!("Failed to connect to {}: {}", self.url, e))?;
+            println!("🎁 Live events connected to {}", self.url);
+            STATUS.set(true, None);
+
+            while let Some(message) = ws.next().await {
+                let text = match message {
+                    Ok(Message::Text(text)) => text,
+                    Ok(Message::Close(_)) => break,
+                    Ok(_) => continue,
+                    Err(e) => return Err(format!("Live event connection lost: {}", e)),
+                };
+                if let Some(event) = parse_event(&text) {
+                    if events.send(event).is_err() {
+                        break;
+                    }
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+fn str_field<'a>(data: &'a Value, key: &str) -> Option<&'a str> {
+    data.get(key).and_then(Value::as_str)
+}
+
+fn u32_field(data: &Value, key: &str) -> Option<u32> {
+    let value = data.get(key)?;
+    value.as_u64()
+        .or_else(|| value.as_str().and_then(|s| s.trim().parse().ok()))
+        .map(|n| n.min(u32::MAX as u64) as u32)
+}
+
+// Parse one message of the TikFinity / tiktok-live-connector JSON format:
+// {"event": "gift", "data": {...}} or the same fields flat with a "type" key.
+// Unknown events and unfinished gift streaks give None.
+pub fn parse_event(text: &str) -> Option<LiveEvent> {
+    let root: Value = serde_json::from_str(text).ok()?;
+    let kind = str_field(&root, "event").or_else(|| str_field(&root, "type"))?.to_ascii_lowercase();
+    let data = root.get("data").filter(|d| d.is_object()).unwrap_or(&root);
+    let user = str_field(data, "uniqueId")
+        .or_else(|| data.get("user").and_then(|u| str_field(u, "uniqueId")))
+        .or_else(|| str_field(data, "nickname"))
+        .unwrap_or_default()
+        .to_string();
+
+    match kind.as_str() {
+        "gift" => {
+            // Streakable gifts (giftType 1) send updates while the combo runs; only the final one counts
+            let streaking = data.get("giftType").and_then(Value::as_u64) == Some(1)
+                && data.get("repeatEnd").and_then(Value::as_bool) == Some(false);
+            if streaking {
+                return None;
+            }
+            let gift = str_field(data, "giftName")
+                .or_else(|| data.get("extendedGiftInfo").and_then(|g| str_field(g, "name")))?
+                .to_string();
+            let count = u32_field(data, "repeatCount").unwrap_or(1).max(1);
+            Some(LiveEvent::Gift { user, gift, count })
+        }
+        "like" => Some(LiveEvent::Like { user, count: u32_field(data, "likeCount").unwrap_or(1).max(1) }),
+        "follow" => Some(LiveEvent::Follow { user }),
+        // Older connector versions report follows as a "social" event
+        "social" => str_field(data, "displayType")
+            .filter(|t| t.contains("follow"))
+            .map(|_| LiveEvent::Follow { user }),
+        "chat" => Some(LiveEvent::Chat { user, comment: str_field(data, "comment")?.to_string() }),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Trigger {
+    // Every `count` gifts with this name ("*" for any gift)
+    Gift { gift: String, #[serde(default = "default_every")] count: u32 },
+    Follow,
+    // Every `every` likes
+    LikeThreshold { every: u32 },
+    // Chat message containing the keyword (case-insensitive)
+    ChatKeyword { keyword: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CounterAction {
+    Increase { amount: i32 },
+    Decrease { amount: i32 },
+    ResetWin,
+    SetWin { value: i32 },
+    SetGoal { value: i32 },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LiveRule {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub trigger: Trigger,
+    pub action: CounterAction,
+}
+
+fn default_every() -> u32 {
+    1
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_url() -> String {
+    DEFAULT_URL.to_string()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LiveEventsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_url")]
+    pub url: String,
+    #[serde(default)]
+    pub rules: Vec<LiveRule>,
+}
+
+impl Default for LiveEventsConfig {
+    fn default() -> Self {
+        Self { enabled: false, url: default_url(), rules: Vec::new() }
+    }
+}
+
+impl CounterAction {
+    // The action for `times` triggers at once: amounts add up, the others apply once
+    fn repeated(&self, times: u32) -> CounterAction {
+        let times = times.min(10000) as i32;
+        match self {
+            CounterAction::Increase { amount } => CounterAction::Increase { amount: amount.saturating_mul(times) },
+            CounterAction::Decrease { amount } => CounterAction::Decrease { amount: amount.saturating_mul(times) },
+            other => other.clone(),
+        }
+    }
+}
+
+// Matches events against the rules. Gift and like rules keep a running total so
+// e.g. "every 5 roses" also fires for five single roses.
+pub struct RuleEngine {
+    rules: Vec<LiveRule>,
+    totals: Vec<u64>,
+}
+
+impl RuleEngine {
+    pub fn new(rules: Vec<LiveRule>) -> Self {
+        let totals = vec![0; rules.len()];
+        Self { rules, totals }
+    }
+
+    pub fn handle(&mut self, event: &LiveEvent) -> Vec<CounterAction> {
+        let mut actions = Vec::new();
+        for (rule, total) in self.rules.iter().zip(self.totals.iter_mut()) {
+            if !rule.enabled {
+                continue;
+            }
+            let (amount, every) = match (&rule.trigger, event) {
+                (Trigger::Gift { gift, count: every }, LiveEvent::Gift { gift: name, count, .. })
+                    if gift == "*" || gift.eq_ignore_ascii_case(name) => (*count, *every),
+                (Trigger::LikeThreshold { every }, LiveEvent::Like { count, .. }) => (*count, *every),
+                (Trigger::Follow, LiveEvent::Follow { .. }) => (1, 1),
+                (Trigger::ChatKeyword { keyword }, LiveEvent::Chat { comment, .. })
+                    if comment.to_lowercase().contains(&keyword.to_lowercase()) => (1, 1),
+                _ => continue,
+            };
+            let every = every.max(1) as u64;
+            *total += amount as u64;
+            let times = *total / every;
+            *total %= every;
+            if times > 0 {
+                actions.push(rule.action.repeated(times.min(u32::MAX as u64) as u32));
+            }
+        }
+        actions
+    }
+}
+
+pub fn validate(config: &LiveEventsConfig) -> Result<(), String> {
+    if !config.url.starts_with("ws://") && !config.url.starts_with("wss://") {
+        return Err("Live event URL must start with ws:// or wss://".to_string());
+    }
+    if config.rules.len() > MAX_RULES {
+        return Err(format!("At most {} live event rules are allowed", MAX_RULES));
+    }
+    for (i, rule) in config.rules.iter().enumerate() {
+        let n = i + 1;
+        match &rule.trigger {
+            Trigger::Gift { gift, count } => {
+                if gift.trim().is_empty() {
+                    return Err(format!("Rule {}: gift name cannot be empty (use * for any gift)", n));
+                }
+                if *count == 0 {
+                    return Err(format!("Rule {}: gift count must be at least 1", n));
+                }
+            }
+            Trigger::LikeThreshold { every } if *every == 0 => {
+                return Err(format!("Rule {}: like threshold must be at least 1", n));
+            }
+            Trigger::ChatKeyword { keyword } if keyword.trim().is_empty() => {
+                return Err(format!("Rule {}: chat keyword cannot be empty", n));
+            }
+            _ => {}
+        }
+        match rule.action {
+            // Resulting values are checked against the preset's bounds when the rule fires
+            CounterAction::Increase { amount } | CounterAction::Decrease { amount } if amount < 1 => {
+                return Err(format!("Rule {}: amount must be at least 1", n));
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+pub fn config() -> LiveEventsConfig {
+    CONFIG.get()
+}
+
+pub fn set_config(new_config: LiveEventsConfig) -> Result<(), String> {
+    validate(&new_config)?;
+    CONFIG.save(new_config)
+}
+
+pub fn reload_config() {
+    CONFIG.reload();
+}
+
+pub fn status() -> ConnectionStatus {
+    STATUS.get()
+}
+
+// Run one source until it ends, handing the actions of its events to `apply`
+pub async fn drive(source: Box<dyn LiveEventSource>, engine: &mut RuleEngine, mut apply: impl FnMut(CounterAction)) -> Result<(), String> {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let run = source.run(tx);
+    tokio::pin!(run);
+    let mut result = None;
+    loop {
+        tokio::select! {
+            event = rx.recv() => match event {
+                Some(event) => engine.handle(&event).into_iter().for_each(&mut apply),
+                // The source finished and every event it sent has been handled
+                None => break,
+            },
+            finished = &mut run, if result.is_none() => result = Some(finished),
+        }
+    }
+    result.unwrap_or(Ok(()))
+}
+
+// Apply an action to the shared state; also used by the chat connector.
+// Fails when the result would leave the active preset's bounds.
+pub(crate) fn apply_action(app: &tauri::AppHandle, state: &SharedWinState, broadcast_tx: &broadcast::Sender<WinState>, action: CounterAction) -> Result<(), String> {
+    match action {
+        CounterAction::Increase { amount } => crate::change_win_with_step(app, state, broadcast_tx, 1, amount)?,
+        CounterAction::Decrease { amount } => crate::change_win_with_step(app, state, broadcast_tx, -1, amount)?,
+        CounterAction::ResetWin => crate::set_win_value(app, state, broadcast_tx, 0)?,
+        CounterAction::SetWin { value } => crate::set_win_value(app, state, broadcast_tx, value)?,
+        CounterAction::SetGoal { value } => crate::set_goal_value(app, state, broadcast_tx, value)?,
+    };
+    Ok(())
+}
+
+async fn supervise(app: tauri::AppHandle, state: SharedWinState, broadcast_tx: broadcast::Sender<WinState>) {
+    integration::supervise("Live event", &CONFIG, &STATUS, |config| config.enabled, |config| {
+        let (app, state, broadcast_tx) = (app.clone(), state.clone(), broadcast_tx.clone());
+        async move {
+            let mut engine = RuleEngine::new(config.rules.clone());
+            let source: Box<dyn LiveEventSource> = Box::new(WebSocketJsonSource { url: config.url.clone() });
+            drive(source, &mut engine, |action| {
+                println!("🎁 Live event action: {:?}", action);
+                if let Err(e) = apply_action(&app, &state, &broadcast_tx, action) {
+                    println!("⚠️ Live event action skipped: {}", e);
+                }
+            }).await
+        }
+    }).await;
+}
+
+pub fn start(app: tauri::AppHandle, state: SharedWinState, broadcast_tx: broadcast::Sender<WinState>) {
+    std::thread::spawn(move || {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(supervise(app, state, broadcast_tx));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(trigger: Trigger, action: CounterAction) -> LiveRule {
+        LiveRule { enabled: true, trigger, action }
+    }
+
+    fn gift(name: &str, count: u32) -> LiveEvent {
+        LiveEvent::Gift { user: "viewer".to_string(), gift: name.to_string(), count }
+    }
+
+    #[test]
+    fn parses_tikfinity_and_connector_messages() {
+        assert_eq!(
+            parse_event(r#"{"event":"gift","data":{"uniqueId":"anna","giftName":"Rose","giftType":1,"repeatCount":5,"repeatEnd":true}}"#),
+            Some(LiveEvent::Gift { user: "anna".to_string(), gift: "Rose".to_string(), count: 5 })
+        );
+        // Streak still running
+        assert_eq!(parse_event(r#"{"event":"gift","data":{"giftName":"Rose","giftType":1,"repeatCount":3,"repeatEnd":false}}"#), None);
+        assert_eq!(
+            parse_event(r#"{"type":"like","user":{"uniqueId":"bo"},"likeCount":"15"}"#),
+            Some(LiveEvent::Like { user: "bo".to_string(), count: 15 })
+        );
+        assert_eq!(
+            parse_event(r#"{"event":"social","data":{"uniqueId":"cy","displayType":"pm_mt_msg_viewer_follow"}}"#),
+            Some(LiveEvent::Follow { user: "cy".to_string() })
+        );
+        assert_eq!(parse_event(r#"{"event":"roomUser","data":{"viewerCount":10}}"#), None);
+        assert_eq!(parse_event("not json"), None);
+    }
+
+    #[test]
+    fn gift_rules_accumulate() {
+        let mut engine = RuleEngine::new(vec![
+            rule(Trigger::Gift { gift: "rose".to_string(), count: 5 }, CounterAction::Increase { amount: 1 }),
+            rule(Trigger::Gift { gift: "*".to_string(), count: 1 }, CounterAction::Increase { amount: 2 }),
+        ]);
+        assert_eq!(engine.handle(&gift("Rose", 3)), vec![CounterAction::Increase { amount: 6 }]);
+        assert_eq!(
+            engine.handle(&gift("Rose", 12)),
+            vec![CounterAction::Increase { amount: 3 }, CounterAction::Increase { amount: 24 }]
+        );
+        assert_eq!(engine.handle(&gift("Lion", 1)), vec![CounterAction::Increase { amount: 2 }]);
+    }
+
+    #[test]
+    fn like_follow_and_chat_rules() {
+        let mut engine = RuleEngine::new(vec![
+            rule(Trigger::LikeThreshold { every: 100 }, CounterAction::Increase { amount: 1 }),
+            rule(Trigger::Follow, CounterAction::SetGoal { value: 20 }),
+            rule(Trigger::ChatKeyword { keyword: "!reset".to_string() }, CounterAction::ResetWin),
+            LiveRule { enabled: false, ..rule(Trigger::Follow, CounterAction::Decrease { amount: 1 }) },
+        ]);
+        let like = |count| LiveEvent::Like { user: "u".to_string(), count };
+        assert!(engine.handle(&like(60)).is_empty());
+        assert_eq!(engine.handle(&like(60)), vec![CounterAction::Increase { amount: 1 }]);
+        assert_eq!(engine.handle(&LiveEvent::Follow { user: "u".to_string() }), vec![CounterAction::SetGoal { value: 20 }]);
+        let chat = |comment: &str| LiveEvent::Chat { user: "u".to_string(), comment: comment.to_string() };
+        assert_eq!(engine.handle(&chat("please !RESET now")), vec![CounterAction::ResetWin]);
+        assert!(engine.handle(&chat("hello")).is_empty());
+    }
+
+    #[test]
+    fn rejects_bad_config() {
+        let mut config = LiveEventsConfig::default();
+        assert!(validate(&config).is_ok());
+        config.url = "http://127.0.0.1:21213".to_string();
+        assert!(validate(&config).is_err());
+        config.url = DEFAULT_URL.to_string();
+        config.rules.push(rule(Trigger::LikeThreshold { every: 0 }, CounterAction::Increase { amount: 1 }));
+        assert!(validate(&config).is_err());
+        config.rules[0] = rule(Trigger::Follow, CounterAction::Increase { amount: 0 });
+        assert!(validate(&config).is_err());
+        let json = r#"{"enabled":true,"rules":[{"trigger":{"kind":"gift","gift":"Rose"},"action":{"kind":"increase","amount":1}}]}"#;
+        let parsed: LiveEventsConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(parsed.url, DEFAULT_URL);
+        assert_eq!(parsed.rules[0].trigger, Trigger::Gift { gift: "Rose".to_string(), count: 1 });
+    }
+
+    #[tokio::test]
+    async fn drives_actions_from_a_local_event_server() {
+        use futures_util::SinkExt;
+        use tokio_tungstenite::tungstenite::Message;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}/", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            for message in [
+                r#"{"event":"gift","data":{"uniqueId":"a","giftName":"Rose","giftType":1,"repeatCount":2,"repeatEnd":false}}"#,
+                r#"{"event":"gift","data":{"uniqueId":"a","giftName":"Rose","giftType":1,"repeatCount":5,"repeatEnd":true}}"#,
+                r#"{"event":"follow","data":{"uniqueId":"b"}}"#,
+                r#"{"event":"chat","data":{"uniqueId":"c","comment":"gg"}}"#,
+            ] {
+                ws.send(Message::Text(message.to_string())).await.unwrap();
+            }
+            ws.close(None).await.unwrap();
+        });
+
+        let mut engine = RuleEngine::new(vec![
+            rule(Trigger::Gift { gift: "Rose".to_string(), count: 5 }, CounterAction::Increase { amount: 1 }),
+            rule(Trigger::Follow, CounterAction::SetGoal { value: 30 }),
+        ]);
+        let mut applied = Vec::new();
+        let source = Box::new(WebSocketJsonSource { url });
+        drive(source, &mut engine, |action| applied.push(action)).await.unwrap();
+        server.await.unwrap();
+
+        assert_eq!(applied, vec![CounterAction::Increase { amount: 1 }, CounterAction::SetGoal { value: 30 }]);
+    }
+}
@@ -21,7 +21,8 @@ use tokio::sync::broadcast;
 use futures_util::{StreamExt, SinkExt};
 use tokio::runtime::Runtime;
 use tokio_tungstenite::tungstenite::Message;
-use tokio_tungstenite::accept_async;
+use tokio_tungstenite::accept_hdr_async;
+use tokio_tungstenite::tungstenite::handshake::server::{Request as WsRequest, Response as WsResponse};
 use serde_json;
 use std::env;
 use sha2::{Digest, Sha256};
@@ -38,17 +39,40 @@ use std::sync::atomic::AtomicBool;
 #[folder = "../static"]
 struct EmbeddedAssets;
 
+mod api;
+mod audio;
+mod backup;
+mod chat;
+mod history;
+mod integration;
+mod labels;
+mod launch;
+mod live_events;
+mod obs;
+mod osc;
+mod overlay;
+mod overlay_instances;
+mod portable;
+mod presets;
+mod schedule;
+mod settings;
+mod sounds;
+mod text_output;
+mod themes;
+mod webhooks;
+use presets::PresetData;
+use win_count_by_artywoof::data_dir;
+
 // PromptPay module removed - using promptpay.io instead
 
 // License system removed
 
 // ===== Secure storage helpers (AES-GCM with machine-bound key) =====
 fn crypto_secret() -> String {
-    std::env::var("LICENSE_CRYPTO_SECRET").unwrap_or_else(|_| "WINCOUNT_DEFAULT_SECRET".to_string())
+    settings::crypto_secret()
 }
 
-fn derive_aes_key(machine_id: &str) -> [u8; 32] {
-    let secret = crypto_secret();
+fn derive_aes_key(machine_id: &str, secret: &str) -> [u8; 32] {
     let mut hasher = Sha256::new();
     hasher.update(machine_id.as_bytes());
     hasher.update(":");
@@ -60,7 +84,11 @@ fn derive_aes_key(machine_id: &str) -> [u8; 32] {
 }
 
 fn encrypt_for_machine(plaintext: &str, machine_id: &str) -> Result<String, String> {
-    let key_bytes = derive_aes_key(machine_id);
+    encrypt_with_secret(plaintext, machine_id, &crypto_secret())
+}
+
+fn encrypt_with_secret(plaintext: &str, machine_id: &str, secret: &str) -> Result<String, String> {
+    let key_bytes = derive_aes_key(machine_id, secret);
     let cipher = Aes256Gcm::new_from_slice(&key_bytes).map_err(|e| format!("cipher init error: {}", e))?;
     let mut nonce_bytes = [0u8; 12];
     getrandom::getrandom(&mut nonce_bytes).map_err(|e| format!("nonce error: {}", e))?;
@@ -76,7 +104,7 @@ fn decrypt_for_machine(b64: &str, machine_id: &str) -> Result<String, String> {
     let data = general_purpose::STANDARD.decode(b64).map_err(|e| format!("base64 error: {}", e))?;
     if data.len() < 13 { return Err("cipher too short".into()); }
     let (nonce_bytes, ct) = data.split_at(12);
-    let key_bytes = derive_aes_key(machine_id);
+    let key_bytes = derive_aes_key(machine_id, &crypto_secret());
     let cipher = Aes256Gcm::new_from_slice(&key_bytes).map_err(|e| format!("cipher init error: {}", e))?;
     let nonce = Nonce::from_slice(nonce_bytes);
     let plaintext = cipher.decrypt(nonce, ct).map_err(|e| format!("decrypt error: {}", e))?;
@@ -383,9 +411,10 @@ use winapi::um::winuser::{GetAsyncKeyState, VK_MENU, VK_OEM_PLUS, VK_OEM_MINUS};
 
 
 fn get_app_data_dir() -> Result<PathBuf, String> {
-    let app_data_dir = dirs::data_local_dir()
-        .ok_or("Failed to get local data directory")?
-        .join("Win Count by ArtYWoof");
+    let app_data_dir = match portable::data_dir_override() {
+        Some(dir) => dir.to_path_buf(),
+        None => data_dir::system_dir().ok_or("Failed to get local data directory")?,
+    };
     
     // Create directory if it doesn't exist
     if !app_data_dir.exists() {
@@ -402,6 +431,23 @@ fn get_app_data_file(filename: &str) -> Result<PathBuf, String> {
     Ok(app_data_dir.join(filename))
 }
 
+// Write through a temp file and rename so readers never see a half-written file
+fn write_atomic(path: &std::path::Path, data: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+    {
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(data)?;
+        file.sync_all()?;
+    }
+    fs::rename(&tmp_path, path)
+}
+
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 #[tauri::command]
 fn greet(name: &str) -> Result<String, String> {
@@ -423,7 +469,7 @@ fn get_app_version() -> Result<String, String> {
 
 // License management functions
 fn license_server_url() -> String {
-    let url = std::env::var("LICENSE_SERVER_URL").unwrap_or_else(|_| "http://127.0.0.1:8765".to_string());
+    let url = settings::license_server_url();
     #[cfg(not(debug_assertions))]
     {
         // Enforce HTTPS in release
@@ -772,6 +818,11 @@ fn register_hotkeys_dynamically(app_handle: &tauri::AppHandle, state: &SharedWin
     let _ = gs.unregister_all();
     println!("🧹 Cleared existing shortcuts");
     
+    if !settings::hotkeys_enabled() {
+        println!("⌨️ Global hotkeys are disabled, nothing registered");
+        return Ok(());
+    }
+    
     // Load custom hotkeys from localStorage
     let custom_hotkeys = load_custom_hotkeys();
     println!("📋 Loaded hotkeys from storage: {:?}", custom_hotkeys);
@@ -866,19 +917,27 @@ fn register_hotkeys_dynamically(app_handle: &tauri::AppHandle, state: &SharedWin
                 match action.as_str() {
                     "increment" => {
                         println!("⬆️ increase_win (+1) - STRICT KEY DOWN ONLY");
-                        change_win(&app_handle, &state, &broadcast_tx, 1);
+                        if let Err(e) = change_win(&app_handle, &state, &broadcast_tx, 1) {
+                            println!("⚠️ Hotkey ignored: {}", e);
+                        }
                     }
                     "decrement" => {
                         println!("⬇️ decrease_win (-1) - STRICT KEY DOWN ONLY");
-                        change_win(&app_handle, &state, &broadcast_tx, -1);
+                        if let Err(e) = change_win(&app_handle, &state, &broadcast_tx, -1) {
+                            println!("⚠️ Hotkey ignored: {}", e);
+                        }
                     }
                     "increment10" => {
                         println!("⬆️⬆️ big increase_win (+10) - STRICT KEY DOWN ONLY");
-                        change_win_with_step(&app_handle, &state, &broadcast_tx, 1, 10);
+                        if let Err(e) = change_win_with_step(&app_handle, &state, &broadcast_tx, 1, 10) {
+                            println!("⚠️ Hotkey ignored: {}", e);
+                        }
                     }
                     "decrement10" => {
                         println!("⬇️⬇️ big decrease_win (-10) - STRICT KEY DOWN ONLY");
-                        change_win_with_step(&app_handle, &state, &broadcast_tx, -1, 10);
+                        if let Err(e) = change_win_with_step(&app_handle, &state, &broadcast_tx, -1, 10) {
+                            println!("⚠️ Hotkey ignored: {}", e);
+                        }
                     }
                     _ => {
                         println!("❓ Unknown action: {}", action);
@@ -1014,35 +1073,15 @@ fn load_custom_hotkeys() -> std::collections::HashMap<String, String> {
     // If no custom hotkeys loaded, try to load from presets
     if hotkeys.is_empty() {
         println!("🎹 No custom hotkeys found, checking presets...");
-        let presets_path = get_app_data_file("win_count_presets.json").unwrap_or_else(|_| {
-            println!("❌ Failed to get presets file path, using temp directory");
-            std::env::temp_dir().join("win_count_presets.json")
-        });
-        println!("🎹 Checking presets file: {:?}", presets_path);
-        
-        if presets_path.exists() {
-            println!("🎹 Presets file exists, attempting to load...");
-            if let Ok(presets_data) = fs::read_to_string(&presets_path) {
-                println!("🎹 Presets file content: {}", presets_data);
-                if let Ok(presets) = serde_json::from_str::<Vec<PresetData>>(&presets_data) {
-                    // Use the first preset's hotkeys
-                    if let Some(first_preset) = presets.first() {
-                        hotkeys.insert("increment".to_string(), first_preset.hotkeys.increase.clone());
-                        hotkeys.insert("decrement".to_string(), first_preset.hotkeys.decrease.clone());
-                        hotkeys.insert("increment10".to_string(), format!("Shift+{}", first_preset.hotkeys.increase));
-                        hotkeys.insert("decrement10".to_string(), format!("Shift+{}", first_preset.hotkeys.decrease));
-                        println!("🎹 Loaded hotkeys from presets: {:?}", hotkeys);
-                    } else {
-                        println!("❌ No presets found in file");
-                    }
-                } else {
-                    println!("❌ Failed to parse presets file");
-                }
-            } else {
-                println!("❌ Failed to read presets file");
-            }
+        if let Some(first_preset) = presets::store().first() {
+            // Use the first preset's hotkeys
+            hotkeys.insert("increment".to_string(), first_preset.hotkeys.increase.clone());
+            hotkeys.insert("decrement".to_string(), first_preset.hotkeys.decrease.clone());
+            hotkeys.insert("increment10".to_string(), format!("Shift+{}", first_preset.hotkeys.increase));
+            hotkeys.insert("decrement10".to_string(), format!("Shift+{}", first_preset.hotkeys.decrease));
+            println!("🎹 Loaded hotkeys from presets: {:?}", hotkeys);
         } else {
-            println!("🎹 Presets file does not exist");
+            println!("❌ No presets found");
         }
     }
     
@@ -1085,23 +1124,6 @@ pub struct WinState {
     pub current_preset: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PresetData {
-    pub name: String,
-    pub win: i32,
-    pub goal: i32,
-    pub show_goal: bool,
-    pub show_crown: bool,
-    pub hotkeys: HotkeyConfig,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct HotkeyConfig {
-    pub increase: String,
-    pub decrease: String,
-    pub step_size: i32,
-}
-
 // Struct สำหรับข้อมูล Update
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateInfo {
@@ -1124,13 +1146,11 @@ impl Default for WinState {
     }
 }
 
-impl Default for HotkeyConfig {
-    fn default() -> Self {
-        Self {
-            increase: "Alt+Equal".to_string(),
-            decrease: "Alt+Minus".to_string(),
-            step_size: 1,
-        }
+#[cfg(test)]
+impl WinState {
+    // Test fixture shared by the module tests: preset "Ranked", goal and crown shown
+    pub fn sample(win: i32, goal: i32) -> Self {
+        Self { win, goal, current_preset: "Ranked".to_string(), ..Self::default() }
     }
 }
 
@@ -1140,8 +1160,20 @@ type KeyTrackerMap = Arc<Mutex<HashMap<String, KeyEventTracker>>>;
 // Add global shortcut manager state
 type GlobalShortcutManager = Arc<Mutex<Option<tauri::AppHandle>>>;
 
+// Data files kept by this module, for backups
+const STATE_DATA_FILE: backup::DataFile = backup::DataFile::json::<WinState>("win_count_state.json").at(get_state_path);
+const HOTKEYS_DATA_FILE: backup::DataFile = backup::DataFile::json::<HashMap<String, String>>("win_count_hotkeys.json");
+// The license file is encrypted, only require it to be non-empty
+const LICENSE_DATA_FILE: backup::DataFile = backup::DataFile::checked("win_count_license.json", |data| {
+    if data.is_empty() { Err("empty file".to_string()) } else { Ok(()) }
+});
+
 fn get_state_path() -> PathBuf {
-    std::env::temp_dir().join("win_count_state.json")
+    // A chosen data directory (or portable mode) keeps the state with the rest of the data
+    match portable::data_dir_override() {
+        Some(dir) => dir.join("win_count_state.json"),
+        None => std::env::temp_dir().join("win_count_state.json"),
+    }
 }
 
 fn load_state(path: &PathBuf) -> WinState {
@@ -1155,7 +1187,7 @@ fn save_state(path: &PathBuf, state: &WinState) {
     if let Some(parent) = path.parent() {
         let _ = fs::create_dir_all(parent);
     }
-    let _ = fs::write(path, serde_json::to_string_pretty(state).unwrap());
+    let _ = write_atomic(path, serde_json::to_string_pretty(state).unwrap().as_bytes());
 }
 
 #[tauri::command]
@@ -1172,6 +1204,8 @@ fn set_win_state(new_state: WinState, state: State<'_, SharedWinState>) -> Resul
         return Err("UNAUTHORIZED_ACCESS".to_string());
     }
     
+    check_counter(&new_state.current_preset, "Win count", new_state.win)?;
+    check_counter(&new_state.current_preset, "Goal", new_state.goal)?;
     let mut s = state.lock().unwrap();
     *s = new_state.clone();
     let path = get_state_path();
@@ -1315,75 +1349,61 @@ fn calculate_dynamic_step(tracker: &KeyEventTracker) -> i32 {
     }
 }
 
-// Helper function for win state mutation and event emitting
-fn change_win_with_step(app: &tauri::AppHandle, state: &SharedWinState, broadcast_tx: &tokio::sync::broadcast::Sender<WinState>, delta: i32, step: i32) {
+// Bounds of a preset, defaults if it does not exist
+fn preset_bounds(preset: &str) -> presets::CounterBounds {
+    presets::store().get(preset).map(|p| p.bounds.clone()).unwrap_or_default()
+}
+
+// Check a new win count or goal against the preset's range: its own min/max where
+// set, the range in settings.json otherwise. Every counter change goes through here
+// or through check_preset_counts.
+fn check_counter(preset: &str, what: &str, value: i32) -> Result<i32, String> {
+    preset_bounds(preset).check(what, value, settings::counter_range())
+}
+
+// The same check for a whole preset about to be stored or loaded, against its own bounds
+fn check_preset_counts(preset: &PresetData) -> Result<(), String> {
+    let global = settings::counter_range();
+    preset.bounds.check("Win count", preset.win, global)?;
+    preset.bounds.check("Goal", preset.goal, global)?;
+    Ok(())
+}
+
+// Helper function for win state mutation and event emitting.
+// Out-of-range results are rejected, not clamped.
+fn change_win_with_step(app: &tauri::AppHandle, state: &SharedWinState, broadcast_tx: &tokio::sync::broadcast::Sender<WinState>, delta: i32, step: i32) -> Result<i32, String> {
     let mut s = state.lock().unwrap();
-    let new_win = (s.win + (delta * step)).max(-10000).min(10000);  // Support negative values, match set_win range
+    let old_win = s.win;
+    let new_win = check_counter(&s.current_preset, "Win count", s.win.saturating_add(delta.saturating_mul(step)))?;
     s.win = new_win;
+    let goal = s.goal;
     let _ = app.emit("state-updated", s.clone());
     let path = get_state_path();
     save_state(&path, &s);
     let _ = broadcast_tx.send(s.clone());
     
-    // Auto-save to current preset (same as set_win)
-    let current_preset_name = s.current_preset.clone();
-    let current_state = s.clone();
-    drop(s); // Release lock before calling save_preset
-    
-    if let Ok(mut presets) = load_presets() {
-        if let Some(preset) = presets.iter_mut().find(|p| p.name == current_preset_name) {
-            preset.win = current_state.win;
-            preset.goal = current_state.goal;
-            preset.show_goal = current_state.show_goal;
-            preset.show_crown = current_state.show_crown;
-            
-            // Save updated presets
-            let presets_path = get_app_data_file("win_count_presets.json").unwrap_or_else(|_| {
-                println!("❌ Failed to get presets file path, using temp directory");
-                std::env::temp_dir().join("win_count_presets.json")
-            });
-            if let Ok(json) = serde_json::to_string_pretty(&presets) {
-                let _ = fs::write(&presets_path, json);
-                println!("💾 Auto-saved hotkey change to preset: {}", current_preset_name);
-            }
-        } else {
-            println!("⚠️ Preset '{}' not found for auto-save, hotkey change saved to state only", current_preset_name);
-            // Try to create the preset if it doesn't exist
-            let new_preset = PresetData {
-                name: current_preset_name.clone(),
-                win: current_state.win,
-                goal: current_state.goal,
-                show_goal: current_state.show_goal,
-                show_crown: current_state.show_crown,
-                hotkeys: HotkeyConfig::default(),
-            };
-            presets.push(new_preset);
-            
-            // Save updated presets
-            let presets_path = get_app_data_file("win_count_presets.json").unwrap_or_else(|_| {
-                println!("❌ Failed to get presets file path, using temp directory");
-                std::env::temp_dir().join("win_count_presets.json")
-            });
-            if let Ok(json) = serde_json::to_string_pretty(&presets) {
-                let _ = fs::write(&presets_path, json);
-                println!("💾 Created and auto-saved to new preset: {}", current_preset_name);
-            }
-        }
+    // Auto-save to current preset while still holding the state lock so
+    // concurrent changes reach the preset store in the same order
+    match presets::store().sync_from_state(&s, true) {
+        Ok(_) => println!("💾 Auto-saved hotkey change to preset: {}", s.current_preset),
+        Err(e) => println!("⚠️ Failed to auto-save preset '{}': {}", s.current_preset, e),
     }
+    drop(s);
     
-    // Emit sound event
-    if delta > 0 {
-        let _ = app.emit("play-increase-sound", ());
-    } else {
-        let _ = app.emit("play-decrease-sound", ());
-    }
+    // Emit sound event with the clip picked from the sound library
+    let slot = sounds::slot_for_change(old_win, new_win, goal, sounds::milestone_every())
+        .unwrap_or(if delta > 0 { sounds::SoundSlot::Increase } else { sounds::SoundSlot::Decrease });
+    sounds::emit_cue(app, slot);
     
     println!("🔥 Win changed by {} (step: {}), new value: {}", delta * step, step, new_win);
+    Ok(new_win)
 }
 
-// Helper function for win state mutation and event emitting
-fn change_win(app: &tauri::AppHandle, state: &SharedWinState, broadcast_tx: &tokio::sync::broadcast::Sender<WinState>, delta: i32) {
-    change_win_with_step(app, state, broadcast_tx, delta, 1);
+// Change the win count by the active preset's default step
+fn change_win(app: &tauri::AppHandle, state: &SharedWinState, broadcast_tx: &tokio::sync::broadcast::Sender<WinState>, delta: i32) -> Result<i32, String> {
+    let preset = state.lock().unwrap().current_preset.clone();
+    let step = preset_bounds(&preset).default_step;
+    change_win_with_step(app, state, broadcast_tx, delta, step)
 }
 
 #[tauri::command]
@@ -1391,7 +1411,7 @@ fn increase_win(app: tauri::AppHandle, state: State<'_, SharedWinState>, broadca
     if !x7y9z2() {
         return Err("UNAUTHORIZED_ACCESS".to_string());
     }
-    change_win(&app, &state, &*broadcast_tx, 1);
+    change_win(&app, &state, &*broadcast_tx, 1)?;
     Ok(())
 }
 
@@ -1400,7 +1420,7 @@ fn decrease_win(app: tauri::AppHandle, state: State<'_, SharedWinState>, broadca
     if !x7y9z2() {
         return Err("UNAUTHORIZED_ACCESS".to_string());
     }
-    change_win(&app, &state, &*broadcast_tx, -1);
+    change_win(&app, &state, &*broadcast_tx, -1)?;
     Ok(())
 }
 
@@ -1409,7 +1429,7 @@ fn increase_win_by_step(app: tauri::AppHandle, state: State<'_, SharedWinState>,
     if !x7y9z2() {
         return Err("UNAUTHORIZED_ACCESS".to_string());
     }
-    change_win_with_step(&app, &state, &*broadcast_tx, 1, step);
+    change_win_with_step(&app, &state, &*broadcast_tx, 1, step)?;
     Ok(())
 }
 
@@ -1418,19 +1438,16 @@ fn decrease_win_by_step(app: tauri::AppHandle, state: State<'_, SharedWinState>,
     if !x7y9z2() {
         return Err("UNAUTHORIZED_ACCESS".to_string());
     }
-    change_win_with_step(&app, &state, &*broadcast_tx, -1, step);
+    change_win_with_step(&app, &state, &*broadcast_tx, -1, step)?;
     Ok(())
 }
 
-#[tauri::command]
-fn set_win(app: tauri::AppHandle, state: State<'_, SharedWinState>, broadcast_tx: State<'_, broadcast::Sender<WinState>>, value: i32) -> Result<(), String> {
-    if !x7y9z2() {
-        return Err("UNAUTHORIZED_ACCESS".to_string());
-    }
-    
+// Set the win count: check the bounds, save, broadcast and auto-save to the active preset.
+// Shared by the set_win command and the integrations (live events, chat, OSC).
+fn set_win_value(app: &tauri::AppHandle, state: &SharedWinState, broadcast_tx: &broadcast::Sender<WinState>, value: i32) -> Result<i32, String> {
     let mut s = state.lock().unwrap();
-    // Clamp value between -10000 and 10000
-    let new_win = value.max(-10000).min(10000);
+    let old_win = s.win;
+    let new_win = check_counter(&s.current_preset, "Win count", value)?;
     s.win = new_win;
     let _ = app.emit("state-updated", s.clone());
     let path = get_state_path();
@@ -1438,42 +1455,25 @@ fn set_win(app: tauri::AppHandle, state: State<'_, SharedWinState>, broadcast_tx
     let _ = broadcast_tx.send(s.clone());
     
     // Auto-save to current preset
-    let current_preset_name = s.current_preset.clone();
-    let current_state = s.clone();
-    drop(s); // Release lock before calling save_preset
-    
-    if let Ok(mut presets) = load_presets() {
-        if let Some(preset) = presets.iter_mut().find(|p| p.name == current_preset_name) {
-            preset.win = current_state.win;
-            preset.goal = current_state.goal;
-            preset.show_goal = current_state.show_goal;
-            preset.show_crown = current_state.show_crown;
-            
-            // Save updated presets
-            let presets_path = get_app_data_file("win_count_presets.json").unwrap_or_else(|_| {
-                println!("❌ Failed to get presets file path, using temp directory");
-                std::env::temp_dir().join("win_count_presets.json")
-            });
-            if let Ok(json) = serde_json::to_string_pretty(&presets) {
-                let _ = fs::write(&presets_path, json);
-                println!("💾 Auto-saved to preset: {}", current_preset_name);
-            }
-        }
+    match presets::store().sync_from_state(&s, false) {
+        Ok(true) => println!("💾 Auto-saved to preset: {}", s.current_preset),
+        Ok(false) => {}
+        Err(e) => println!("⚠️ Failed to auto-save preset '{}': {}", s.current_preset, e),
+    }
+    drop(s);
+    
+    if new_win == 0 && old_win != 0 {
+        sounds::emit_cue(app, sounds::SoundSlot::Reset);
     }
     
     println!("🎯 Win set to: {}", new_win);
-    Ok(())
+    Ok(new_win)
 }
 
-#[tauri::command]
-fn set_goal(app: tauri::AppHandle, state: State<'_, SharedWinState>, broadcast_tx: State<'_, broadcast::Sender<WinState>>, value: i32) -> Result<(), String> {
-    if !x7y9z2() {
-        return Err("UNAUTHORIZED_ACCESS".to_string());
-    }
-    
+// Set the goal, same steps as set_win_value
+fn set_goal_value(app: &tauri::AppHandle, state: &SharedWinState, broadcast_tx: &broadcast::Sender<WinState>, value: i32) -> Result<i32, String> {
     let mut s = state.lock().unwrap();
-    // Clamp value between -10000 and 10000  
-    let new_goal = value.max(-10000).min(10000);
+    let new_goal = check_counter(&s.current_preset, "Goal", value)?;
     s.goal = new_goal;
     let _ = app.emit("state-updated", s.clone());
     let path = get_state_path();
@@ -1481,30 +1481,34 @@ fn set_goal(app: tauri::AppHandle, state: State<'_, SharedWinState>, broadcast_t
     let _ = broadcast_tx.send(s.clone());
     
     // Auto-save to current preset
-    let current_preset_name = s.current_preset.clone();
-    let current_state = s.clone();
-    drop(s); // Release lock before calling save_preset
-    
-    if let Ok(mut presets) = load_presets() {
-        if let Some(preset) = presets.iter_mut().find(|p| p.name == current_preset_name) {
-            preset.win = current_state.win;
-            preset.goal = current_state.goal;
-            preset.show_goal = current_state.show_goal;
-            preset.show_crown = current_state.show_crown;
-            
-            // Save updated presets
-            let presets_path = get_app_data_file("win_count_presets.json").unwrap_or_else(|_| {
-                println!("❌ Failed to get presets file path, using temp directory");
-                std::env::temp_dir().join("win_count_presets.json")
-            });
-            if let Ok(json) = serde_json::to_string_pretty(&presets) {
-                let _ = fs::write(&presets_path, json);
-                println!("💾 Auto-saved to preset: {}", current_preset_name);
-            }
-        }
+    match presets::store().sync_from_state(&s, false) {
+        Ok(true) => println!("💾 Auto-saved to preset: {}", s.current_preset),
+        Ok(false) => {}
+        Err(e) => println!("⚠️ Failed to auto-save preset '{}': {}", s.current_preset, e),
     }
+    drop(s);
     
     println!("🎯 Goal set to: {}", new_goal);
+    Ok(new_goal)
+}
+
+#[tauri::command]
+fn set_win(app: tauri::AppHandle, state: State<'_, SharedWinState>, broadcast_tx: State<'_, broadcast::Sender<WinState>>, value: i32) -> Result<(), String> {
+    if !x7y9z2() {
+        return Err("UNAUTHORIZED_ACCESS".to_string());
+    }
+    
+    set_win_value(&app, &state, &broadcast_tx, value)?;
+    Ok(())
+}
+
+#[tauri::command]
+fn set_goal(app: tauri::AppHandle, state: State<'_, SharedWinState>, broadcast_tx: State<'_, broadcast::Sender<WinState>>, value: i32) -> Result<(), String> {
+    if !x7y9z2() {
+        return Err("UNAUTHORIZED_ACCESS".to_string());
+    }
+    
+    set_goal_value(&app, &state, &broadcast_tx, value)?;
     Ok(())
 }
 
@@ -1541,304 +1545,825 @@ fn toggle_crown_visibility(app: tauri::AppHandle, state: State<'_, SharedWinStat
 }
 
 #[tauri::command]
-async fn copy_overlay_link() -> Result<String, String> {
+fn get_overlay_url(with_sounds: Option<bool>) -> Result<String, String> {
     if !x7y9z2() {
         return Err("License not valid".to_string());
     }
     
-    use std::process::Command;
-    let overlay_url = "http://localhost:1420/overlay";
-    
-    // Use Windows clipboard command  
-    #[cfg(windows)]
-    {
-        let output = Command::new("cmd")
-            .args(&["/C", &format!("echo {} | clip", overlay_url)])
-            .output()
-            .map_err(|e| format!("Failed to copy to clipboard: {}", e))?;
-            
-        if output.status.success() {
-            println!("📋 Copied overlay link to clipboard: {}", overlay_url);
-            Ok(overlay_url.to_string())
-        } else {
-            Err("Failed to copy overlay link to clipboard".to_string())
-        }
-    }
-    
-    #[cfg(not(windows))]
-    {
-        // For non-Windows systems, just return the URL
-        println!("📋 Overlay link: {}", overlay_url);
-        Ok(overlay_url.to_string())
-    }    
+    Ok(overlay::overlay_url(with_sounds.unwrap_or(false)))
 }
 
+// Port the overlay WebSocket server currently listens on
 #[tauri::command]
-fn save_preset(preset: PresetData, state: State<'_, SharedWinState>) -> Result<(), String> {
+fn get_ws_port() -> Result<u16, String> {
     if !x7y9z2() {
         return Err("License not valid".to_string());
     }
-    
-    let presets_path = get_app_data_file("win_count_presets.json")?;
-    
-    println!("🔴 Attempting to save preset: {:?}", preset);
-    
-    // Load existing presets
-    let mut presets: Vec<PresetData> = if presets_path.exists() {
-        let json = fs::read_to_string(&presets_path)
-            .map_err(|e| format!("Failed to read presets: {}", e))?;
-        serde_json::from_str(&json)
-            .map_err(|e| format!("Failed to parse presets: {}", e))?
-    } else {
-        Vec::new()
-    };
-    
-    // Remove existing preset with same name
-    presets.retain(|p| p.name != preset.name);
-    
-    // Add new preset
-    presets.push(preset.clone());
-    
-    // Limit to 10 presets
-    if presets.len() > 10 {
-        presets.remove(0);
+
+    Ok(settings::ws_port())
+}
+
+#[tauri::command]
+fn get_osc_settings() -> Result<osc::OscConfig, String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
     }
     
-    // Save presets
-    let json = serde_json::to_string_pretty(&presets)
-        .map_err(|e| format!("Failed to serialize presets: {}", e))?;
-    fs::write(&presets_path, &json)
-        .map_err(|e| format!("Failed to save presets: {}", e))?;
-    
-    // Update current state if this is the active preset
-    let mut s = state.lock().unwrap();
-    if s.current_preset == preset.name {
-        s.win = preset.win;
-        s.goal = preset.goal;
-        s.show_goal = preset.show_goal;
-        s.show_crown = preset.show_crown;
+    Ok(osc::config())
+}
+
+#[tauri::command]
+fn set_osc_settings(config: osc::OscConfig) -> Result<(), String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
     }
     
-    println!("💾 Saved preset: {} | Win: {} | Goal: {}", preset.name, preset.win, preset.goal);
+    osc::set_config(config.clone())?;
+    println!("🎛️ OSC listener {} on {}:{}, sender {} to {}:{}", if config.listen_enabled { "on" } else { "off" }, config.listen_host, config.listen_port, if config.send_enabled { "on" } else { "off" }, config.send_host, config.send_port);
     Ok(())
 }
 
 #[tauri::command]
-fn load_presets() -> Result<Vec<PresetData>, String> {
+fn list_themes() -> Result<Vec<themes::ThemeInfo>, String> {
     if !x7y9z2() {
         return Err("License not valid".to_string());
     }
     
-    let presets_path = get_app_data_file("win_count_presets.json")?;
-    
-    println!("📋 Loading presets from: {:?}", presets_path);
-    
-    let mut presets: Vec<PresetData> = if presets_path.exists() {
-        let json = fs::read_to_string(&presets_path)
-            .map_err(|e| format!("Failed to read presets: {}", e))?;
-        
-        println!("📄 Presets JSON: {}", json);
-        
-        serde_json::from_str(&json)
-            .map_err(|e| format!("Failed to parse presets: {}", e))?
-    } else {
-        Vec::new()
-    };
-    
-    // Ensure Default preset exists (only if no presets exist)
-    if presets.is_empty() {
-        let default_preset = PresetData {
-            name: "Default".to_string(),
-            win: 0,
-            goal: 10,
-            show_goal: true,
-            show_crown: true,
-            hotkeys: HotkeyConfig::default(),
-        };
-        presets.insert(0, default_preset);
-        
-        // Save updated presets
-        let json = serde_json::to_string_pretty(&presets)
-            .map_err(|e| format!("Failed to serialize presets: {}", e))?;
-        fs::write(&presets_path, json)
-            .map_err(|e| format!("Failed to save presets: {}", e))?;
-        println!("🔄 Created Default preset because no presets existed");
-    }
-    
-    println!("✅ Loaded {} presets", presets.len());
-    Ok(presets)
+    themes::list_themes()
 }
 
 #[tauri::command]
-fn load_preset(name: String, app: tauri::AppHandle, state: State<'_, SharedWinState>, broadcast_tx: State<'_, broadcast::Sender<WinState>>) -> Result<PresetData, String> {
+fn install_theme(file_data: Vec<u8>) -> Result<themes::ThemeInfo, String> {
     if !x7y9z2() {
         return Err("License not valid".to_string());
     }
     
-    println!("🔍 Attempting to load preset: {}", name);
-    
-    let presets = load_presets()?;
-    let preset = presets.into_iter()
-        .find(|p| p.name == name)
-        .ok_or_else(|| format!("Preset '{}' not found", name))?;
-    
-    println!("📂 Found preset: {} | Win: {} | Goal: {}", preset.name, preset.win, preset.goal);
-    
-    // Update state
-    let mut s = state.lock().unwrap();
-    s.win = preset.win;
-    s.goal = preset.goal;
-    s.show_goal = preset.show_goal;
-    s.show_crown = preset.show_crown;
-    s.current_preset = preset.name.clone();
-    
-    // Save state and broadcast
-    let path = get_state_path();
-    save_state(&path, &s);
-    let _ = app.emit("state-updated", s.clone());
-    let _ = broadcast_tx.send(s.clone());
-    
-    // Sync hotkeys with preset
-    let mut hotkeys = std::collections::HashMap::new();
-    hotkeys.insert("increment".to_string(), preset.hotkeys.increase.clone());
-    hotkeys.insert("decrement".to_string(), preset.hotkeys.decrease.clone());
-    hotkeys.insert("increment10".to_string(), format!("Shift+{}", preset.hotkeys.increase));
-    hotkeys.insert("decrement10".to_string(), format!("Shift+{}", preset.hotkeys.decrease));
-    
-    // Save hotkeys to sync with preset
-    if let Err(e) = save_custom_hotkeys(&hotkeys) {
-        println!("⚠️ Failed to save hotkeys for preset: {}", e);
-    } else {
-        println!("🎹 Synced hotkeys with preset: {:?}", hotkeys);
-        
-        // Reload hotkeys to make them active immediately
-        if let Err(e) = register_hotkeys_dynamically(&app, &state, &broadcast_tx) {
-            println!("⚠️ Failed to reload hotkeys after preset sync: {}", e);
-        } else {
-            println!("✅ Hotkeys reloaded after preset sync");
-        }
-    }
-    
-    println!("✅ Loaded preset: {} | Updated Win: {} | Updated Goal: {}", name, s.win, s.goal);
-    Ok(preset)
+    let theme = themes::install_theme(&file_data)?;
+    println!("🎨 Installed overlay theme '{}' ({})", theme.name, theme.id);
+    Ok(theme)
 }
 
 #[tauri::command]
-fn delete_preset(name: String) -> Result<(), String> {
+fn delete_theme(id: String) -> Result<(), String> {
     if !x7y9z2() {
         return Err("License not valid".to_string());
     }
     
-    println!("🗑️ DELETE_PRESET CALLED with name: {}", name);
-    let presets_path = get_app_data_file("win_count_presets.json")?;
-    println!("📁 Presets path: {:?}", presets_path);
-    
-    if !presets_path.exists() {
-        println!("⚠️ Presets file does not exist, returning OK");
-        return Ok(());
-    }
-    
-    let json = fs::read_to_string(&presets_path)
-        .map_err(|e| format!("Failed to read presets: {}", e))?;
-    println!("📄 Read presets JSON: {}", json);
-    
-    let mut presets: Vec<PresetData> = serde_json::from_str(&json)
-        .map_err(|e| format!("Failed to parse presets: {}", e))?;
-    println!("📋 Parsed presets: {:?}", presets.iter().map(|p| &p.name).collect::<Vec<_>>());
-    
-    // ตรวจสอบว่ามี preset ที่ต้องการลบอยู่จริงหรือไม่
-    if !presets.iter().any(|p| p.name == name) {
-        println!("❌ Preset '{}' not found in list", name);
-        return Err(format!("Preset '{}' not found", name));
-    }
-    
-    println!("✅ Found preset '{}' to delete", name);
-    
-    // ลบ preset ที่ต้องการ
-    let original_count = presets.len();
-    presets.retain(|p| p.name != name);
-    let new_count = presets.len();
-    println!("🔄 Removed {} preset(s), count: {} -> {}", original_count - new_count, original_count, new_count);
-    
-    // ถ้าลบ preset สุดท้าย ให้สร้าง Default preset ใหม่
-    if presets.is_empty() {
-        let default_preset = PresetData {
-            name: "Default".to_string(),
-            win: 0,
-            goal: 10,
-            show_goal: true,
-            show_crown: true,
-            hotkeys: HotkeyConfig::default(),
-        };
-        presets.push(default_preset);
-        println!("🔄 Created new Default preset after deletion");
-    }
-    
-    let json = serde_json::to_string_pretty(&presets)
-        .map_err(|e| format!("Failed to serialize presets: {}", e))?;
-    println!("💾 Saving presets JSON: {}", json);
-    
-    fs::write(&presets_path, json)
-        .map_err(|e| format!("Failed to save presets: {}", e))?;
-    
-    println!("✅ Successfully deleted preset: {}", name);
+    themes::delete_theme(&id)?;
+    println!("🗑️ Deleted overlay theme '{}'", id);
     Ok(())
 }
 
 #[tauri::command]
-fn rename_preset(old_name: String, new_name: String) -> Result<(), String> {
+fn list_overlays() -> Result<Vec<overlay_instances::OverlayInstance>, String> {
     if !x7y9z2() {
         return Err("License not valid".to_string());
     }
     
-    let presets_path = get_app_data_file("win_count_presets.json")?;
-    
-    if !presets_path.exists() {
-        return Err("No presets file found".to_string());
+    Ok(overlay_instances::list())
+}
+
+#[tauri::command]
+fn create_overlay(name: String, layout: Option<overlay_instances::OverlayLayout>, theme: Option<String>) -> Result<overlay_instances::OverlayInstance, String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
     }
     
-    let json = fs::read_to_string(&presets_path)
-        .map_err(|e| format!("Failed to read presets: {}", e))?;
-    let mut presets: Vec<PresetData> = serde_json::from_str(&json)
-        .map_err(|e| format!("Failed to parse presets: {}", e))?;
-    
-    // หา preset ที่ต้องการเปลี่ยนชื่อ
-    if let Some(preset) = presets.iter_mut().find(|p| p.name == old_name) {
-        preset.name = new_name.clone();
-        
-        let json = serde_json::to_string_pretty(&presets)
-            .map_err(|e| format!("Failed to serialize presets: {}", e))?;
-        fs::write(&presets_path, json)
-            .map_err(|e| format!("Failed to save presets: {}", e))?;
-        
-        println!("🔄 Renamed preset from '{}' to '{}'", old_name, new_name);
-        Ok(())
-    } else {
-        Err(format!("Preset '{}' not found", old_name))
-    }
+    let instance = overlay_instances::create(&name, layout.unwrap_or_default(), theme)?;
+    println!("🖼️ Created overlay '{}' ({})", instance.name, instance.id);
+    Ok(instance)
 }
 
 #[tauri::command]
-fn play_test_sounds(app: tauri::AppHandle) -> Result<(), String> {
+fn update_overlay(id: String, changes: overlay_instances::OverlayUpdate) -> Result<overlay_instances::OverlayInstance, String> {
     if !x7y9z2() {
         return Err("License not valid".to_string());
     }
     
-    // Emit events for the frontend to play sounds
-    let _ = app.emit("play-increase-sound", {});
-    
-    // Schedule the decrease sound after a short delay
-    std::thread::spawn(move || {
-        std::thread::sleep(std::time::Duration::from_millis(200));
-        let _ = app.emit("play-decrease-sound", {});
-    });
+    overlay_instances::update(&id, changes)
+}
+
+#[tauri::command]
+fn delete_overlay(id: String) -> Result<(), String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
     
-    println!("🔊 Test sounds requested");
+    overlay_instances::delete(&id)?;
+    println!("🗑️ Deleted overlay '{}'", id);
     Ok(())
 }
 
 #[tauri::command]
-fn test_hotkeys() -> Result<String, String> {
+fn get_overlay_instance_url(id: String) -> Result<String, String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    overlay_instances::get(&id)
+        .map(|instance| instance.url())
+        .ok_or_else(|| format!("Overlay '{}' not found", id))
+}
+
+#[tauri::command]
+fn get_text_output() -> Result<text_output::TextOutputConfig, String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    Ok(text_output::config())
+}
+
+#[tauri::command]
+fn set_text_output(config: text_output::TextOutputConfig, state: State<'_, SharedWinState>) -> Result<(), String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    let current = state.lock().unwrap().clone();
+    text_output::set_config(config.clone(), &current)?;
+    println!("📝 Text output {} ({:?})", if config.enabled { "enabled" } else { "disabled" }, config.folder);
+    Ok(())
+}
+
+#[tauri::command]
+fn get_live_events() -> Result<live_events::LiveEventsConfig, String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    Ok(live_events::config())
+}
+
+#[tauri::command]
+fn set_live_events(config: live_events::LiveEventsConfig) -> Result<(), String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    live_events::set_config(config.clone())?;
+    println!("🎁 Live events {} ({}, {} rules)", if config.enabled { "enabled" } else { "disabled" }, config.url, config.rules.len());
+    Ok(())
+}
+
+#[tauri::command]
+fn get_live_events_status() -> Result<integration::ConnectionStatus, String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    Ok(live_events::status())
+}
+
+#[tauri::command]
+fn get_chat_settings() -> Result<chat::ChatConfig, String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    Ok(chat::config())
+}
+
+#[tauri::command]
+fn set_chat_settings(config: chat::ChatConfig) -> Result<(), String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    chat::set_config(config.clone())?;
+    println!("💬 Chat commands {} ({}:{} #{}, {} allowed users)", if config.enabled { "enabled" } else { "disabled" }, config.host, config.port, config.channel.trim_start_matches('#'), config.allowed_users.len());
+    Ok(())
+}
+
+#[tauri::command]
+fn get_chat_status() -> Result<integration::ConnectionStatus, String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    Ok(chat::status())
+}
+
+#[tauri::command]
+fn list_webhooks() -> Result<Vec<webhooks::Webhook>, String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    Ok(webhooks::list())
+}
+
+#[tauri::command]
+fn save_webhooks(hooks: Vec<webhooks::Webhook>) -> Result<Vec<webhooks::Webhook>, String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    let saved = webhooks::save(hooks)?;
+    println!("🔔 Saved {} webhook(s)", saved.len());
+    Ok(saved)
+}
+
+#[tauri::command]
+fn get_webhook_log() -> Result<Vec<webhooks::DeliveryRecord>, String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    Ok(webhooks::delivery_log())
+}
+
+#[tauri::command]
+fn get_obs_settings() -> Result<obs::ObsConfig, String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    Ok(obs::config())
+}
+
+#[tauri::command]
+fn set_obs_settings(config: obs::ObsConfig) -> Result<(), String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    obs::set_config(config.clone())?;
+    println!("🎬 OBS actions {} ({}:{}, {} rules)", if config.enabled { "enabled" } else { "disabled" }, config.host, config.port, config.rules.len());
+    Ok(())
+}
+
+#[tauri::command]
+async fn test_obs_connection(config: obs::ObsConfig) -> Result<String, String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    obs::test_connection(&config).await
+}
+
+#[tauri::command]
+async fn copy_overlay_link() -> Result<String, String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    use std::process::Command;
+    let overlay_url = "http://localhost:1420/overlay";
+    
+    // Use Windows clipboard command  
+    #[cfg(windows)]
+    {
+        let output = Command::new("cmd")
+            .args(&["/C", &format!("echo {} | clip", overlay_url)])
+            .output()
+            .map_err(|e| format!("Failed to copy to clipboard: {}", e))?;
+            
+        if output.status.success() {
+            println!("📋 Copied overlay link to clipboard: {}", overlay_url);
+            Ok(overlay_url.to_string())
+        } else {
+            Err("Failed to copy overlay link to clipboard".to_string())
+        }
+    }
+    
+    #[cfg(not(windows))]
+    {
+        // For non-Windows systems, just return the URL
+        println!("📋 Overlay link: {}", overlay_url);
+        Ok(overlay_url.to_string())
+    }    
+}
+
+#[tauri::command]
+fn save_preset(preset: PresetData, app: tauri::AppHandle, state: State<'_, SharedWinState>, broadcast_tx: State<'_, broadcast::Sender<WinState>>) -> Result<(), String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    println!("🔴 Attempting to save preset: {:?}", preset);
+    
+    let mut s = state.lock().unwrap();
+    // Bounds are kept from the stored preset, so the counts are checked against those
+    check_counter(&preset.name, "Win count", preset.win)?;
+    check_counter(&preset.name, "Goal", preset.goal)?;
+    presets::store().save(preset.clone())?;
+    
+    // Update current state if this is the active preset
+    if s.current_preset == preset.name {
+        s.win = preset.win;
+        s.goal = preset.goal;
+        s.show_goal = preset.show_goal;
+        s.show_crown = preset.show_crown;
+        save_state(&get_state_path(), &s);
+        let _ = app.emit("state-updated", s.clone());
+        let _ = broadcast_tx.send(s.clone());
+    }
+    
+    println!("💾 Saved preset: {} | Win: {} | Goal: {}", preset.name, preset.win, preset.goal);
+    Ok(())
+}
+
+#[tauri::command]
+fn load_presets() -> Result<Vec<PresetData>, String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    let presets = presets::store().list();
+    println!("✅ Loaded {} presets", presets.len());
+    Ok(presets)
+}
+
+// Make `preset` the active one: copy its values into the state, broadcast,
+// and sync the global hotkeys with the preset's bindings. Callers check the
+// counts with check_preset_counts first.
+fn activate_preset(app: &tauri::AppHandle, state: &SharedWinState, broadcast_tx: &broadcast::Sender<WinState>, s: &mut WinState, preset: &PresetData) {
+    s.win = preset.win;
+    s.goal = preset.goal;
+    s.show_goal = preset.show_goal;
+    s.show_crown = preset.show_crown;
+    s.current_preset = preset.name.clone();
+    sounds::apply_preset_sounds(&preset.sounds);
+    
+    // Save state and broadcast
+    let path = get_state_path();
+    save_state(&path, s);
+    let _ = app.emit("state-updated", s.clone());
+    let _ = broadcast_tx.send(s.clone());
+    
+    // Sync hotkeys with preset
+    let mut hotkeys = std::collections::HashMap::new();
+    hotkeys.insert("increment".to_string(), preset.hotkeys.increase.clone());
+    hotkeys.insert("decrement".to_string(), preset.hotkeys.decrease.clone());
+    hotkeys.insert("increment10".to_string(), format!("Shift+{}", preset.hotkeys.increase));
+    hotkeys.insert("decrement10".to_string(), format!("Shift+{}", preset.hotkeys.decrease));
+    
+    // Save hotkeys to sync with preset
+    if let Err(e) = save_custom_hotkeys(&hotkeys) {
+        println!("⚠️ Failed to save hotkeys for preset: {}", e);
+    } else {
+        println!("🎹 Synced hotkeys with preset: {:?}", hotkeys);
+        
+        // Reload hotkeys to make them active immediately
+        if let Err(e) = register_hotkeys_dynamically(app, state, broadcast_tx) {
+            println!("⚠️ Failed to reload hotkeys after preset sync: {}", e);
+        } else {
+            println!("✅ Hotkeys reloaded after preset sync");
+        }
+    }
+}
+
+#[tauri::command]
+fn load_preset(name: String, app: tauri::AppHandle, state: State<'_, SharedWinState>, broadcast_tx: State<'_, broadcast::Sender<WinState>>) -> Result<PresetData, String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    println!("🔍 Attempting to load preset: {}", name);
+    
+    load_preset_by_name(&app, &state, &broadcast_tx, &name)
+}
+
+// Make the named preset active; shared by the load_preset command and the local API
+fn load_preset_by_name(app: &tauri::AppHandle, state: &SharedWinState, broadcast_tx: &broadcast::Sender<WinState>, name: &str) -> Result<PresetData, String> {
+    let mut s = state.lock().unwrap();
+    let preset = presets::store().get(name)
+        .cloned()
+        .ok_or_else(|| format!("Preset '{}' not found", name))?;
+    
+    println!("📂 Found preset: {} | Win: {} | Goal: {}", preset.name, preset.win, preset.goal);
+    check_preset_counts(&preset).map_err(|e| format!("Cannot load preset '{}': {}", preset.name, e))?;
+    
+    activate_preset(app, state, broadcast_tx, &mut s, &preset);
+    
+    println!("✅ Loaded preset: {} | Updated Win: {} | Updated Goal: {}", name, s.win, s.goal);
+    Ok(preset)
+}
+
+#[tauri::command]
+fn delete_preset(name: String, app: tauri::AppHandle, state: State<'_, SharedWinState>, broadcast_tx: State<'_, broadcast::Sender<WinState>>) -> Result<(), String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    println!("🗑️ DELETE_PRESET CALLED with name: {}", name);
+    
+    // Lock the state first so an auto-save cannot recreate the preset mid-delete
+    let mut s = state.lock().unwrap();
+    let replacement = {
+        let mut store = presets::store();
+        if s.current_preset == name {
+            if let Some(next) = store.list().into_iter().find(|p| p.name != name) {
+                check_preset_counts(&next).map_err(|e| format!("Cannot switch to preset '{}': {}", next.name, e))?;
+            }
+        }
+        store.delete(&name)?;
+        if s.current_preset == name {
+            store.list().into_iter().next()
+        } else {
+            None
+        }
+    };
+    
+    // The active preset is gone, fall back to the first remaining one (checked
+    // above, or the Default preset recreated when none is left)
+    if let Some(preset) = replacement {
+        println!("🔄 Active preset deleted, switching to '{}'", preset.name);
+        activate_preset(&app, &state, &broadcast_tx, &mut s, &preset);
+    }
+    
+    println!("✅ Successfully deleted preset: {}", name);
+    Ok(())
+}
+
+#[tauri::command]
+fn rename_preset(old_name: String, new_name: String, app: tauri::AppHandle, state: State<'_, SharedWinState>, broadcast_tx: State<'_, broadcast::Sender<WinState>>) -> Result<(), String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    let mut s = state.lock().unwrap();
+    presets::store().rename(&old_name, &new_name)?;
+    
+    // Keep the active preset reference pointing at the renamed preset
+    if s.current_preset == old_name {
+        s.current_preset = new_name.trim().to_string();
+        let path = get_state_path();
+        save_state(&path, &s);
+        let _ = app.emit("state-updated", s.clone());
+        let _ = broadcast_tx.send(s.clone());
+    }
+    
+    println!("🔄 Renamed preset from '{}' to '{}'", old_name, new_name);
+    Ok(())
+}
+
+#[tauri::command]
+fn reorder_presets(names: Vec<String>) -> Result<(), String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    presets::store().reorder(&names)?;
+    
+    println!("🔀 Reordered presets: {:?}", names);
+    Ok(())
+}
+
+#[tauri::command]
+fn set_preset_pinned(name: String, pinned: bool) -> Result<(), String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    presets::store().update(&name, |p| p.pinned = pinned)?;
+    
+    println!("📌 Preset '{}' pinned: {}", name, pinned);
+    Ok(())
+}
+
+#[tauri::command]
+fn set_preset_folder(name: String, folder: Option<String>) -> Result<(), String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    // Blank folder names mean "no folder"
+    let folder = folder
+        .map(|f| f.trim().to_string())
+        .filter(|f| !f.is_empty());
+    
+    presets::store().update(&name, |p| p.folder = folder.clone())?;
+    
+    println!("📁 Preset '{}' moved to folder: {:?}", name, folder);
+    Ok(())
+}
+
+#[tauri::command]
+fn duplicate_preset(source: String, new_name: String, reset_counts: bool) -> Result<PresetData, String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    let mut store = presets::store();
+    let mut preset = store.get(&source)
+        .cloned()
+        .ok_or_else(|| format!("Preset '{}' not found", source))?;
+    
+    preset.name = new_name.trim().to_string();
+    preset.pinned = false;
+    if reset_counts {
+        preset.win = 0;
+    }
+    check_preset_counts(&preset)?;
+    store.insert_new(preset.clone())?;
+    
+    println!("📑 Duplicated preset '{}' as '{}' (reset counts: {})", source, preset.name, reset_counts);
+    Ok(preset)
+}
+
+#[tauri::command]
+fn list_preset_templates() -> Result<Vec<presets::PresetTemplate>, String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    Ok(presets::list_templates())
+}
+
+#[tauri::command]
+fn create_preset_from_template(template_id: String, name: Option<String>) -> Result<PresetData, String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    let preset = presets::instantiate_template(&template_id, name)?;
+    check_preset_counts(&preset)?;
+    presets::store().insert_new(preset.clone())?;
+    
+    println!("🧩 Created preset '{}' from template '{}'", preset.name, template_id);
+    Ok(preset)
+}
+
+#[tauri::command]
+fn set_preset_reset_schedule(name: String, schedule: Option<schedule::ResetSchedule>) -> Result<(), String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    if let Some(schedule) = &schedule {
+        schedule.validate()?;
+    }
+    
+    // Count the next reset from now rather than from some earlier slot
+    presets::store().update(&name, |p| {
+        p.reset_schedule = schedule.clone();
+        p.last_reset = Some(Utc::now());
+    })?;
+    
+    println!("⏰ Reset schedule for '{}' set to: {:?}", name, schedule);
+    Ok(())
+}
+
+#[tauri::command]
+fn set_preset_sounds(name: String, settings: sounds::PresetSounds, state: State<'_, SharedWinState>) -> Result<(), String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    sounds::validate_preset_sounds(&settings)?;
+    
+    let s = state.lock().unwrap();
+    presets::store().update(&name, |p| p.sounds = settings.clone())?;
+    if s.current_preset == name {
+        sounds::apply_preset_sounds(&settings);
+    }
+    
+    println!("🔊 Sound settings for preset '{}' updated ({} events)", name, settings.len());
+    Ok(())
+}
+
+#[tauri::command]
+fn set_preset_labels(name: String, labels: std::collections::BTreeMap<String, String>, state: State<'_, SharedWinState>, broadcast_tx: State<'_, broadcast::Sender<WinState>>) -> Result<(), String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    labels::validate_all(&labels)?;
+    
+    let s = state.lock().unwrap();
+    presets::store().update(&name, |p| p.labels = labels.clone())?;
+    // Re-send the state so overlays pick up the new text right away
+    if s.current_preset == name {
+        let _ = broadcast_tx.send(s.clone());
+    }
+    
+    println!("🏷️ Labels for preset '{}' updated ({} templates)", name, labels.len());
+    Ok(())
+}
+
+#[tauri::command]
+fn set_preset_bounds(name: String, bounds: presets::CounterBounds, state: State<'_, SharedWinState>) -> Result<(), String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    let global = settings::counter_range();
+    presets::validate_bounds(&bounds, global)?;
+    
+    let s = state.lock().unwrap();
+    let mut store = presets::store();
+    let (win, goal) = match store.get(&name) {
+        // The live counts of the active preset
+        Some(_) if s.current_preset == name => (s.win, s.goal),
+        Some(preset) => (preset.win, preset.goal),
+        None => return Err(format!("Preset '{}' not found", name)),
+    };
+    // Counts are never moved into the new range; they must already fit it
+    bounds.check("Win count", win, global)
+        .and_then(|_| bounds.check("Goal", goal, global))
+        .map_err(|e| format!("{}; change it before setting these bounds", e))?;
+    store.update(&name, |p| p.bounds = bounds.clone())?;
+    
+    println!("📏 Bounds for preset '{}' updated: {:?}", name, bounds);
+    Ok(())
+}
+
+#[tauri::command]
+fn preview_label(template: String, state: State<'_, SharedWinState>) -> Result<String, String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    labels::validate(&template)?;
+    let s = state.lock().unwrap();
+    Ok(labels::render(&template, &s))
+}
+
+#[tauri::command]
+fn get_win_history(preset: Option<String>, limit: Option<usize>) -> Result<Vec<history::HistoryEntry>, String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    Ok(history::list(preset.as_deref(), limit.unwrap_or(100)))
+}
+
+#[tauri::command]
+fn list_backups() -> Result<Vec<backup::BackupInfo>, String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    backup::list_backups()
+}
+
+#[tauri::command]
+fn create_backup() -> Result<Option<backup::BackupInfo>, String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    backup::create_backup("manual", true)
+}
+
+#[tauri::command]
+fn restore_backup(id: String, app: tauri::AppHandle, state: State<'_, SharedWinState>, broadcast_tx: State<'_, broadcast::Sender<WinState>>) -> Result<(), String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    backup::restore_backup(&id, &app, &state, &broadcast_tx)
+}
+
+#[tauri::command]
+fn get_settings() -> Result<settings::Settings, String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    Ok(settings::redacted(settings::get()))
+}
+
+// The stored license as plain JSON, read with the current crypto secret.
+// None when there is no license file or it is not encrypted.
+fn read_license_json() -> Result<Option<String>, String> {
+    let path = get_app_data_file("win_count_license.json")?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read license: {}", e))?;
+    if content.trim_start().starts_with('{') {
+        return Ok(None); // Not encrypted, nothing to re-encrypt
+    }
+    decrypt_for_machine(&content, &m4c5h6n()?)
+        .map(Some)
+        .map_err(|e| format!("The stored license cannot be decrypted with the current secret, keeping it: {}", e))
+}
+
+// Save settings.json and apply it live: the overlay servers re-bind, hotkeys are
+// re-registered and the counter is pulled into a narrowed range
+#[tauri::command]
+fn update_settings(mut new_settings: settings::Settings, app: tauri::AppHandle, state: State<'_, SharedWinState>, broadcast_tx: State<'_, broadcast::Sender<WinState>>) -> Result<settings::Settings, String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    let old = settings::get();
+    // The secret is write-only: the masked value keeps the stored one
+    new_settings.license.crypto_secret = integration::unmask_secret(new_settings.license.crypto_secret, &old.license.crypto_secret);
+    settings::validate(&new_settings)?;
+    settings::check_license_url(&old.license.server_url, &new_settings.license.server_url, cfg!(not(debug_assertions)))?;
+    // A new counter range must fit every preset that uses it. The state and the
+    // presets stay locked until it is saved, so no count can leave it meanwhile.
+    let range_locks = if new_settings.counter != old.counter {
+        let s = state.lock().unwrap();
+        let store = presets::store();
+        store.check_range((new_settings.counter.min, new_settings.counter.max))?;
+        Some((s, store))
+    } else {
+        None
+    };
+    // Fail before saving if a server cannot move to its new port
+    if new_settings.server.http_port != old.server.http_port && launch::startup().http_port.is_none() {
+        settings::check_port_free(new_settings.server.http_port)?;
+    }
+    if new_settings.server.ws_port != old.server.ws_port && launch::startup().ws_port.is_none() {
+        settings::check_port_free(new_settings.server.ws_port)?;
+    }
+    // A new crypto secret needs the license decrypted with the old one and
+    // re-encrypted next to it before the secret is saved
+    let license_path = get_app_data_file("win_count_license.json")?;
+    let reencrypted = license_path.with_extension("json.new");
+    let new_secret = settings::crypto_secret_of(&new_settings);
+    let mut license_staged = false;
+    if new_secret != settings::crypto_secret_of(&old) {
+        if let Some(json) = read_license_json()? {
+            let encrypted = encrypt_with_secret(&json, &m4c5h6n()?, &new_secret)?;
+            write_atomic(&reencrypted, encrypted.as_bytes())
+                .map_err(|e| format!("Failed to re-encrypt license: {}", e))?;
+            license_staged = true;
+        }
+    }
+    
+    let saved = match settings::update(new_settings) {
+        Ok(saved) => saved,
+        Err(e) => {
+            let _ = fs::remove_file(&reencrypted);
+            return Err(e);
+        }
+    };
+    drop(range_locks);
+    
+    if license_staged {
+        if let Err(e) = fs::rename(&reencrypted, &license_path) {
+            // Keep the secret the license is still encrypted with
+            let _ = fs::remove_file(&reencrypted);
+            let _ = settings::update(old);
+            return Err(format!("Failed to re-encrypt license: {}", e));
+        }
+        println!("🔐 License re-encrypted with the new secret");
+    }
+    if saved.hotkeys.enabled != old.hotkeys.enabled {
+        register_hotkeys_dynamically(&app, &state, &broadcast_tx)?;
+    }
+    Ok(settings::redacted(saved))
+}
+
+#[tauri::command]
+fn get_portable_status() -> Result<portable::PortableStatus, String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    Ok(portable::status())
+}
+
+// Move all data into the portable folder next to the executable (or back) and restart.
+// The files move at the next start, while nothing is writing them.
+#[tauri::command]
+fn migrate_portable_data(to_portable: bool, app: tauri::AppHandle) -> Result<(), String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    // Snapshot first; the backups folder moves along with the data
+    if let Err(e) = backup::create_backup("before-migration", true) {
+        println!("⚠️ Backup before migration failed: {}", e);
+    }
+    portable::request_migration(to_portable)?;
+    
+    println!("🔄 Restarting to move the data folder...");
+    let handle = app.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(500));
+        handle.restart();
+    });
+    Ok(())
+}
+
+#[tauri::command]
+fn play_test_sounds(app: tauri::AppHandle) -> Result<(), String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
+    
+    // Emit events for the frontend to play sounds
+    sounds::emit_cue(&app, sounds::SoundSlot::Increase);
+    
+    // Schedule the decrease sound after a short delay
+    std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        sounds::emit_cue(&app, sounds::SoundSlot::Decrease);
+    });
+    
+    println!("🔊 Test sounds requested");
+    Ok(())
+}
+
+#[tauri::command]
+fn test_hotkeys() -> Result<String, String> {
     if !x7y9z2() {
         return Err("License not valid".to_string());
     }
@@ -1960,100 +2485,104 @@ fn check_hotkey_file() -> Result<String, String> {
 
 // Sound file management functions
 #[tauri::command]
-fn save_custom_sound(file_data: Vec<u8>, filename: String, sound_type: String) -> Result<(), String> {
+fn save_custom_sound(file_data: Vec<u8>, filename: String, sound_type: sounds::SoundSlot) -> Result<sounds::SoundClip, audio::AudioImportError> {
     if !x7y9z2() {
-        return Err("License not valid".to_string());
+        return Err("License not valid".to_string().into());
     }
     
-    let app_data_dir = get_app_data_dir()?;
-    let sounds_dir = app_data_dir.join("sounds");
-    
-    // Create sounds directory if it doesn't exist
-    if !sounds_dir.exists() {
-        fs::create_dir_all(&sounds_dir)
-            .map_err(|e| format!("Failed to create sounds directory: {}", e))?;
+    match sounds::save_sound(sound_type, &file_data, &filename) {
+        Ok(metadata) => {
+            println!("🔊 Saved custom {} sound: {} -> {}", sound_type.as_str(), metadata.original_name, metadata.filename);
+            Ok(metadata)
+        }
+        Err(e) => {
+            println!("❌ Rejected custom {} sound '{}': {}", sound_type.as_str(), filename, e);
+            Err(e)
+        }
+    }
+}
+
+#[tauri::command]
+fn get_custom_sound_info(sound_type: sounds::SoundSlot) -> Result<Option<sounds::SoundClip>, String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
     }
     
-    let file_path = sounds_dir.join(&filename);
-    fs::write(&file_path, file_data)
-        .map_err(|e| format!("Failed to save sound file: {}", e))?;
-    
-    // Save metadata
-    let metadata = serde_json::json!({
-        "filename": filename,
-        "type": sound_type,
-        "saved_at": chrono::Utc::now().to_rfc3339()
-    });
+    sounds::read_metadata(sound_type)
+}
+
+#[tauri::command]
+fn get_sound_library() -> Result<sounds::SoundLibrary, String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
     
-    let metadata_path = app_data_dir.join(format!("sound_{}_metadata.json", sound_type));
-    fs::write(metadata_path, serde_json::to_string(&metadata).unwrap())
-        .map_err(|e| format!("Failed to save sound metadata: {}", e))?;
+    sounds::library()
+}
+
+#[tauri::command]
+fn add_sound_clip(file_data: Vec<u8>, filename: String, sound_type: sounds::SoundSlot, weight: Option<u32>) -> Result<sounds::SoundClip, audio::AudioImportError> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string().into());
+    }
     
-    Ok(())
+    let clip = sounds::add_clip(sound_type, &file_data, &filename, weight.unwrap_or(1))?;
+    println!("🔊 Added {} sound clip: {} -> {}", sound_type.as_str(), clip.original_name, clip.filename);
+    Ok(clip)
 }
 
 #[tauri::command]
-fn get_custom_sound_path(sound_type: String) -> Result<String, String> {
+fn remove_sound_clip(sound_type: sounds::SoundSlot, clip: String) -> Result<(), String> {
     if !x7y9z2() {
         return Err("License not valid".to_string());
     }
     
-    let app_data_dir = get_app_data_dir()?;
-    let metadata_path = app_data_dir.join(format!("sound_{}_metadata.json", sound_type));
+    sounds::remove_clip(sound_type, &clip)
+}
+
+#[tauri::command]
+fn set_sound_clip_weight(sound_type: sounds::SoundSlot, clip: String, weight: u32) -> Result<(), String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
     
-    if metadata_path.exists() {
-        let metadata_str = fs::read_to_string(metadata_path)
-            .map_err(|_| "Failed to read sound metadata".to_string())?;
-        
-        let metadata: serde_json::Value = serde_json::from_str(&metadata_str)
-            .map_err(|_| "Invalid sound metadata format".to_string())?;
-        
-        let filename = metadata["filename"].as_str()
-            .ok_or("Invalid filename in metadata".to_string())?;
-        
-        let sound_path = app_data_dir.join("sounds").join(filename);
-        if sound_path.exists() {
-            Ok(sound_path.to_string_lossy().to_string())
-        } else {
-            Err("Sound file not found".to_string())
-        }
-    } else {
-        Err("No custom sound metadata found".to_string())
+    sounds::set_clip_weight(sound_type, &clip, weight)
+}
+
+#[tauri::command]
+fn set_sound_policy(sound_type: sounds::SoundSlot, policy: sounds::PlaybackPolicy) -> Result<(), String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
     }
+    
+    sounds::set_policy(sound_type, policy)
 }
 
 #[tauri::command]
-fn delete_custom_sound(sound_type: String) -> Result<(), String> {
+fn set_sound_milestone_every(every: u32) -> Result<(), String> {
     if !x7y9z2() {
         return Err("License not valid".to_string());
     }
     
-    let app_data_dir = get_app_data_dir()?;
-    let metadata_path = app_data_dir.join(format!("sound_{}_metadata.json", sound_type));
+    sounds::set_milestone_every(every)
+}
+
+#[tauri::command]
+fn get_custom_sound_path(sound_type: sounds::SoundSlot) -> Result<String, String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
+    }
     
-    if metadata_path.exists() {
-        let metadata_str = fs::read_to_string(&metadata_path)
-            .map_err(|_| "Failed to read sound metadata".to_string())?;
-        
-        let metadata: serde_json::Value = serde_json::from_str(&metadata_str)
-            .map_err(|_| "Invalid sound metadata format".to_string())?;
-        
-        let filename = metadata["filename"].as_str()
-            .ok_or("Invalid filename in metadata".to_string())?;
-        
-        // Delete sound file
-        let sound_path = app_data_dir.join("sounds").join(filename);
-        if sound_path.exists() {
-            fs::remove_file(&sound_path)
-                .map_err(|e| format!("Failed to delete sound file: {}", e))?;
-        }
-        
-        // Delete metadata
-        fs::remove_file(metadata_path)
-            .map_err(|e| format!("Failed to delete sound metadata: {}", e))?;
+    sounds::sound_path(sound_type).map(|p| p.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+fn delete_custom_sound(sound_type: sounds::SoundSlot) -> Result<(), String> {
+    if !x7y9z2() {
+        return Err("License not valid".to_string());
     }
     
-    Ok(())
+    sounds::delete_sound(sound_type)
 }
 
 #[tauri::command]
@@ -2062,32 +2591,23 @@ fn read_sound_file(file_path: String) -> Result<Vec<u8>, String> {
         return Err("License not valid".to_string());
     }
     
-    fs::read(&file_path)
-        .map_err(|e| format!("Failed to read sound file: {}", e))
+    sounds::read_sound(&file_path)
 }
 
 #[tauri::command]
-fn get_custom_sound_filename(sound_type: String) -> Result<String, String> {
+fn get_custom_sound_filename(sound_type: sounds::SoundSlot) -> Result<String, String> {
     if !x7y9z2() {
         return Err("License not valid".to_string());
     }
     
-    let app_data_dir = get_app_data_dir()?;
-    let metadata_path = app_data_dir.join(format!("sound_{}_metadata.json", sound_type));
+    let metadata = sounds::read_metadata(sound_type)?
+        .ok_or("No custom sound metadata found".to_string())?;
     
-    if metadata_path.exists() {
-        let metadata_str = fs::read_to_string(metadata_path)
-            .map_err(|_| "Failed to read sound metadata".to_string())?;
-        
-        let metadata: serde_json::Value = serde_json::from_str(&metadata_str)
-            .map_err(|_| "Invalid sound metadata format".to_string())?;
-        
-        let filename = metadata["filename"].as_str()
-            .ok_or("Invalid filename in metadata".to_string())?;
-        
-        Ok(filename.to_string())
+    // Older metadata predates original_name and stored the picked name directly
+    if metadata.original_name.is_empty() {
+        Ok(metadata.filename)
     } else {
-        Err("No custom sound metadata found".to_string())
+        Ok(metadata.original_name)
     }
 }
 
@@ -2230,14 +2750,13 @@ async fn install_update_and_restart(app: tauri::AppHandle) -> Result<(), String>
 
 // License system removed
 
-fn start_http_server() {
+fn start_http_server(shared_state: SharedWinState) {
     thread::spawn(move || {
         let rt = Runtime::new().unwrap();
         rt.block_on(async move {
-            use tokio::net::TcpListener;
             use tokio::io::{AsyncReadExt, AsyncWriteExt};
             
-            println!("🌐 Starting HTTP server on 127.0.0.1:777");
+            println!("🌐 Starting HTTP server on 127.0.0.1:{}", settings::http_port());
             
             // Debug: Print current working directory
             if let Ok(current_dir) = std::env::current_dir() {
@@ -2304,12 +2823,14 @@ fn start_http_server() {
                 println!("ℹ️  MSI installation not found (normal for development)");
             }
             
-            let listener = TcpListener::bind("127.0.0.1:777").await.unwrap();
+            // Re-binds when the port in settings.json changes
+            let mut listener = settings::ServerListener::bind("HTTP", settings::http_port).await;
             
             loop {
                 match listener.accept().await {
                     Ok((mut stream, addr)) => {
                         println!("📄 HTTP connection from: {}", addr);
+                        let shared_state = shared_state.clone();
                         
                         tokio::spawn(async move {
                             let mut buffer = [0; 1024];
@@ -2345,6 +2866,42 @@ fn start_http_server() {
                                                 let _ = stream.write_all(response.as_bytes()).await;
                                             }
                                         }
+                                    } else if request.starts_with("GET /sounds/") {
+                                        // Custom sound clips for the overlay, token protected
+                                        let target = request.split_whitespace().nth(1).unwrap_or("");
+                                        let reply = overlay::sound_response(&request, target);
+                                        if let Err(e) = stream.write_all(&reply.to_bytes()).await {
+                                            println!("❌ Failed to send sound response: {}", e);
+                                        }
+                                    } else if request.starts_with("POST /api/") || request.starts_with("GET /api/presets") {
+                                        // Local API for scripts and wincount-cli, token protected
+                                        let target = request.split_whitespace().nth(1).unwrap_or("");
+                                        let reply = api::response(&request, target);
+                                        let _ = stream.write_all(&reply.to_bytes()).await;
+                                    } else if request.starts_with("GET /api/state") || request.starts_with("GET /api/labels/") {
+                                        // Current state with rendered label templates, for text sources
+                                        let target = request.split_whitespace().nth(1).unwrap_or("");
+                                        let state = shared_state.lock().unwrap().clone();
+                                        let reply = match target.split('?').next().unwrap_or("").strip_prefix("/api/labels/") {
+                                            Some(name) => match labels::labels_for(&state).remove(name) {
+                                                Some(text) => overlay::HttpReply::ok("text/plain; charset=utf-8", text.into_bytes()),
+                                                None => overlay::HttpReply::error("404 Not Found", "404 - Label not found"),
+                                            },
+                                            None => overlay::HttpReply::ok("application/json", labels::state_json(&state).into_bytes()),
+                                        };
+                                        let _ = stream.write_all(&reply.to_bytes()).await;
+                                    } else if request.starts_with("GET /overlay/") {
+                                        // Named overlay instance: redirect to its theme page
+                                        let target = request.split_whitespace().nth(1).unwrap_or("");
+                                        let reply = overlay_instances::overlay_response(target);
+                                        let _ = stream.write_all(&reply.to_bytes()).await;
+                                    } else if request.starts_with("GET /themes/") {
+                                        // Theme packs from app data, falling back to embedded assets
+                                        let target = request.split_whitespace().nth(1).unwrap_or("");
+                                        let reply = themes::theme_response(target);
+                                        if let Err(e) = stream.write_all(&reply.to_bytes()).await {
+                                            println!("❌ Failed to send theme response: {}", e);
+                                        }
                                     } else if request.starts_with("GET /assets/") {
                                         // Handle static assets (images, fonts, etc.)
                                         let path_start = request.find("GET ").unwrap() + 4;
@@ -2371,6 +2928,12 @@ fn start_http_server() {
                                                     "font/woff"
                                                 } else if asset_path.ends_with(".woff2") {
                                                     "font/woff2"
+                                                } else if asset_path.ends_with(".mp3") {
+                                                    "audio/mpeg"
+                                                } else if asset_path.ends_with(".wav") {
+                                                    "audio/wav"
+                                                } else if asset_path.ends_with(".ogg") {
+                                                    "audio/ogg"
                                                 } else {
                                                     "application/octet-stream"
                                                 };
@@ -2426,9 +2989,8 @@ fn start_ws_server(shared_state: Arc<Mutex<WinState>>, broadcast_tx: broadcast::
     thread::spawn(move || {
         let rt = Runtime::new().unwrap();
         rt.block_on(async move {
-            use tokio::net::TcpListener;
-            println!("🌐 Starting WebSocket server on 127.0.0.1:779");
-            let listener = TcpListener::bind("127.0.0.1:779").await.unwrap();
+            println!("🌐 Starting WebSocket server on 127.0.0.1:{}", settings::ws_port());
+            let mut listener = settings::ServerListener::bind("WebSocket", settings::ws_port).await;
             
             loop {
                 match listener.accept().await {
@@ -2438,17 +3000,33 @@ fn start_ws_server(shared_state: Arc<Mutex<WinState>>, broadcast_tx: broadcast::
                         let shared_state_clone = shared_state.clone();
                         
                         tokio::spawn(async move {
-                            match accept_async(stream).await {
+                            // The request path names the overlay instance ("/overlay/<id>"), if any
+                            let mut ws_path = String::new();
+                            let handshake = accept_hdr_async(stream, |request: &WsRequest, response: WsResponse| {
+                                ws_path = request.uri().to_string();
+                                Ok(response)
+                            }).await;
+                            match handshake {
                                 Ok(ws_stream) => {
                                     let (mut ws_write, mut ws_read) = ws_stream.split();
                                     let _rx = broadcast_tx_clone.subscribe();
                                     
+                                    let instance = overlay_instances::id_from_ws_path(&ws_path)
+                                        .and_then(|id| overlay_instances::get(&id));
+                                    if let Some(instance) = &instance {
+                                        let _ = ws_write.send(Message::Text(overlay_instances::hello_message(instance))).await;
+                                        println!("👋 Overlay instance '{}' connected", instance.id);
+                                    }
+                                    
                                     // ส่ง state ปัจจุบันจาก shared_state ให้ overlay ทุกครั้งที่เชื่อมต่อใหม่
                                     let current_state = {
                                         let state_guard = shared_state_clone.lock().unwrap();
-                                        (*state_guard).clone()
+                                        match &instance {
+                                            Some(instance) => instance.view(&state_guard),
+                                            None => (*state_guard).clone(),
+                                        }
                                     };
-                                    let current_msg = serde_json::to_string(&current_state).unwrap();
+                                    let current_msg = labels::state_json(&current_state);
                                     let _ = ws_write.send(Message::Text(current_msg)).await;
                                     println!("📡 Sent current state to new connection: {:?}", current_state);
                                     
@@ -2456,11 +3034,42 @@ fn start_ws_server(shared_state: Arc<Mutex<WinState>>, broadcast_tx: broadcast::
                                     let mut rx_send = broadcast_tx_clone.subscribe();
                                     let mut ws_write_send = ws_write;
                                     
-                                    // Task to send state updates
+                                    let mut rx_sound = sounds::cue_channel().subscribe();
+                                    let mut rx_config = overlay_instances::config_channel().subscribe();
+                                    let mut send_instance = instance.clone();
+                                    
+                                    // Task to send state updates, sound events and overlay config changes
                                     let send_task = tokio::spawn(async move {
-                                        while let Ok(state) = rx_send.recv().await {
-                                            println!("📡 Sending state update: {:?}", state);
-                                            let msg = serde_json::to_string(&state).unwrap();
+                                        loop {
+                                            let msg = tokio::select! {
+                                                state = rx_send.recv() => match state {
+                                                    Ok(state) => {
+                                                        println!("📡 Sending state update: {:?}", state);
+                                                        let state = match &send_instance {
+                                                            Some(instance) => instance.view(&state),
+                                                            None => state,
+                                                        };
+                                                        labels::state_json(&state)
+                                                    }
+                                                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                                                    Err(_) => break,
+                                                },
+                                                config = rx_config.recv() => match config {
+                                                    Ok(updated) if send_instance.as_ref().map(|i| i.id == updated.id).unwrap_or(false) => {
+                                                        let msg = overlay_instances::config_message(&updated);
+                                                        send_instance = Some(updated);
+                                                        msg
+                                                    }
+                                                    Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                                                    Err(_) => break,
+                                                },
+                                                cue = rx_sound.recv() => match cue {
+                                                    // Sound events carry a "type" so the overlay can tell them from state
+                                                    Ok(cue) => serde_json::json!({ "type": "sound", "event": cue.event, "clip": cue.clip, "volume": cue.volume, "muted": cue.muted }).to_string(),
+                                                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                                                    Err(_) => break,
+                                                },
+                                            };
                                             match ws_write_send.send(Message::Text(msg)).await {
                                                 Ok(_) => {},
                                                 Err(e) => {
@@ -2475,6 +3084,8 @@ fn start_ws_server(shared_state: Arc<Mutex<WinState>>, broadcast_tx: broadcast::
                                     // สร้าง receiver ใหม่สำหรับ read task
                                     let _rx_read = broadcast_tx_clone.subscribe();
                                     
+                                    let instance_id = instance.map(|i| i.id);
+                                    
                                     // Task to handle incoming messages and keepalive
                                     let read_task = tokio::spawn(async move {
                                         while let Some(msg) = ws_read.next().await {
@@ -2485,7 +3096,14 @@ fn start_ws_server(shared_state: Arc<Mutex<WinState>>, broadcast_tx: broadcast::
                                                         if let Some(msg_type) = json.get("type").and_then(|v| v.as_str()) {
                                                             match msg_type {
                                                                 "toggle_goal" => {
-                                                                    if let Some(val) = json.get("value").and_then(|v| v.as_bool()) {
+                                                                    // A named overlay only toggles its own flag
+                                                                    if let (Some(id), Some(val)) = (&instance_id, json.get("value").and_then(|v| v.as_bool())) {
+                                                                        let changes = overlay_instances::OverlayUpdate { show_goal: Some(val), ..Default::default() };
+                                                                        match overlay_instances::update(id, changes) {
+                                                                            Ok(_) => println!("🔄 show_goal of overlay '{}' updated: {}", id, val),
+                                                                            Err(e) => println!("⚠️ Failed to update overlay '{}': {}", id, e),
+                                                                        }
+                                                                    } else if let Some(val) = json.get("value").and_then(|v| v.as_bool()) {
                                                                         let mut state = shared_state_clone.lock().unwrap();
                                                                         state.show_goal = val;
                                                                         let _ = broadcast_tx_clone.send(state.clone());
@@ -2493,7 +3111,14 @@ fn start_ws_server(shared_state: Arc<Mutex<WinState>>, broadcast_tx: broadcast::
                                                                     }
                                                                 },
                                                                 "toggle_crown" => {
-                                                                    if let Some(val) = json.get("value").and_then(|v| v.as_bool()) {
+                                                                    // A named overlay only toggles its own flag
+                                                                    if let (Some(id), Some(val)) = (&instance_id, json.get("value").and_then(|v| v.as_bool())) {
+                                                                        let changes = overlay_instances::OverlayUpdate { show_crown: Some(val), ..Default::default() };
+                                                                        match overlay_instances::update(id, changes) {
+                                                                            Ok(_) => println!("🔄 show_crown of overlay '{}' updated: {}", id, val),
+                                                                            Err(e) => println!("⚠️ Failed to update overlay '{}': {}", id, e),
+                                                                        }
+                                                                    } else if let Some(val) = json.get("value").and_then(|v| v.as_bool()) {
                                                                         let mut state = shared_state_clone.lock().unwrap();
                                                                         state.show_crown = val;
                                                                         let _ = broadcast_tx_clone.send(state.clone());
@@ -2510,13 +3135,19 @@ fn start_ws_server(shared_state: Arc<Mutex<WinState>>, broadcast_tx: broadcast::
                                                                         println!("📥 Parsed JSON: {:?}", json);
                                                                         // Extract WinState fields from the update message
                                                                         if let (Some(win), Some(goal), Some(show_goal), Some(show_crown), Some(current_preset)) = (
-                                                                            json.get("win").and_then(|v| v.as_i64()).map(|v| v as i32),
-                                                                            json.get("goal").and_then(|v| v.as_i64()).map(|v| v as i32),
+                                                                            json.get("win").and_then(|v| v.as_i64()).and_then(|v| i32::try_from(v).ok()),
+                                                                            json.get("goal").and_then(|v| v.as_i64()).and_then(|v| i32::try_from(v).ok()),
                                                                             json.get("show_goal").and_then(|v| v.as_bool()),
                                                                             json.get("show_crown").and_then(|v| v.as_bool()),
                                                                             json.get("current_preset").and_then(|v| v.as_str())
                                                                         ) {
                                                                             let mut state = shared_state_clone.lock().unwrap();
+                                                                            let checked = check_counter(current_preset, "Win count", win)
+                                                                                .and_then(|_| check_counter(current_preset, "Goal", goal));
+                                                                            if let Err(e) = checked {
+                                                                                println!("❌ Rejected update message: {}", e);
+                                                                                continue;
+                                                                            }
                                                                             state.win = win;
                                                                             state.goal = goal;
                                                                             state.show_goal = show_goal;
@@ -2586,34 +3217,45 @@ fn main() {
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
+// The saved state, moved to the first preset if its preset no longer exists
+fn load_initial_state() -> WinState {
     let path = get_state_path();
     let mut initial = load_state(&path);
-    
-    // Validate current_preset exists in presets, fallback to Default if not
-    if let Ok(presets) = load_presets() {
-        if !presets.iter().any(|p| p.name == initial.current_preset) {
-            println!("⚠️ Current preset '{}' not found in presets, falling back to 'Default'", initial.current_preset);
-            initial.current_preset = "Default".to_string();
-            
-            // Update the state file with the corrected preset
-            save_state(&path, &initial);
-        }
+    let store = presets::store();
+    if !store.contains(&initial.current_preset) {
+        let fallback = store.list().into_iter().next().map(|p| p.name).unwrap_or_else(|| "Default".to_string());
+        println!("⚠️ Current preset '{}' not found in presets, falling back to '{}'", initial.current_preset, fallback);
+        initial.current_preset = fallback;
+        
+        // Update the state file with the corrected preset
+        save_state(&path, &initial);
+    }
+    if let Some(preset) = store.get(&initial.current_preset) {
+        sounds::apply_preset_sounds(&preset.sounds);
     }
+    initial
+}
+
+pub fn run() {
+    // Startup flags and WINCOUNT_* overrides; read before anything touches the data folder
+    let startup = launch::init_startup();
     
-    let shared_state = Arc::new(Mutex::new(initial));
+    // Filled in setup: the data folder is only read or written once the
+    // single-instance check has let this launch through
+    let shared_state = Arc::new(Mutex::new(WinState::default()));
     let (broadcast_tx, _broadcast_rx) = broadcast::channel::<WinState>(32);
     let key_tracker: KeyTrackerMap = Arc::new(Mutex::new(HashMap::new()));
     
-    // Start HTTP server for overlay.html
-    start_http_server();
-    
-    // Start WebSocket server
-    start_ws_server(shared_state.clone(), broadcast_tx.clone());
+    // Servers and integrations are started in setup, after the single-instance
+    // check, so a second launch never tries to bind the overlay ports
     
     // Start Heartbeat monitoring (will be started in setup)
     
     tauri::Builder::default()
+        // Must be the first plugin: a second launch focuses this instance and exits
+        .plugin(tauri_plugin_single_instance::init(|app, argv, _cwd| {
+            launch::on_second_instance(app, argv);
+        }))
         .manage(shared_state.clone())
         .manage(broadcast_tx.clone())
         .manage(key_tracker.clone())
@@ -2623,7 +3265,7 @@ pub fn run() {
         .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
 
-        .invoke_handler(tauri::generate_handler![greet, get_app_version, a1b2c3d4, s4v3k3y, m4c5h6n, update_hotkey, reload_hotkeys_command, test_hotkeys, get_win_state, set_win_state, minimize_app, hide_to_tray, show_from_tray, increase_win, decrease_win, increase_win_by_step, decrease_win_by_step, set_win, set_goal, toggle_goal_visibility, toggle_crown_visibility, copy_overlay_link, save_preset, load_presets, load_preset, delete_preset, rename_preset, play_test_sounds, clear_hotkeys, save_default_hotkeys, check_hotkey_file, save_custom_sound, get_custom_sound_path, delete_custom_sound, read_sound_file, get_custom_sound_filename, check_for_updates, download_and_install_update, install_update_and_restart, create_promptpay_qr, get_license_key, remove_license_key])
+        .invoke_handler(tauri::generate_handler![greet, get_app_version, a1b2c3d4, s4v3k3y, m4c5h6n, update_hotkey, reload_hotkeys_command, test_hotkeys, get_win_state, set_win_state, minimize_app, hide_to_tray, show_from_tray, increase_win, decrease_win, increase_win_by_step, decrease_win_by_step, set_win, set_goal, toggle_goal_visibility, toggle_crown_visibility, get_overlay_url, get_ws_port, get_osc_settings, set_osc_settings, list_themes, install_theme, delete_theme, list_overlays, create_overlay, update_overlay, delete_overlay, get_overlay_instance_url, get_text_output, set_text_output, get_live_events, set_live_events, get_live_events_status, get_chat_settings, set_chat_settings, get_chat_status, list_webhooks, save_webhooks, get_webhook_log, get_obs_settings, set_obs_settings, test_obs_connection, copy_overlay_link, save_preset, load_presets, load_preset, delete_preset, rename_preset, reorder_presets, set_preset_pinned, set_preset_folder, duplicate_preset, list_preset_templates, create_preset_from_template, set_preset_reset_schedule, set_preset_sounds, set_preset_labels, set_preset_bounds, preview_label, get_win_history, list_backups, create_backup, restore_backup, get_settings, update_settings, get_portable_status, migrate_portable_data, play_test_sounds, clear_hotkeys, save_default_hotkeys, check_hotkey_file, save_custom_sound, get_custom_sound_info, get_custom_sound_path, delete_custom_sound, read_sound_file, get_custom_sound_filename, get_sound_library, add_sound_clip, remove_sound_clip, set_sound_clip_weight, set_sound_policy, set_sound_milestone_every, check_for_updates, download_and_install_update, install_update_and_restart, create_promptpay_qr, get_license_key, remove_license_key])
         .setup({
             let shared_state = Arc::clone(&shared_state);
             let broadcast_tx = broadcast_tx.clone();
@@ -2632,6 +3274,22 @@ pub fn run() {
             move |app| {
                 let app_handle: Arc<tauri::AppHandle> = Arc::new(app.handle().clone());
                 let state: SharedWinState = Arc::clone(&shared_state);
+                
+                // A mode switch asked for before the last restart; must run before the data folder is resolved
+                portable::run_pending_migration();
+                let initial = load_initial_state();
+                *state.lock().unwrap() = initial;
+                // Start HTTP server for overlay.html
+                start_http_server(state.clone());
+                
+                // Start WebSocket server
+                start_ws_server(state.clone(), broadcast_tx.clone());
+                
+                // Plain-text files for OBS Text sources (does nothing until enabled)
+                text_output::start(state.clone(), broadcast_tx.subscribe());
+                webhooks::start(state.clone(), broadcast_tx.subscribe());
+                obs::start(state.clone(), broadcast_tx.subscribe());
+                
                 let gs = app_handle.global_shortcut();
                 let gs_manager_state: GlobalShortcutManager = Arc::new(Mutex::new(Some(app.handle().clone())));
                 
@@ -2658,6 +3316,47 @@ pub fn run() {
                 // Start Enhanced Security Monitor
                 start_security_monitor(app.handle().clone());
                 
+                // Let the local API reach the app state
+                api::init(app.handle().clone());
+                
+                // Arguments of this launch, e.g. --preset "Ranked" --inc 1
+                let args: Vec<String> = std::env::args().skip(1).collect();
+                match launch::parse(&args) {
+                    Ok(mut launch_args) => {
+                        if launch_args.preset.is_none() {
+                            launch_args.preset = startup.preset.clone();
+                        }
+                        if !launch_args.is_empty() {
+                            launch::apply(app.handle(), &launch_args);
+                        }
+                    }
+                    Err(e) => println!("⚠️ Ignoring launch arguments: {}", e),
+                }
+                
+                // The window starts hidden; show it unless started minimized or headless
+                if startup.headless {
+                    println!("🖥️ Headless mode: overlay servers only, no window");
+                } else if startup.minimized {
+                    println!("🔽 Started minimized to tray");
+                } else if let Some(window) = app.get_webview_window("main") {
+                    let _ = window.show();
+                }
+                
+                // Start scheduled preset resets
+                schedule::start_reset_scheduler(app.handle().clone(), state.clone(), broadcast_tx.clone());
+                
+                // Start rolling backups of user data
+                backup::start_backup_scheduler();
+                
+                // Start TikTok LIVE event ingestion (idles until enabled)
+                live_events::start(app.handle().clone(), state.clone(), broadcast_tx.clone());
+                
+                // Start the chat command connector (idles until enabled)
+                chat::start(app.handle().clone(), state.clone(), broadcast_tx.clone());
+                
+                // Start the OSC listener and state sender (idle until enabled)
+                osc::start(app.handle().clone(), state.clone(), broadcast_tx.clone());
+                
                 // Setup System Tray with enhanced menu
                 println!("🎯 Setting up system tray...");
                 let show_menu_item = MenuItemBuilder::with_id("show", "👑 Show Win Counter").build(app)?;
@@ -2665,9 +3364,16 @@ pub fn run() {
                 let current_goal_item = MenuItemBuilder::with_id("current_goal", "🎯 Current Goal: 10").build(app)?;
                 let quit_menu_item = MenuItemBuilder::with_id("quit", "❌ Quit Win Count").build(app)?;
                 
-                let tray_menu = MenuBuilder::new(app)
-                    .items(&[&show_menu_item, &current_win_item, &current_goal_item, &quit_menu_item])
-                    .build()?;
+                // Headless mode has no window to show
+                let tray_menu = if startup.headless {
+                    MenuBuilder::new(app)
+                        .items(&[&current_win_item, &current_goal_item, &quit_menu_item])
+                        .build()?
+                } else {
+                    MenuBuilder::new(app)
+                        .items(&[&show_menu_item, &current_win_item, &current_goal_item, &quit_menu_item])
+                        .build()?
+                };
                 
                 let tray = app.tray_by_id("main").unwrap();
                 tray.set_menu(Some(tray_menu))?;
@@ -2752,7 +3458,6 @@ pub fn run() {
 static T4MP3R_C0UNT: AtomicUsize = AtomicUsize::new(0);
 static GR4C3_P3R10D: AtomicBool = AtomicBool::new(false);
 static mut GRACE_PERIOD_START: Option<DateTime<Utc>> = None;
-const GRACE_PERIOD_DURATION: i64 = 5 * 60; // 5 นาที (วินาที)
 
 // --- Anti-Debugging & Tamper Detection ---
 static ANTI_DEBUG_ACTIVE: AtomicBool = AtomicBool::new(false);
@@ -2771,7 +3476,6 @@ extern "system" {
 // --- Heartbeat System ---
 static H3A2T_4CT1V3: AtomicBool = AtomicBool::new(false);
 static mut LAST_HEARTBEAT: Option<DateTime<Utc>> = None;
-const HEARTBEAT_INTERVAL: u64 = 30; // 30 วินาที
 
 fn h4s5h6(path: &str) -> Option<String> {
     let mut file = File::open(path).ok()?;
@@ -2960,7 +3664,7 @@ fn start_security_monitor(app: tauri::AppHandle) {
                 let now = Utc::now();
                 let expired = unsafe {
                     if let Some(start) = GRACE_PERIOD_START {
-                        (now - start).num_seconds() > GRACE_PERIOD_DURATION
+                        (now - start).num_seconds() > settings::get().license.grace_period_secs
                     } else { false }
                 };
                 if expired {
@@ -3066,7 +3770,8 @@ fn m0n1t0r(app: tauri::AppHandle) {
         let rt = tokio::runtime::Runtime::new().unwrap();
         
         loop {
-            std::thread::sleep(std::time::Duration::from_secs(HEARTBEAT_INTERVAL));
+            // Read every round so a changed interval applies without a restart
+            std::thread::sleep(std::time::Duration::from_secs(settings::get().license.heartbeat_interval_secs));
             
             if !H3A2T_4CT1V3.load(Ordering::SeqCst) {
                 break; // Stop if disabled
@@ -0,0 +1,562 @@
+// OBS WebSocket v5 client. Rules map counter events (goal reached, milestone, ...)
+// to OBS requests: switch scene, show or hide a source, save the replay buffer.
+// Requests go through the ObsConnection trait so rules can run against a mock.
+// Settings live in win_count_obs.json.
+
+use base64::{engine::general_purpose, Engine as _};
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::sync::broadcast;
+use tokio::time::Instant;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+use crate::backup::DataFile;
+use crate::webhooks::{events_for, CounterEvent};
+use crate::{get_app_data_file, sounds, write_atomic, SharedWinState, WinState};
+
+const CONFIG_FILE: &str = "win_count_obs.json";
+// The password stays out of backups
+pub const DATA_FILE: DataFile = DataFile::json::<ObsConfig>(CONFIG_FILE).with_secrets(backup_copy, restored_copy);
+pub const DEFAULT_PORT: u16 = 4455;
+pub const MAX_RULES: usize = 50;
+const RPC_VERSION: u64 = 1;
+const TIMEOUT: Duration = Duration::from_secs(5);
+// How long a hide that could not reach OBS waits before it is tried again
+const HIDE_RETRY: Duration = Duration::from_secs(10);
+
+static CONFIG: Mutex<Option<ObsConfig>> = Mutex::new(None);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ObsAction {
+    SwitchScene { scene: String },
+    // Turn a source on in a scene, optionally off again after a few seconds
+    ShowSource { scene: String, source: String, #[serde(default)] hide_after_secs: Option<u32> },
+    HideSource { scene: String, source: String },
+    SaveReplayBuffer,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ObsRule {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub event: CounterEvent,
+    pub action: ObsAction,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ObsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_host")]
+    pub host: String,
+    #[serde(default = "default_port")]
+    pub port: u16,
+    #[serde(default)]
+    pub password: String,
+    #[serde(default)]
+    pub rules: Vec<ObsRule>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_host() -> String {
+    "127.0.0.1".to_string()
+}
+
+fn default_port() -> u16 {
+    DEFAULT_PORT
+}
+
+impl Default for ObsConfig {
+    fn default() -> Self {
+        Self { enabled: false, host: default_host(), port: default_port(), password: String::new(), rules: Vec::new() }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ObsError {
+    // No usable connection: OBS is unreachable, closed it or stopped answering
+    Connection(String),
+    // OBS answered but could not carry out the request
+    Request(String),
+}
+
+impl fmt::Display for ObsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObsError::Connection(message) | ObsError::Request(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+pub type ObsFuture<'a> = Pin<Box<dyn Future<Output = Result<Value, ObsError>> + Send + 'a>>;
+
+// Something that answers OBS requests: the real client or a test double
+pub trait ObsConnection: Send {
+    fn request<'a>(&'a mut self, request_type: &'a str, data: Value) -> ObsFuture<'a>;
+}
+
+// Authentication string of the v5 handshake:
+// base64(sha256(base64(sha256(password + salt)) + challenge))
+pub fn auth_response(password: &str, salt: &str, challenge: &str) -> String {
+    let secret = general_purpose::STANDARD.encode(Sha256::digest(format!("{}{}", password, salt)));
+    general_purpose::STANDARD.encode(Sha256::digest(format!("{}{}", secret, challenge)))
+}
+
+pub struct ObsClient {
+    ws: WebSocketStream<MaybeTlsStream<TcpStream>>,
+    next_id: u64,
+}
+
+impl ObsClient {
+    // Connect and identify (Hello -> Identify -> Identified)
+    pub async fn connect(host: &str, port: u16, password: &str) -> Result<Self, ObsError> {
+        let url = format!("ws://{}:{}", host.trim(), port);
+        let (ws, _) = tokio::time::timeout(TIMEOUT, tokio_tungstenite::connect_async(url.as_str()))
+            .await
+            .map_err(|_| ObsError::Connection(format!("Timed out connecting to OBS at {}", url)))?
+            .map_err(|e| ObsError::Connection(format!("Failed to connect to OBS at {}: {}", url, e)))?;
+        let mut client = Self { ws, next_id: 0 };
+
+        let hello = client.receive_op(0).await?;
+        let mut identify = json!({ "rpcVersion": RPC_VERSION, "eventSubscriptions": 0 });
+        if let Some(auth) = hello.get("authentication") {
+            if password.is_empty() {
+                return Err(ObsError::Connection("OBS requires a password, enter it in the OBS settings".to_string()));
+            }
+            let salt = auth.get("salt").and_then(Value::as_str).unwrap_or_default();
+            let challenge = auth.get("challenge").and_then(Value::as_str).unwrap_or_default();
+            identify["authentication"] = json!(auth_response(password, salt, challenge));
+        }
+        client.send(json!({ "op": 1, "d": identify })).await?;
+        client.receive_op(2).await.map_err(|e| match e {
+            ObsError::Connection(message) if message.contains("4009") => ObsError::Connection("OBS rejected the password".to_string()),
+            other => other,
+        })?;
+        Ok(client)
+    }
+
+    async fn send(&mut self, message: Value) -> Result<(), ObsError> {
+        self.ws.send(Message::Text(message.to_string()))
+            .await
+            .map_err(|e| ObsError::Connection(format!("OBS connection lost: {}", e)))
+    }
+
+    // Wait for the next message with the given opcode and return its "d" field
+    async fn receive_op(&mut self, op: u64) -> Result<Value, ObsError> {
+        let deadline = Instant::now() + TIMEOUT;
+        loop {
+            let message = tokio::time::timeout_at(deadline, self.ws.next())
+                .await
+                .map_err(|_| ObsError::Connection("Timed out waiting for OBS".to_string()))?;
+            let text = match message {
+                Some(Ok(Message::Text(text))) => text,
+                Some(Ok(Message::Close(frame))) => {
+                    let reason = frame.map(|f| format!("{} {}", u16::from(f.code), f.reason)).unwrap_or_default();
+                    return Err(ObsError::Connection(format!("OBS closed the connection {}", reason).trim_end().to_string()));
+                }
+                Some(Ok(_)) => continue,
+                Some(Err(e)) => return Err(ObsError::Connection(format!("OBS connection lost: {}", e))),
+                None => return Err(ObsError::Connection("OBS closed the connection".to_string())),
+            };
+            let Ok(message) = serde_json::from_str::<Value>(&text) else {
+                continue;
+            };
+            if message.get("op").and_then(Value::as_u64) == Some(op) {
+                return Ok(message.get("d").cloned().unwrap_or(Value::Null));
+            }
+        }
+    }
+}
+
+impl ObsConnection for ObsClient {
+    fn request<'a>(&'a mut self, request_type: &'a str, data: Value) -> ObsFuture<'a> {
+        Box::pin(async move {
+            self.next_id += 1;
+            let request_id = self.next_id.to_string();
+            self.send(json!({
+                "op": 6,
+                "d": { "requestType": request_type, "requestId": request_id, "requestData": data }
+            })).await?;
+            loop {
+                let response = self.receive_op(7).await?;
+                if response.get("requestId").and_then(Value::as_str) != Some(request_id.as_str()) {
+                    continue;
+                }
+                let status = response.get("requestStatus").cloned().unwrap_or(Value::Null);
+                if status.get("result").and_then(Value::as_bool) == Some(true) {
+                    return Ok(response.get("responseData").cloned().unwrap_or(Value::Null));
+                }
+                let comment = status.get("comment").and_then(Value::as_str).unwrap_or("request failed");
+                return Err(ObsError::Request(format!("OBS {}: {}", request_type, comment)));
+            }
+        })
+    }
+}
+
+async fn set_source_enabled(conn: &mut dyn ObsConnection, scene: &str, source: &str, enabled: bool) -> Result<(), ObsError> {
+    let item = conn.request("GetSceneItemId", json!({ "sceneName": scene, "sourceName": source })).await?;
+    let item_id = item.get("sceneItemId").cloned().ok_or_else(|| ObsError::Request(format!("Source '{}' not found in scene '{}'", source, scene)))?;
+    conn.request("SetSceneItemEnabled", json!({ "sceneName": scene, "sceneItemId": item_id, "sceneItemEnabled": enabled })).await?;
+    Ok(())
+}
+
+// Run one action; returns a source to hide later for ShowSource with a timeout
+pub async fn run_action(conn: &mut dyn ObsConnection, action: &ObsAction) -> Result<Option<(String, String, u32)>, ObsError> {
+    match action {
+        ObsAction::SwitchScene { scene } => {
+            conn.request("SetCurrentProgramScene", json!({ "sceneName": scene })).await?;
+        }
+        ObsAction::ShowSource { scene, source, hide_after_secs } => {
+            set_source_enabled(conn, scene, source, true).await?;
+            if let Some(secs) = hide_after_secs.filter(|s| *s > 0) {
+                return Ok(Some((scene.clone(), source.clone(), secs)));
+            }
+        }
+        ObsAction::HideSource { scene, source } => set_source_enabled(conn, scene, source, false).await?,
+        ObsAction::SaveReplayBuffer => {
+            conn.request("SaveReplayBuffer", json!({})).await?;
+        }
+    }
+    Ok(None)
+}
+
+pub fn actions_for(rules: &[ObsRule], events: &[CounterEvent]) -> Vec<ObsAction> {
+    rules.iter()
+        .filter(|rule| rule.enabled && events.contains(&rule.event))
+        .map(|rule| rule.action.clone())
+        .collect()
+}
+
+pub fn validate(config: &ObsConfig) -> Result<(), String> {
+    if config.host.trim().is_empty() || config.host.contains(char::is_whitespace) {
+        return Err("Enter the OBS host".to_string());
+    }
+    if config.port == 0 {
+        return Err("OBS port must be between 1 and 65535".to_string());
+    }
+    if config.rules.len() > MAX_RULES {
+        return Err(format!("At most {} OBS rules are allowed", MAX_RULES));
+    }
+    for (i, rule) in config.rules.iter().enumerate() {
+        let names: Vec<&String> = match &rule.action {
+            ObsAction::SwitchScene { scene } => vec![scene],
+            ObsAction::ShowSource { scene, source, .. } | ObsAction::HideSource { scene, source } => vec![scene, source],
+            ObsAction::SaveReplayBuffer => vec![],
+        };
+        if names.iter().any(|n| n.trim().is_empty()) {
+            return Err(format!("Rule {}: scene and source names cannot be empty", i + 1));
+        }
+        if let ObsAction::ShowSource { hide_after_secs: Some(secs), .. } = rule.action {
+            if secs > 3600 {
+                return Err(format!("Rule {}: hide after at most 3600 seconds", i + 1));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn read_config() -> ObsConfig {
+    get_app_data_file(CONFIG_FILE)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+pub fn config() -> ObsConfig {
+    let mut config = CONFIG.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    config.get_or_insert_with(read_config).clone()
+}
+
+// The settings file as stored in backups: the password stays on this machine
+fn backup_copy(data: &[u8]) -> Result<Vec<u8>, String> {
+    let config: ObsConfig = serde_json::from_slice(data).map_err(|e| format!("Invalid OBS settings: {}", e))?;
+    serde_json::to_vec_pretty(&ObsConfig { password: String::new(), ..config })
+        .map_err(|e| format!("Failed to serialize OBS settings: {}", e))
+}
+
+// A backed-up settings file about to be restored, given back the current password
+fn restored_copy(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut restored: ObsConfig = serde_json::from_slice(data).map_err(|e| format!("Invalid OBS settings: {}", e))?;
+    if restored.password.is_empty() {
+        restored.password = config().password;
+    }
+    serde_json::to_vec_pretty(&restored)
+        .map_err(|e| format!("Failed to serialize OBS settings: {}", e))
+}
+
+// Drop the cached settings so they are read from disk again (after a restore)
+pub fn reload_config() {
+    CONFIG.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take();
+}
+
+pub fn set_config(new_config: ObsConfig) -> Result<(), String> {
+    validate(&new_config)?;
+    let json = serde_json::to_string_pretty(&new_config)
+        .map_err(|e| format!("Failed to serialize OBS settings: {}", e))?;
+    write_atomic(&get_app_data_file(CONFIG_FILE)?, json.as_bytes())
+        .map_err(|e| format!("Failed to save OBS settings: {}", e))?;
+    *CONFIG.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(new_config);
+    Ok(())
+}
+
+// Connect with the given settings and return the OBS version
+pub async fn test_connection(config: &ObsConfig) -> Result<String, String> {
+    let mut client = ObsClient::connect(&config.host, config.port, &config.password).await.map_err(|e| e.to_string())?;
+    let version = client.request("GetVersion", json!({})).await.map_err(|e| e.to_string())?;
+    Ok(version.get("obsVersion").and_then(Value::as_str).unwrap_or("unknown").to_string())
+}
+
+type Target = (String, u16, String);
+
+// Run `actions` in order over the cached connection, opening it when needed. A
+// connection found dead is reopened once and the failed action retried. Returns
+// the actions left over when OBS cannot be reached.
+async fn run_actions(client: &mut Option<(ObsClient, Target)>, config: &ObsConfig, actions: Vec<ObsAction>, pending_hides: &mut Vec<(Instant, String, String)>) -> Vec<ObsAction> {
+    let target = (config.host.clone(), config.port, config.password.clone());
+    if client.as_ref().is_some_and(|(_, connected)| *connected != target) {
+        *client = None;
+    }
+    let mut queue: VecDeque<ObsAction> = actions.into();
+    let mut reconnected = false;
+    while let Some(action) = queue.front() {
+        if client.is_none() {
+            match ObsClient::connect(&config.host, config.port, &config.password).await {
+                Ok(connected) => {
+                    println!("🎬 Connected to OBS at {}:{}", config.host, config.port);
+                    *client = Some((connected, target.clone()));
+                }
+                Err(e) => {
+                    println!("⚠️ {}", e);
+                    return queue.into();
+                }
+            }
+        }
+        let Some((conn, _)) = client.as_mut() else {
+            return queue.into();
+        };
+        match run_action(conn, action).await {
+            Ok(Some((scene, source, secs))) => {
+                pending_hides.push((Instant::now() + Duration::from_secs(secs as u64), scene, source));
+            }
+            Ok(None) => println!("🎬 OBS: {:?}", action),
+            Err(ObsError::Connection(e)) => {
+                *client = None;
+                if reconnected {
+                    println!("⚠️ OBS action {:?} failed: {}", action, e);
+                    return queue.into();
+                }
+                println!("🔄 OBS connection lost ({}), reconnecting", e);
+                reconnected = true;
+                continue;
+            }
+            Err(ObsError::Request(e)) => println!("⚠️ OBS action {:?} failed: {}", action, e),
+        }
+        queue.pop_front();
+    }
+    Vec::new()
+}
+
+// Runs the rules for each state change over one lazily opened connection, which
+// is reopened after errors or when the connection settings change
+pub fn start(shared_state: SharedWinState, mut rx: broadcast::Receiver<WinState>) {
+    std::thread::spawn(move || {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async move {
+            let mut client: Option<(ObsClient, Target)> = None;
+            let mut pending_hides: Vec<(Instant, String, String)> = Vec::new();
+            let mut previous = shared_state.lock().unwrap().clone();
+            loop {
+                let next_hide = pending_hides.iter().map(|(at, _, _)| *at).min();
+                let mut actions = Vec::new();
+                tokio::select! {
+                    received = rx.recv() => {
+                        let state = match received {
+                            Ok(state) => state,
+                            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                            Err(broadcast::error::RecvError::Closed) => break,
+                        };
+                        let events = events_for(&previous, &state, sounds::milestone_every());
+                        previous = state;
+                        let config = config();
+                        if config.enabled && !events.is_empty() {
+                            actions = actions_for(&config.rules, &events);
+                        }
+                    }
+                    _ = tokio::time::sleep_until(next_hide.unwrap_or_else(Instant::now)), if next_hide.is_some() => {
+                        let now = Instant::now();
+                        pending_hides.retain(|(at, scene, source)| {
+                            if *at <= now {
+                                actions.push(ObsAction::HideSource { scene: scene.clone(), source: source.clone() });
+                            }
+                            *at > now
+                        });
+                    }
+                }
+                if actions.is_empty() {
+                    continue;
+                }
+
+                let config = config();
+                for action in run_actions(&mut client, &config, actions, &mut pending_hides).await {
+                    match action {
+                        // A shown source must not stay up because OBS was briefly away
+                        ObsAction::HideSource { scene, source } if config.enabled => {
+                            pending_hides.push((Instant::now() + HIDE_RETRY, scene, source));
+                        }
+                        other => println!("⚠️ OBS unreachable, dropped {:?}", other),
+                    }
+                }
+            }
+        });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Records requests and answers scene item lookups
+    struct MockObs {
+        requests: Vec<(String, Value)>,
+    }
+
+    impl ObsConnection for MockObs {
+        fn request<'a>(&'a mut self, request_type: &'a str, data: Value) -> ObsFuture<'a> {
+            Box::pin(async move {
+                self.requests.push((request_type.to_string(), data.clone()));
+                match request_type {
+                    "GetSceneItemId" if data["sourceName"] == "Confetti" => Ok(json!({ "sceneItemId": 7 })),
+                    "GetSceneItemId" => Err(ObsError::Request("OBS GetSceneItemId: No source was found".to_string())),
+                    _ => Ok(Value::Null),
+                }
+            })
+        }
+    }
+
+    #[test]
+    fn auth_follows_the_v5_handshake() {
+        let salt = "lM1GncleQOaCu9lT1yeUZhFYnqhsLLP1G5lAGo3ixaI=";
+        let challenge = "+IxH4CnCiqpX1rM9scsNynZzbOe4KhDeYcTNS3PDaeY=";
+        assert_eq!(auth_response("supersecretpassword", salt, challenge), "1Ct943GAT+6YQUUX47Ia/ncufilbe6+oD6lY+5kaCu4=");
+        assert_ne!(auth_response("wrong", salt, challenge), "1Ct943GAT+6YQUUX47Ia/ncufilbe6+oD6lY+5kaCu4=");
+    }
+
+    #[test]
+    fn rules_pick_actions_by_event() {
+        let rules = vec![
+            ObsRule { enabled: true, event: CounterEvent::GoalReached, action: ObsAction::SwitchScene { scene: "Win".to_string() } },
+            ObsRule { enabled: true, event: CounterEvent::GoalReached, action: ObsAction::SaveReplayBuffer },
+            ObsRule { enabled: false, event: CounterEvent::GoalReached, action: ObsAction::SwitchScene { scene: "Off".to_string() } },
+            ObsRule { enabled: true, event: CounterEvent::Reset, action: ObsAction::SwitchScene { scene: "Start".to_string() } },
+        ];
+        assert_eq!(
+            actions_for(&rules, &[CounterEvent::Change, CounterEvent::GoalReached]),
+            vec![ObsAction::SwitchScene { scene: "Win".to_string() }, ObsAction::SaveReplayBuffer]
+        );
+        assert!(actions_for(&rules, &[CounterEvent::Change]).is_empty());
+    }
+
+    #[tokio::test]
+    async fn actions_become_obs_requests() {
+        let mut mock = MockObs { requests: Vec::new() };
+        let show = ObsAction::ShowSource { scene: "Main".to_string(), source: "Confetti".to_string(), hide_after_secs: Some(5) };
+        assert_eq!(run_action(&mut mock, &show).await, Ok(Some(("Main".to_string(), "Confetti".to_string(), 5))));
+        run_action(&mut mock, &ObsAction::SwitchScene { scene: "Win".to_string() }).await.unwrap();
+        let missing = ObsAction::HideSource { scene: "Main".to_string(), source: "Nope".to_string() };
+        assert!(run_action(&mut mock, &missing).await.is_err());
+
+        let types: Vec<&str> = mock.requests.iter().map(|(t, _)| t.as_str()).collect();
+        assert_eq!(types, vec!["GetSceneItemId", "SetSceneItemEnabled", "SetCurrentProgramScene", "GetSceneItemId"]);
+        assert_eq!(mock.requests[1].1, json!({ "sceneName": "Main", "sceneItemId": 7, "sceneItemEnabled": true }));
+    }
+
+    #[tokio::test]
+    async fn handshake_and_requests_against_a_mock_server() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let hello = json!({ "op": 0, "d": { "obsWebSocketVersion": "5.4.2", "rpcVersion": 1, "authentication": { "challenge": "ch", "salt": "sa" } } });
+            ws.send(Message::Text(hello.to_string())).await.unwrap();
+
+            let identify: Value = serde_json::from_str(ws.next().await.unwrap().unwrap().to_text().unwrap()).unwrap();
+            assert_eq!(identify["op"], 1);
+            assert_eq!(identify["d"]["authentication"], auth_response("secret", "sa", "ch"));
+            ws.send(Message::Text(json!({ "op": 2, "d": { "negotiatedRpcVersion": 1 } }).to_string())).await.unwrap();
+
+            let request: Value = serde_json::from_str(ws.next().await.unwrap().unwrap().to_text().unwrap()).unwrap();
+            assert_eq!(request["d"]["requestType"], "SetCurrentProgramScene");
+            // An unrelated event first, then the response
+            ws.send(Message::Text(json!({ "op": 5, "d": { "eventType": "SceneNameChanged" } }).to_string())).await.unwrap();
+            let response = json!({ "op": 7, "d": {
+                "requestType": "SetCurrentProgramScene",
+                "requestId": request["d"]["requestId"],
+                "requestStatus": { "result": true, "code": 100 }
+            } });
+            ws.send(Message::Text(response.to_string())).await.unwrap();
+            request["d"]["requestData"].clone()
+        });
+
+        let mut client = ObsClient::connect("127.0.0.1", port, "secret").await.unwrap();
+        run_action(&mut client, &ObsAction::SwitchScene { scene: "Goal!".to_string() }).await.unwrap();
+        assert_eq!(server.await.unwrap(), json!({ "sceneName": "Goal!" }));
+    }
+
+    #[tokio::test]
+    async fn reconnects_once_when_the_connection_went_stale() {
+        async fn identify(ws: &mut WebSocketStream<TcpStream>) {
+            ws.send(Message::Text(json!({ "op": 0, "d": { "rpcVersion": 1 } }).to_string())).await.unwrap();
+            ws.next().await.unwrap().unwrap();
+            ws.send(Message::Text(json!({ "op": 2, "d": { "negotiatedRpcVersion": 1 } }).to_string())).await.unwrap();
+        }
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            // The first connection dies on its first request, like OBS after a restart
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut stale = tokio_tungstenite::accept_async(stream).await.unwrap();
+            identify(&mut stale).await;
+            stale.next().await.unwrap().unwrap();
+            drop(stale);
+
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            identify(&mut ws).await;
+            let request: Value = serde_json::from_str(ws.next().await.unwrap().unwrap().to_text().unwrap()).unwrap();
+            let response = json!({ "op": 7, "d": { "requestId": request["d"]["requestId"], "requestStatus": { "result": true, "code": 100 } } });
+            ws.send(Message::Text(response.to_string())).await.unwrap();
+            request["d"]["requestType"].clone()
+        });
+
+        let config = ObsConfig { enabled: true, host: "127.0.0.1".to_string(), port, ..ObsConfig::default() };
+        let mut client = None;
+        let mut hides = Vec::new();
+        let left = run_actions(&mut client, &config, vec![ObsAction::SaveReplayBuffer], &mut hides).await;
+        assert!(left.is_empty());
+        assert_eq!(server.await.unwrap(), "SaveReplayBuffer");
+
+        // Nobody listening: the action comes back to the caller
+        drop(client.take());
+        let closed = ObsConfig { port: 1, ..config };
+        let hide = ObsAction::HideSource { scene: "Main".to_string(), source: "Confetti".to_string() };
+        assert_eq!(run_actions(&mut client, &closed, vec![hide.clone()], &mut hides).await, vec![hide]);
+    }
+}
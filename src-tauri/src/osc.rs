@@ -0,0 +1,417 @@
+// OSC over UDP for VTuber rigs, lighting controllers and TouchOSC pads.
+// The listener maps <prefix>/increment, /decrement, /set, /goal and /reset to
+// counter actions; the sender emits <prefix>/state (win, goal, preset) on every
+// broadcast. Settings live in win_count_osc.json, shown with the overlay server settings.
+
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use tokio::net::UdpSocket;
+use tokio::sync::broadcast;
+
+use crate::backup::DataFile;
+use crate::integration::ConfigFile;
+use crate::live_events::{self, CounterAction};
+use crate::{SharedWinState, WinState};
+
+const CONFIG_FILE: &str = "win_count_osc.json";
+pub const DATA_FILE: DataFile = DataFile::json::<OscConfig>(CONFIG_FILE);
+pub const DEFAULT_PREFIX: &str = "/wincount";
+pub const DEFAULT_LISTEN_PORT: u16 = 9000;
+pub const DEFAULT_SEND_PORT: u16 = 9001;
+const MAX_PACKET: usize = 4096;
+const MAX_BUNDLE_DEPTH: usize = 4;
+
+static CONFIG: ConfigFile<OscConfig> = ConfigFile::new(CONFIG_FILE, "OSC settings");
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OscConfig {
+    #[serde(default)]
+    pub listen_enabled: bool,
+    // 127.0.0.1 by default; 0.0.0.0 accepts controllers on the local network
+    #[serde(default = "default_host")]
+    pub listen_host: String,
+    #[serde(default = "default_listen_port")]
+    pub listen_port: u16,
+    #[serde(default)]
+    pub send_enabled: bool,
+    #[serde(default = "default_host")]
+    pub send_host: String,
+    #[serde(default = "default_send_port")]
+    pub send_port: u16,
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+}
+
+fn default_host() -> String {
+    "127.0.0.1".to_string()
+}
+
+fn default_listen_port() -> u16 {
+    DEFAULT_LISTEN_PORT
+}
+
+fn default_send_port() -> u16 {
+    DEFAULT_SEND_PORT
+}
+
+fn default_prefix() -> String {
+    DEFAULT_PREFIX.to_string()
+}
+
+impl Default for OscConfig {
+    fn default() -> Self {
+        Self {
+            listen_enabled: false,
+            listen_host: default_host(),
+            listen_port: default_listen_port(),
+            send_enabled: false,
+            send_host: default_host(),
+            send_port: default_send_port(),
+            prefix: default_prefix(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OscArg {
+    Int(i32),
+    Float(f32),
+    Str(String),
+}
+
+impl OscArg {
+    fn as_i32(&self) -> Option<i32> {
+        match self {
+            OscArg::Int(n) => Some(*n),
+            OscArg::Float(f) if f.is_finite() => Some(f.round() as i32),
+            OscArg::Str(s) => s.trim().parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OscMessage {
+    pub address: String,
+    pub args: Vec<OscArg>,
+}
+
+fn pad4(len: usize) -> usize {
+    (len + 4) & !3
+}
+
+// OSC string: bytes, a NUL and padding to a multiple of 4
+fn read_string(data: &[u8], pos: &mut usize) -> Option<String> {
+    let rest = data.get(*pos..)?;
+    let end = rest.iter().position(|b| *b == 0)?;
+    let text = std::str::from_utf8(&rest[..end]).ok()?.to_string();
+    *pos += pad4(end);
+    (*pos <= data.len()).then_some(text)
+}
+
+fn read_u32(data: &[u8], pos: &mut usize) -> Option<u32> {
+    let bytes: [u8; 4] = data.get(*pos..*pos + 4)?.try_into().ok()?;
+    *pos += 4;
+    Some(u32::from_be_bytes(bytes))
+}
+
+fn decode_message(data: &[u8]) -> Option<OscMessage> {
+    let mut pos = 0;
+    let address = read_string(data, &mut pos)?;
+    if !address.starts_with('/') {
+        return None;
+    }
+    // Old senders may leave out the type tags
+    let tags = if pos < data.len() { read_string(data, &mut pos)? } else { ",".to_string() };
+    let mut args = Vec::new();
+    for tag in tags.strip_prefix(',')?.chars() {
+        args.push(match tag {
+            'i' => OscArg::Int(read_u32(data, &mut pos)? as i32),
+            'f' => OscArg::Float(f32::from_bits(read_u32(data, &mut pos)?)),
+            's' => OscArg::Str(read_string(data, &mut pos)?),
+            // Booleans and impulses carry no data
+            'T' => OscArg::Int(1),
+            'F' => OscArg::Int(0),
+            'I' | 'N' => continue,
+            _ => return None,
+        });
+    }
+    Some(OscMessage { address, args })
+}
+
+// Messages in a packet; bundles are flattened and their time tags ignored
+pub fn decode_packet(data: &[u8]) -> Vec<OscMessage> {
+    fn walk(data: &[u8], depth: usize, out: &mut Vec<OscMessage>) {
+        let Some(elements) = data.strip_prefix(b"#bundle\0") else {
+            out.extend(decode_message(data));
+            return;
+        };
+        if depth >= MAX_BUNDLE_DEPTH || elements.len() < 8 {
+            return;
+        }
+        let mut pos = 8;
+        while let Some(size) = read_u32(elements, &mut pos) {
+            let Some(element) = elements.get(pos..pos + size as usize) else {
+                return;
+            };
+            walk(element, depth + 1, out);
+            pos += size as usize;
+        }
+    }
+    let mut out = Vec::new();
+    walk(data, 0, &mut out);
+    out
+}
+
+fn write_string(out: &mut Vec<u8>, text: &str) {
+    out.extend_from_slice(text.as_bytes());
+    out.resize(out.len() + pad4(text.len()) - text.len(), 0);
+}
+
+pub fn encode_message(message: &OscMessage) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_string(&mut out, &message.address);
+    let tags: String = std::iter::once(',')
+        .chain(message.args.iter().map(|arg| match arg {
+            OscArg::Int(_) => 'i',
+            OscArg::Float(_) => 'f',
+            OscArg::Str(_) => 's',
+        }))
+        .collect();
+    write_string(&mut out, &tags);
+    for arg in &message.args {
+        match arg {
+            OscArg::Int(n) => out.extend_from_slice(&n.to_be_bytes()),
+            OscArg::Float(f) => out.extend_from_slice(&f.to_bits().to_be_bytes()),
+            OscArg::Str(s) => write_string(&mut out, s),
+        }
+    }
+    out
+}
+
+fn normalized_prefix(prefix: &str) -> &str {
+    prefix.trim().trim_end_matches('/')
+}
+
+// Counter action for an incoming message, None for other addresses or no-op values.
+// Buttons on control surfaces send 1 on press and 0 on release, so a zero step is ignored.
+pub fn action_for(message: &OscMessage, prefix: &str) -> Option<CounterAction> {
+    let command = message.address.strip_prefix(normalized_prefix(prefix))?;
+    let value = message.args.first().and_then(OscArg::as_i32);
+    // Bounds are checked when the action applies
+    let step = || value.unwrap_or(1);
+    match command {
+        "/increment" if step() > 0 => Some(CounterAction::Increase { amount: step() }),
+        "/decrement" if step() > 0 => Some(CounterAction::Decrease { amount: step() }),
+        "/set" => value.map(|value| CounterAction::SetWin { value }),
+        "/goal" => value.map(|value| CounterAction::SetGoal { value }),
+        "/reset" if value != Some(0) => Some(CounterAction::ResetWin),
+        _ => None,
+    }
+}
+
+pub fn state_message(state: &WinState, prefix: &str) -> OscMessage {
+    OscMessage {
+        address: format!("{}/state", normalized_prefix(prefix)),
+        args: vec![OscArg::Int(state.win), OscArg::Int(state.goal), OscArg::Str(state.current_preset.clone())],
+    }
+}
+
+pub fn validate(config: &OscConfig) -> Result<(), String> {
+    let prefix = normalized_prefix(&config.prefix);
+    if !prefix.starts_with('/') || prefix.contains(|c: char| c.is_whitespace() || "#*,?[]{}".contains(c)) {
+        return Err("OSC address prefix must start with '/' and may not contain spaces or # * , ? [ ] { }".to_string());
+    }
+    for (host, port, what) in [(&config.listen_host, config.listen_port, "listen"), (&config.send_host, config.send_port, "send")] {
+        if host.trim().is_empty() || host.contains(char::is_whitespace) {
+            return Err(format!("Enter the OSC {} host", what));
+        }
+        if port == 0 {
+            return Err(format!("OSC {} port must be between 1 and 65535", what));
+        }
+    }
+    if config.listen_enabled && config.send_enabled && config.listen_port == config.send_port && config.listen_host == config.send_host {
+        return Err("OSC would send its state to its own listener, use different ports".to_string());
+    }
+    Ok(())
+}
+
+pub fn config() -> OscConfig {
+    CONFIG.get()
+}
+
+pub fn set_config(new_config: OscConfig) -> Result<(), String> {
+    validate(&new_config)?;
+    CONFIG.save(new_config)
+}
+
+// Rebinds the listener if the file changed
+pub fn reload_config() {
+    CONFIG.reload();
+}
+
+// Receive packets on `socket` and hand their actions to `apply` until the socket fails
+pub async fn listen(socket: &UdpSocket, prefix: &str, mut apply: impl FnMut(SocketAddr, CounterAction)) -> Result<(), String> {
+    let mut buffer = [0u8; MAX_PACKET];
+    loop {
+        let (len, from) = socket.recv_from(&mut buffer).await.map_err(|e| format!("OSC receive failed: {}", e))?;
+        for message in decode_packet(&buffer[..len]) {
+            if let Some(action) = action_for(&message, prefix) {
+                apply(from, action);
+            }
+        }
+    }
+}
+
+async fn run_listener(app: tauri::AppHandle, state: SharedWinState, broadcast_tx: broadcast::Sender<WinState>) {
+    let mut config_rx = CONFIG.subscribe();
+    loop {
+        let config = config_rx.borrow_and_update().clone();
+        if config.listen_enabled {
+            let address = format!("{}:{}", config.listen_host.trim(), config.listen_port);
+            match UdpSocket::bind(&address).await {
+                Ok(socket) => {
+                    println!("🎛️ OSC listening on udp://{}{}", address, normalized_prefix(&config.prefix));
+                    let result = tokio::select! {
+                        result = listen(&socket, &config.prefix, |from, action| {
+                            println!("🎛️ OSC from {}: {:?}", from, action);
+                            if let Err(e) = live_events::apply_action(&app, &state, &broadcast_tx, action) {
+                                println!("⚠️ OSC action skipped: {}", e);
+                            }
+                        }) => result,
+                        _ = config_rx.changed() => continue,
+                    };
+                    if let Err(e) = result {
+                        println!("⚠️ {}", e);
+                    }
+                }
+                Err(e) => println!("⚠️ Failed to bind OSC listener on {}: {}", address, e),
+            }
+        }
+        // Wait for new settings before binding again
+        if config_rx.changed().await.is_err() {
+            return;
+        }
+    }
+}
+
+async fn run_sender(mut rx: broadcast::Receiver<WinState>) {
+    let socket = match UdpSocket::bind("0.0.0.0:0").await {
+        Ok(socket) => socket,
+        Err(e) => {
+            println!("⚠️ Failed to open OSC sender socket: {}", e);
+            return;
+        }
+    };
+    loop {
+        let state = match rx.recv().await {
+            Ok(state) => state,
+            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => return,
+        };
+        let config = config();
+        if !config.send_enabled {
+            continue;
+        }
+        let packet = encode_message(&state_message(&state, &config.prefix));
+        let target = format!("{}:{}", config.send_host.trim(), config.send_port);
+        if let Err(e) = socket.send_to(&packet, &target).await {
+            println!("⚠️ OSC send to {} failed: {}", target, e);
+        }
+    }
+}
+
+pub fn start(app: tauri::AppHandle, state: SharedWinState, broadcast_tx: broadcast::Sender<WinState>) {
+    let rx = broadcast_tx.subscribe();
+    std::thread::spawn(move || {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async move {
+            tokio::join!(run_listener(app, state, broadcast_tx), run_sender(rx));
+        });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(address: &str, args: Vec<OscArg>) -> OscMessage {
+        OscMessage { address: address.to_string(), args }
+    }
+
+    #[test]
+    fn encodes_and_decodes_messages() {
+        let state = WinState::sample(7, 10);
+        let packet = encode_message(&state_message(&state, "/wincount/"));
+        // "/wincount/state" (15 + NUL = 16), ",iis" (4 + NUL -> 8), two ints, "Ranked" (6 + NUL -> 8)
+        assert_eq!(packet.len(), 16 + 8 + 4 + 4 + 8);
+        assert_eq!(&packet[..16], b"/wincount/state\0");
+        assert_eq!(decode_packet(&packet), vec![message("/wincount/state", vec![OscArg::Int(7), OscArg::Int(10), OscArg::Str("Ranked".to_string())])]);
+
+        let float = encode_message(&message("/wincount/increment", vec![OscArg::Float(2.0)]));
+        assert_eq!(decode_packet(&float), vec![message("/wincount/increment", vec![OscArg::Float(2.0)])]);
+        assert!(decode_packet(b"garbage").is_empty());
+        assert!(decode_packet(&packet[..20]).is_empty());
+    }
+
+    #[test]
+    fn flattens_bundles() {
+        let first = encode_message(&message("/wincount/increment", vec![]));
+        let second = encode_message(&message("/wincount/goal", vec![OscArg::Int(20)]));
+        let mut bundle = b"#bundle\0".to_vec();
+        bundle.extend_from_slice(&1u64.to_be_bytes());
+        for element in [&first, &second] {
+            bundle.extend_from_slice(&(element.len() as u32).to_be_bytes());
+            bundle.extend_from_slice(element);
+        }
+        let messages = decode_packet(&bundle);
+        assert_eq!(messages.len(), 2);
+        assert_eq!(action_for(&messages[1], DEFAULT_PREFIX), Some(CounterAction::SetGoal { value: 20 }));
+    }
+
+    #[test]
+    fn maps_addresses_to_actions() {
+        let p = DEFAULT_PREFIX;
+        assert_eq!(action_for(&message("/wincount/increment", vec![]), p), Some(CounterAction::Increase { amount: 1 }));
+        assert_eq!(action_for(&message("/wincount/increment", vec![OscArg::Int(3)]), p), Some(CounterAction::Increase { amount: 3 }));
+        // Button release
+        assert_eq!(action_for(&message("/wincount/increment", vec![OscArg::Float(0.0)]), p), None);
+        assert_eq!(action_for(&message("/wincount/decrement", vec![OscArg::Float(1.0)]), p), Some(CounterAction::Decrease { amount: 1 }));
+        assert_eq!(action_for(&message("/wincount/set", vec![OscArg::Int(0)]), p), Some(CounterAction::SetWin { value: 0 }));
+        assert_eq!(action_for(&message("/wincount/set", vec![]), p), None);
+        assert_eq!(action_for(&message("/wincount/reset", vec![]), p), Some(CounterAction::ResetWin));
+        assert_eq!(action_for(&message("/other/increment", vec![]), p), None);
+        assert_eq!(action_for(&message("/rig/wins/goal", vec![OscArg::Int(15)]), "/rig/wins"), Some(CounterAction::SetGoal { value: 15 }));
+    }
+
+    #[test]
+    fn validates_settings() {
+        let mut config = OscConfig::default();
+        assert!(validate(&config).is_ok());
+        config.prefix = "wincount".to_string();
+        assert!(validate(&config).is_err());
+        config.prefix = DEFAULT_PREFIX.to_string();
+        config.listen_enabled = true;
+        config.send_enabled = true;
+        config.send_port = config.listen_port;
+        assert!(validate(&config).is_err());
+    }
+
+    #[tokio::test]
+    async fn listener_turns_packets_into_actions() {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let address = socket.local_addr().unwrap();
+        let sender = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        for packet in [
+            encode_message(&message("/wincount/increment", vec![OscArg::Int(2)])),
+            encode_message(&message("/elsewhere/reset", vec![])),
+            encode_message(&message("/wincount/set", vec![OscArg::Int(12)])),
+        ] {
+            sender.send_to(&packet, address).await.unwrap();
+        }
+
+        let mut applied = Vec::new();
+        let _ = tokio::time::timeout(std::time::Duration::from_millis(300), listen(&socket, DEFAULT_PREFIX, |_, action| applied.push(action))).await;
+        assert_eq!(applied, vec![CounterAction::Increase { amount: 2 }, CounterAction::SetWin { value: 12 }]);
+    }
+}
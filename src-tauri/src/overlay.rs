@@ -0,0 +1,229 @@
+// Overlay access token and the authenticated /sounds/<file> route of the overlay
+// HTTP server. The token is generated once, stored in app data and handed to the
+// overlay in its URL (?token=...); sound requests without it are refused.
+
+use rand::RngCore;
+use std::fs;
+use std::sync::OnceLock;
+
+use crate::audio::AudioFormat;
+use crate::{settings, sounds};
+use crate::{get_app_data_file, write_atomic};
+
+const TOKEN_FILE: &str = "win_count_overlay_token";
+
+static TOKEN: OnceLock<String> = OnceLock::new();
+
+pub struct HttpReply {
+    pub status: &'static str,
+    pub content_type: &'static str,
+    pub body: Vec<u8>,
+    pub location: Option<String>,
+}
+
+impl HttpReply {
+    pub fn ok(content_type: &'static str, body: Vec<u8>) -> Self {
+        Self { status: "200 OK", content_type, body, location: None }
+    }
+
+    pub fn error(status: &'static str, message: &str) -> Self {
+        Self { status, content_type: "text/plain; charset=utf-8", body: message.as_bytes().to_vec(), location: None }
+    }
+
+    pub fn redirect(location: String) -> Self {
+        Self { status: "301 Moved Permanently", content_type: "text/plain; charset=utf-8", body: Vec::new(), location: Some(location) }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let location = self.location.as_ref()
+            .map(|l| format!("Location: {}\r\n", l))
+            .unwrap_or_default();
+        let mut bytes = format!(
+            "HTTP/1.1 {}\r\n\
+            Content-Type: {}\r\n\
+            Content-Length: {}\r\n\
+            {}\
+            Access-Control-Allow-Origin: *\r\n\
+            Cache-Control: no-store\r\n\
+            Connection: close\r\n\
+            \r\n",
+            self.status,
+            self.content_type,
+            self.body.len(),
+            location
+        ).into_bytes();
+        bytes.extend_from_slice(&self.body);
+        bytes
+    }
+}
+
+// Random token kept in app data under `file`; `what` names it in warnings
+pub fn load_or_create_token(file: &str, what: &str) -> String {
+    let path = get_app_data_file(file).ok();
+    if let Some(existing) = path.as_ref().and_then(|p| fs::read_to_string(p).ok()) {
+        let existing = existing.trim();
+        if existing.len() >= 32 {
+            return existing.to_string();
+        }
+    }
+
+    let mut bytes = [0u8; 24];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    let token = hex::encode(bytes);
+    match path {
+        Some(path) => {
+            if let Err(e) = write_atomic(&path, token.as_bytes()) {
+                println!("⚠️ Failed to save {}, it will change on restart: {}", what, e);
+            }
+        }
+        None => println!("⚠️ No app data directory, {} will change on restart", what),
+    }
+    token
+}
+
+pub fn overlay_token() -> &'static str {
+    TOKEN.get_or_init(|| load_or_create_token(TOKEN_FILE, "overlay token"))
+}
+
+// Address of the overlay HTTP server, e.g. "http://127.0.0.1:777"
+pub fn server_base() -> String {
+    format!("http://127.0.0.1:{}", settings::http_port())
+}
+
+// Overlays connect to the default WebSocket port unless the link names another one
+pub fn ws_port_param() -> String {
+    match settings::ws_port() {
+        settings::DEFAULT_WS_PORT => String::new(),
+        port => format!("&ws={}", port),
+    }
+}
+
+// Overlay URL carrying the token; `with_sounds` makes the overlay play sound events itself
+pub fn overlay_url(with_sounds: bool) -> String {
+    let mut url = format!("{}/overlay.html?token={}{}", server_base(), overlay_token(), ws_port_param());
+    if with_sounds {
+        url.push_str("&sounds=1");
+    }
+    url
+}
+
+// Split "/path?query" into its path and query parts
+fn split_target(target: &str) -> (&str, &str) {
+    match target.split_once('?') {
+        Some((path, query)) => (path, query),
+        None => (target, ""),
+    }
+}
+
+pub fn query_param<'a>(query: &'a str, key: &str) -> Option<&'a str> {
+    query.split('&').find_map(|pair| {
+        let (k, v) = pair.split_once('=')?;
+        (k == key).then_some(v)
+    })
+}
+
+// Compare without bailing out on the first differing byte
+pub fn tokens_match(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given.bytes().zip(expected.bytes()).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+// Value of a request header, matched case-insensitively
+pub fn header_value<'a>(request: &'a str, header: &str) -> Option<&'a str> {
+    request.lines().skip(1).find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        name.trim().eq_ignore_ascii_case(header).then(|| value.trim())
+    })
+}
+
+// Token from the query string or an X-Overlay-Token header
+fn request_token<'a>(request: &'a str, query: &'a str) -> Option<&'a str> {
+    query_param(query, "token").or_else(|| header_value(request, "x-overlay-token"))
+}
+
+fn mime_for(filename: &str) -> Option<&'static str> {
+    let extension = filename.rsplit_once('.')?.1.to_ascii_lowercase();
+    [AudioFormat::Mp3, AudioFormat::Wav, AudioFormat::Ogg, AudioFormat::M4a]
+        .into_iter()
+        .find(|f| f.extension() == extension)
+        .map(|f| f.mime_type())
+}
+
+fn authorized_sound(request: &str, target: &str, expected_token: &str) -> Result<String, HttpReply> {
+    let (path, query) = split_target(target);
+    let authorized = request_token(request, query)
+        .map(|token| tokens_match(token, expected_token))
+        .unwrap_or(false);
+    if !authorized {
+        return Err(HttpReply::error("401 Unauthorized", "401 - Missing or invalid overlay token"));
+    }
+    let filename = path.strip_prefix("/sounds/").unwrap_or_default();
+    if filename.is_empty() || filename.contains(['/', '\\', ':', '%']) || filename.starts_with('.') {
+        return Err(HttpReply::error("400 Bad Request", "400 - Invalid sound name"));
+    }
+    Ok(filename.to_string())
+}
+
+// Handle "GET /sounds/<file>": only clips that are active in the sound library are served
+pub fn sound_response(request: &str, target: &str) -> HttpReply {
+    let filename = match authorized_sound(request, target, overlay_token()) {
+        Ok(filename) => filename,
+        Err(reply) => return reply,
+    };
+
+    let active = sounds::library().map(|l| l.references(&filename)).unwrap_or(false);
+    let Some(content_type) = mime_for(&filename).filter(|_| active) else {
+        return HttpReply::error("404 Not Found", "404 - Sound not found");
+    };
+    match sounds::read_sound(&filename) {
+        Ok(body) => HttpReply::ok(content_type, body),
+        Err(e) => {
+            println!("❌ Failed to serve sound {}: {}", filename, e);
+            HttpReply::error("404 Not Found", "404 - Sound not found")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKEN: &str = "0123456789abcdef0123456789abcdef";
+
+    fn get(target: &str, headers: &str) -> String {
+        format!("GET {} HTTP/1.1\r\nHost: 127.0.0.1:777\r\n{}\r\n", target, headers)
+    }
+
+    #[test]
+    fn requires_the_overlay_token() {
+        let target = "/sounds/abc.mp3";
+        assert!(authorized_sound(&get(target, ""), target, TOKEN).is_err());
+
+        let wrong = "/sounds/abc.mp3?token=nope";
+        assert!(authorized_sound(&get(wrong, ""), wrong, TOKEN).is_err());
+
+        let good = format!("/sounds/abc.mp3?token={}", TOKEN);
+        assert_eq!(authorized_sound(&get(&good, ""), &good, TOKEN).ok().as_deref(), Some("abc.mp3"));
+
+        let header = format!("X-Overlay-Token: {}\r\n", TOKEN);
+        assert!(authorized_sound(&get(target, &header), target, TOKEN).is_ok());
+    }
+
+    #[test]
+    fn rejects_path_tricks() {
+        for bad in ["/sounds/../secret.txt", "/sounds/a/b.mp3", "/sounds/..%2Fsecret.txt", "/sounds/", "/sounds/.hidden"] {
+            let target = format!("{}?token={}", bad, TOKEN);
+            assert!(authorized_sound(&get(&target, ""), &target, TOKEN).is_err(), "{} should be rejected", bad);
+        }
+    }
+
+    #[test]
+    fn mime_types_follow_the_extension() {
+        assert_eq!(mime_for("abc.mp3"), Some("audio/mpeg"));
+        assert_eq!(mime_for("abc.WAV"), Some("audio/wav"));
+        assert_eq!(mime_for("abc.ogg"), Some("audio/ogg"));
+        assert_eq!(mime_for("abc.m4a"), Some("audio/mp4"));
+        assert_eq!(mime_for("abc.exe"), None);
+        assert_eq!(mime_for("abc"), None);
+    }
+}
@@ -0,0 +1,282 @@
+// Named overlay instances. Each one has its own layout, visibility flags and theme,
+// is served at /overlay/<id> and receives its config in the WebSocket hello.
+// Stored in win_count_overlays.json; toggles on one instance never touch the others.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::sync::{Mutex, OnceLock};
+use tokio::sync::broadcast;
+
+use crate::backup::DataFile;
+use crate::overlay::{self, HttpReply};
+use crate::themes;
+use crate::{get_app_data_file, write_atomic, WinState};
+
+const OVERLAYS_FILE: &str = "win_count_overlays.json";
+pub const DATA_FILE: DataFile = DataFile::json::<Vec<OverlayInstance>>(OVERLAYS_FILE);
+pub const MAX_OVERLAYS: usize = 20;
+
+// Serializes read-modify-write of the overlays file
+static OVERLAYS_LOCK: Mutex<()> = Mutex::new(());
+
+// Config changes are pushed to the instance's open WebSocket connections
+static CONFIG_TX: OnceLock<broadcast::Sender<OverlayInstance>> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverlayLayout {
+    #[default]
+    Full,
+    Compact,
+    GoalBar,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OverlayInstance {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub layout: OverlayLayout,
+    pub show_goal: bool,
+    pub show_crown: bool,
+    #[serde(default = "default_theme")]
+    pub theme: String,
+}
+
+fn default_theme() -> String {
+    themes::BUILTIN_THEME.to_string()
+}
+
+// Fields the UI may change; anything left out stays as it is
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct OverlayUpdate {
+    pub name: Option<String>,
+    pub layout: Option<OverlayLayout>,
+    pub show_goal: Option<bool>,
+    pub show_crown: Option<bool>,
+    pub theme: Option<String>,
+}
+
+impl OverlayInstance {
+    // The shared state as this overlay should see it: its own visibility flags
+    pub fn view(&self, state: &WinState) -> WinState {
+        WinState {
+            show_goal: self.show_goal,
+            show_crown: self.show_crown,
+            ..state.clone()
+        }
+    }
+
+    pub fn url(&self) -> String {
+        format!("{}/overlay/{}?token={}{}", overlay::server_base(), self.id, overlay::overlay_token(), overlay::ws_port_param())
+    }
+}
+
+pub fn config_channel() -> &'static broadcast::Sender<OverlayInstance> {
+    CONFIG_TX.get_or_init(|| broadcast::channel(16).0)
+}
+
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= 64
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+// "Main Scene" -> "main-scene"; numbered when taken
+fn unique_id(name: &str, existing: &[OverlayInstance]) -> String {
+    let mut base = String::new();
+    for c in name.trim().to_lowercase().chars() {
+        if c.is_ascii_alphanumeric() {
+            base.push(c);
+        } else if !base.is_empty() && !base.ends_with('-') {
+            base.push('-');
+        }
+    }
+    let base: String = base.trim_end_matches('-').chars().take(48).collect();
+    let base = if base.is_empty() { "overlay".to_string() } else { base };
+
+    let taken = |id: &str| existing.iter().any(|o| o.id == id);
+    if !taken(&base) {
+        return base;
+    }
+    (2..).map(|n| format!("{}-{}", base, n)).find(|id| !taken(id)).unwrap()
+}
+
+fn validate_theme(theme: &str) -> Result<(), String> {
+    if themes::list_themes()?.iter().any(|t| t.id == theme) {
+        Ok(())
+    } else {
+        Err(format!("Theme '{}' is not installed", theme))
+    }
+}
+
+fn read_overlays() -> Vec<OverlayInstance> {
+    get_app_data_file(OVERLAYS_FILE)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn write_overlays(overlays: &[OverlayInstance]) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(overlays)
+        .map_err(|e| format!("Failed to serialize overlays: {}", e))?;
+    write_atomic(&get_app_data_file(OVERLAYS_FILE)?, json.as_bytes())
+        .map_err(|e| format!("Failed to save overlays: {}", e))
+}
+
+pub fn list() -> Vec<OverlayInstance> {
+    let _guard = OVERLAYS_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    read_overlays()
+}
+
+pub fn get(id: &str) -> Option<OverlayInstance> {
+    list().into_iter().find(|o| o.id == id)
+}
+
+pub fn create(name: &str, layout: OverlayLayout, theme: Option<String>) -> Result<OverlayInstance, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Overlay name cannot be empty".to_string());
+    }
+    let theme = theme.unwrap_or_else(default_theme);
+    validate_theme(&theme)?;
+
+    let _guard = OVERLAYS_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let mut overlays = read_overlays();
+    if overlays.len() >= MAX_OVERLAYS {
+        return Err(format!("Cannot create more than {} overlays", MAX_OVERLAYS));
+    }
+    let instance = OverlayInstance {
+        id: unique_id(name, &overlays),
+        name: name.to_string(),
+        layout,
+        show_goal: true,
+        show_crown: true,
+        theme,
+    };
+    overlays.push(instance.clone());
+    write_overlays(&overlays)?;
+    Ok(instance)
+}
+
+pub fn update(id: &str, changes: OverlayUpdate) -> Result<OverlayInstance, String> {
+    if let Some(theme) = &changes.theme {
+        validate_theme(theme)?;
+    }
+
+    let _guard = OVERLAYS_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let mut overlays = read_overlays();
+    let instance = overlays.iter_mut()
+        .find(|o| o.id == id)
+        .ok_or_else(|| format!("Overlay '{}' not found", id))?;
+    if let Some(name) = changes.name {
+        let name = name.trim().to_string();
+        if name.is_empty() {
+            return Err("Overlay name cannot be empty".to_string());
+        }
+        instance.name = name;
+    }
+    if let Some(layout) = changes.layout {
+        instance.layout = layout;
+    }
+    if let Some(show_goal) = changes.show_goal {
+        instance.show_goal = show_goal;
+    }
+    if let Some(show_crown) = changes.show_crown {
+        instance.show_crown = show_crown;
+    }
+    if let Some(theme) = changes.theme {
+        instance.theme = theme;
+    }
+    let updated = instance.clone();
+    write_overlays(&overlays)?;
+    drop(_guard);
+
+    let _ = config_channel().send(updated.clone());
+    Ok(updated)
+}
+
+pub fn delete(id: &str) -> Result<(), String> {
+    let _guard = OVERLAYS_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let mut overlays = read_overlays();
+    let before = overlays.len();
+    overlays.retain(|o| o.id != id);
+    if overlays.len() == before {
+        return Err(format!("Overlay '{}' not found", id));
+    }
+    write_overlays(&overlays)
+}
+
+// Overlay id from a WebSocket request path ("/overlay/<id>")
+pub fn id_from_ws_path(path: &str) -> Option<String> {
+    let id = path.split('?').next()?.strip_prefix("/overlay/")?.trim_end_matches('/');
+    is_valid_id(id).then(|| id.to_string())
+}
+
+// First message on an instance connection
+pub fn hello_message(instance: &OverlayInstance) -> String {
+    serde_json::json!({ "type": "hello", "overlay": instance }).to_string()
+}
+
+pub fn config_message(instance: &OverlayInstance) -> String {
+    serde_json::json!({ "type": "config", "overlay": instance }).to_string()
+}
+
+// Handle "GET /overlay/<id>": send the browser to the instance's theme page. The
+// page stays under /themes/<theme>/ so the theme's relative links keep working.
+pub fn overlay_response(target: &str) -> HttpReply {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let id = path.strip_prefix("/overlay/").unwrap_or("").trim_end_matches('/');
+    let Some(instance) = is_valid_id(id).then(|| get(id)).flatten() else {
+        return HttpReply::error("404 Not Found", "404 - Overlay not found");
+    };
+    let mut location = format!("/themes/{}/?overlay={}", instance.theme, instance.id);
+    // Keep the token and other parameters of the original link
+    for pair in query.split('&').filter(|p| !p.is_empty() && !p.starts_with("overlay=")) {
+        location.push('&');
+        location.push_str(pair);
+    }
+    HttpReply::redirect(location)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instance(id: &str) -> OverlayInstance {
+        OverlayInstance {
+            id: id.to_string(),
+            name: id.to_string(),
+            layout: OverlayLayout::Full,
+            show_goal: false,
+            show_crown: true,
+            theme: default_theme(),
+        }
+    }
+
+    #[test]
+    fn ids_are_slugs_and_unique() {
+        let existing = vec![instance("main-scene")];
+        assert_eq!(unique_id("Goal Bar", &existing), "goal-bar");
+        assert_eq!(unique_id("Main Scene", &existing), "main-scene-2");
+        assert_eq!(unique_id("!!!", &existing), "overlay");
+    }
+
+    #[test]
+    fn ws_paths_name_the_overlay() {
+        assert_eq!(id_from_ws_path("/overlay/main-scene"), Some("main-scene".to_string()));
+        assert_eq!(id_from_ws_path("/overlay/main-scene/?x=1"), Some("main-scene".to_string()));
+        assert_eq!(id_from_ws_path("/"), None);
+        assert_eq!(id_from_ws_path("/overlay/../x"), None);
+    }
+
+    #[test]
+    fn instances_see_their_own_flags() {
+        let state = WinState { show_crown: false, ..WinState::sample(3, 10) };
+        let view = instance("a").view(&state);
+        assert_eq!(view.win, 3);
+        assert!(!view.show_goal);
+        assert!(view.show_crown);
+    }
+}
@@ -0,0 +1,238 @@
+// Portable mode: a marker file next to the executable moves every data file
+// (state, presets, hotkeys, sounds, license, backups, ...) into a folder beside
+// it, so the whole setup can live on a USB stick or in a sync folder. The marker
+// may name the folder on its first line (relative to the executable); the
+// default is "data". --data-dir / WINCOUNT_DATA_DIR still take precedence.
+// Switching modes only records the request; the files move at the next startup,
+// before anything reads or writes them.
+
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use win_count_by_artywoof::data_dir::{exe_dir, portable_dir_in, system_dir, DEFAULT_DIR, MARKER_FILE};
+
+use crate::launch;
+
+// Pending switch, next to the executable: "portable" or "system"
+const PENDING_FILE: &str = "wincount-migrate.txt";
+const STATE_FILE: &str = "win_count_state.json";
+
+// Resolved once: the data folder must not change under a running app
+static DATA_DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PortableStatus {
+    pub portable: bool,
+    // Set by --data-dir / WINCOUNT_DATA_DIR; migration is off in that case
+    pub overridden: bool,
+    pub data_dir: Option<String>,
+    pub marker_path: Option<String>,
+    // A switch waiting for the next start
+    pub migration_pending: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationReport {
+    pub from: String,
+    pub to: String,
+    pub files: usize,
+}
+
+// Folder replacing the per-user data folder, if any
+pub fn data_dir_override() -> Option<&'static Path> {
+    DATA_DIR
+        .get_or_init(|| {
+            if let Some(dir) = &launch::startup().data_dir {
+                return Some(dir.clone());
+            }
+            let dir = exe_dir().and_then(|exe| portable_dir_in(&exe))?;
+            println!("💼 Portable mode, data folder: {}", dir.display());
+            Some(dir)
+        })
+        .as_deref()
+}
+
+pub fn status() -> PortableStatus {
+    let overridden = launch::startup().data_dir.is_some();
+    let marker = exe_dir().map(|dir| dir.join(MARKER_FILE));
+    PortableStatus {
+        portable: !overridden && marker.as_ref().is_some_and(|m| m.exists()),
+        overridden,
+        data_dir: data_dir_override().map(|d| d.display().to_string()).or_else(|| system_dir().map(|d| d.display().to_string())),
+        marker_path: marker.map(|m| m.display().to_string()),
+        migration_pending: exe_dir().is_some_and(|dir| dir.join(PENDING_FILE).exists()),
+    }
+}
+
+// Copy a folder tree except `skip`, collecting the files copied
+fn copy_tree(from: &Path, to: &Path, skip: &Path, copied: &mut Vec<PathBuf>) -> Result<(), String> {
+    fs::create_dir_all(to).map_err(|e| format!("Failed to create {}: {}", to.display(), e))?;
+    let entries = fs::read_dir(from).map_err(|e| format!("Failed to read {}: {}", from.display(), e))?;
+    for entry in entries.flatten() {
+        let source = entry.path();
+        let target = to.join(entry.file_name());
+        if source == skip {
+            continue;
+        }
+        if source.is_dir() {
+            copy_tree(&source, &target, skip, copied)?;
+        } else {
+            fs::copy(&source, &target).map_err(|e| format!("Failed to copy {}: {}", source.display(), e))?;
+            copied.push(source);
+        }
+    }
+    Ok(())
+}
+
+// Remove folders left empty; anything written meanwhile stays
+fn remove_empty_dirs(dir: &Path) {
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten().filter(|e| e.path().is_dir()) {
+            remove_empty_dirs(&entry.path());
+        }
+    }
+    let _ = fs::remove_dir(dir);
+}
+
+// Move the data folder `from` (plus the state file kept outside it, if any) into `to`.
+// Everything is copied before anything is deleted, so a failure leaves the source intact.
+pub fn move_data(from: &Path, to: &Path, state_from: &Path, state_to: &Path) -> Result<usize, String> {
+    if from == to {
+        return Err("Source and destination are the same folder".to_string());
+    }
+    let mut copied = Vec::new();
+    if from.exists() {
+        copy_tree(from, to, state_from, &mut copied)?;
+    } else {
+        fs::create_dir_all(to).map_err(|e| format!("Failed to create {}: {}", to.display(), e))?;
+    }
+    let move_state = state_from != state_to && state_from.exists();
+    if move_state {
+        fs::copy(state_from, state_to).map_err(|e| format!("Failed to copy state: {}", e))?;
+        copied.push(state_from.to_path_buf());
+    }
+
+    for file in &copied {
+        let _ = fs::remove_file(file);
+    }
+    remove_empty_dirs(from);
+    Ok(copied.len())
+}
+
+// Ask for the data to move into the portable folder (`to_portable`) or back to the
+// per-user folder at the next start. The app must restart afterwards.
+pub fn request_migration(to_portable: bool) -> Result<(), String> {
+    if launch::startup().data_dir.is_some() {
+        return Err("The data folder is set by --data-dir / WINCOUNT_DATA_DIR".to_string());
+    }
+    let exe = exe_dir().ok_or("Failed to locate the executable folder")?;
+    let portable = exe.join(MARKER_FILE).exists();
+    if to_portable && portable {
+        return Err("Already in portable mode".to_string());
+    }
+    if !to_portable && !portable {
+        return Err("Not in portable mode".to_string());
+    }
+    // Also proves the folder is writable before the restart
+    let target = if to_portable { "portable" } else { "system" };
+    fs::write(exe.join(PENDING_FILE), format!("{}\n", target))
+        .map_err(|e| format!("Cannot write next to the executable ({}): {}", exe.display(), e))?;
+    println!("💼 Data will move to the {} folder on restart", target);
+    Ok(())
+}
+
+// Carry out a requested switch; called at startup before any data is touched
+pub fn run_pending_migration() {
+    let Some(pending) = exe_dir().map(|dir| dir.join(PENDING_FILE)) else {
+        return;
+    };
+    let Ok(target) = fs::read_to_string(&pending) else {
+        return;
+    };
+    // One attempt only; a failed move leaves the data where it was
+    let _ = fs::remove_file(&pending);
+    let result = match target.trim() {
+        "portable" => migrate(true),
+        "system" => migrate(false),
+        other => Err(format!("Unknown migration target '{}'", other)),
+    };
+    if let Err(e) = result {
+        println!("❌ Data folder migration failed, keeping the current folder: {}", e);
+    }
+}
+
+// Move the data into the portable folder (`to_portable`) or back to the per-user
+// folder, and write or remove the marker
+fn migrate(to_portable: bool) -> Result<MigrationReport, String> {
+    if launch::startup().data_dir.is_some() {
+        return Err("The data folder is set by --data-dir / WINCOUNT_DATA_DIR".to_string());
+    }
+    let exe = exe_dir().ok_or("Failed to locate the executable folder")?;
+    let marker = exe.join(MARKER_FILE);
+    let system = system_dir().ok_or("Failed to get local data directory")?;
+    // The per-user state file lives in the temp folder
+    let system_state = std::env::temp_dir().join(STATE_FILE);
+
+    let report = if to_portable {
+        if marker.exists() {
+            return Err("Already in portable mode".to_string());
+        }
+        let portable = exe.join(DEFAULT_DIR);
+        // Write the marker first: an unwritable folder fails before anything moves
+        fs::write(&marker, format!("{}\n", DEFAULT_DIR))
+            .map_err(|e| format!("Cannot write next to the executable ({}): {}", exe.display(), e))?;
+        match move_data(&system, &portable, &system_state, &portable.join(STATE_FILE)) {
+            Ok(files) => MigrationReport { from: system.display().to_string(), to: portable.display().to_string(), files },
+            Err(e) => {
+                let _ = fs::remove_file(&marker);
+                return Err(e);
+            }
+        }
+    } else {
+        let portable = portable_dir_in(&exe).ok_or("Not in portable mode")?;
+        let files = move_data(&portable, &system, &portable.join(STATE_FILE), &system_state)?;
+        fs::remove_file(&marker).map_err(|e| format!("Data moved, but failed to remove {}: {}", marker.display(), e))?;
+        MigrationReport { from: portable.display().to_string(), to: system.display().to_string(), files }
+    };
+    println!("💼 Moved {} data files from {} to {}", report.files, report.from, report.to);
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_root(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("wincount_portable_test_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        root
+    }
+
+    #[test]
+    fn moves_data_and_state() {
+        let root = temp_root("move");
+        let from = root.join("system");
+        fs::create_dir_all(from.join("sounds")).unwrap();
+        fs::write(from.join("win_count_presets.json"), "[]").unwrap();
+        fs::write(from.join("sounds").join("win.mp3"), [1u8, 2, 3]).unwrap();
+        let state_from = root.join(STATE_FILE);
+        fs::write(&state_from, "{}").unwrap();
+
+        let to = root.join("portable");
+        assert_eq!(move_data(&from, &to, &state_from, &to.join(STATE_FILE)).unwrap(), 3);
+        assert_eq!(fs::read(to.join("sounds").join("win.mp3")).unwrap(), vec![1u8, 2, 3]);
+        assert!(to.join(STATE_FILE).exists() && to.join("win_count_presets.json").exists());
+        assert!(!from.exists() && !state_from.exists());
+
+        // Back out: the state inside the portable folder goes to its own path only
+        let state_back = root.join("state_back.json");
+        assert_eq!(move_data(&to, &from, &to.join(STATE_FILE), &state_back).unwrap(), 3);
+        assert!(state_back.exists() && !from.join(STATE_FILE).exists() && !to.exists());
+
+        assert!(move_data(&to, &to, &state_from, &state_from).is_err());
+        let _ = fs::remove_dir_all(&root);
+    }
+}
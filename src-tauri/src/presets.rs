@@ -0,0 +1,636 @@
+// Preset storage: a single in-memory copy of win_count_presets.json behind a lock.
+// Every read-modify-write goes through the store so hotkey auto-saves and UI edits
+// can no longer interleave and lose updates.
+
+use chrono::{DateTime, Utc};
+use rust_embed::RustEmbed;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+use crate::backup::DataFile;
+use crate::schedule::ResetSchedule;
+use crate::sounds::PresetSounds;
+use crate::{get_app_data_file, write_atomic, WinState};
+
+const PRESETS_FILE: &str = "win_count_presets.json";
+pub const DATA_FILE: DataFile = DataFile::json::<Vec<PresetData>>(PRESETS_FILE);
+
+// Built-in preset templates shipped in /presets (default.json and friends)
+#[derive(RustEmbed)]
+#[folder = "../presets"]
+struct PresetTemplates;
+
+// Upper bound on stored presets; saving past it is rejected instead of evicting old ones
+pub const MAX_PRESETS: usize = 100;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PresetData {
+    pub name: String,
+    pub win: i32,
+    pub goal: i32,
+    pub show_goal: bool,
+    pub show_crown: bool,
+    pub hotkeys: HotkeyConfig,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub folder: Option<String>,
+    #[serde(default)]
+    pub reset_schedule: Option<ResetSchedule>,
+    #[serde(default)]
+    pub last_reset: Option<DateTime<Utc>>,
+    // Clip selection, volume and mute per sound event; events left out use the global library
+    #[serde(default)]
+    pub sounds: PresetSounds,
+    // Overlay text templates by label name, e.g. "main" -> "{win}/{goal} 👑"
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+    #[serde(default)]
+    pub bounds: CounterBounds,
+}
+
+// Counter limits of a preset; a bound left unset uses the range in settings.json
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CounterBounds {
+    pub min: Option<i32>,
+    pub max: Option<i32>,
+    // Keep the win count and goal at 0 or above
+    pub no_negatives: bool,
+    // Step of the +/- buttons and the plain hotkeys
+    pub default_step: i32,
+}
+
+impl Default for CounterBounds {
+    fn default() -> Self {
+        Self { min: None, max: None, no_negatives: false, default_step: 1 }
+    }
+}
+
+impl CounterBounds {
+    // Effective (min, max): a bound set here replaces the global one, so a preset
+    // may go beyond the range in settings.json
+    pub fn range(&self, global: (i32, i32)) -> (i32, i32) {
+        let min = self.min.unwrap_or(global.0);
+        let min = if self.no_negatives { min.max(0) } else { min };
+        (min, self.max.unwrap_or(global.1))
+    }
+
+    // `value` if it lies in the range, otherwise an error naming the limit
+    pub fn check(&self, what: &str, value: i32, global: (i32, i32)) -> Result<i32, String> {
+        let (min, max) = self.range(global);
+        if value < 0 && self.no_negatives {
+            Err(format!("{} cannot be negative in this preset (got {})", what, value))
+        } else if value < min {
+            Err(format!("{} {} is below the minimum of {}", what, value, min))
+        } else if value > max {
+            Err(format!("{} {} is above the maximum of {}", what, value, max))
+        } else {
+            Ok(value)
+        }
+    }
+}
+
+pub fn validate_bounds(bounds: &CounterBounds, global: (i32, i32)) -> Result<(), String> {
+    if !(1..=10000).contains(&bounds.default_step) {
+        return Err("Default step must be between 1 and 10000".to_string());
+    }
+    if [bounds.min, bounds.max].into_iter().flatten().any(|limit| !(-1_000_000..=1_000_000).contains(&limit)) {
+        return Err("Bounds must stay within -1000000 and 1000000".to_string());
+    }
+    let (min, max) = bounds.range(global);
+    if min >= max {
+        return Err(format!("Minimum ({}) must be below the maximum ({})", min, max));
+    }
+    // Resets always go to 0
+    if min > 0 || max < 0 {
+        return Err(format!("The range {}..{} must include 0", min, max));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HotkeyConfig {
+    pub increase: String,
+    pub decrease: String,
+    pub step_size: i32,
+}
+
+impl Default for PresetData {
+    fn default() -> Self {
+        Self {
+            name: "Default".to_string(),
+            win: 0,
+            goal: 10,
+            show_goal: true,
+            show_crown: true,
+            hotkeys: HotkeyConfig::default(),
+            pinned: false,
+            folder: None,
+            reset_schedule: None,
+            last_reset: None,
+            sounds: PresetSounds::new(),
+            labels: BTreeMap::new(),
+            bounds: CounterBounds::default(),
+        }
+    }
+}
+
+impl Default for HotkeyConfig {
+    fn default() -> Self {
+        Self {
+            increase: "Alt+Equal".to_string(),
+            decrease: "Alt+Minus".to_string(),
+            step_size: 1,
+        }
+    }
+}
+
+pub struct PresetStore {
+    path: PathBuf,
+    presets: Vec<PresetData>,
+}
+
+static PRESET_STORE: OnceLock<Mutex<PresetStore>> = OnceLock::new();
+
+// Lock the shared preset store, loading it from disk on first use.
+// Lock order: take the WinState lock first, then this one, never the other way round.
+pub fn store() -> MutexGuard<'static, PresetStore> {
+    PRESET_STORE
+        .get_or_init(|| {
+            let path = get_app_data_file(PRESETS_FILE).unwrap_or_else(|_| {
+                println!("❌ Failed to get presets file path, using temp directory");
+                std::env::temp_dir().join(PRESETS_FILE)
+            });
+            Mutex::new(PresetStore::open(path))
+        })
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+impl PresetStore {
+    fn open(path: PathBuf) -> Self {
+        let mut store = Self { path, presets: Vec::new() };
+        if let Err(e) = store.reload() {
+            println!("⚠️ {}", e);
+        }
+        store
+    }
+
+    // Re-read the presets file, e.g. after a backup restore
+    pub fn reload(&mut self) -> Result<(), String> {
+        self.presets = Vec::new();
+        if self.path.exists() {
+            let json = fs::read_to_string(&self.path)
+                .map_err(|e| format!("Failed to read presets: {}", e))?;
+            match serde_json::from_str::<Vec<PresetData>>(&json) {
+                Ok(presets) => self.presets = presets,
+                Err(e) => {
+                    // Keep the unreadable file around instead of overwriting it
+                    let corrupt_path = self.path.with_extension("json.corrupt");
+                    let _ = fs::rename(&self.path, &corrupt_path);
+                    println!("⚠️ Failed to parse presets ({}), moved to {:?}", e, corrupt_path);
+                }
+            }
+        }
+
+        // Ensure Default preset exists (only if no presets exist)
+        if self.presets.is_empty() {
+            self.presets.push(PresetData::default());
+            self.persist()?;
+            println!("🔄 Created Default preset because no presets existed");
+        }
+        Ok(())
+    }
+
+    fn persist(&self) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(&self.presets)
+            .map_err(|e| format!("Failed to serialize presets: {}", e))?;
+        write_atomic(&self.path, json.as_bytes())
+            .map_err(|e| format!("Failed to save presets: {}", e))
+    }
+
+    // Presets in display order: pinned first, otherwise the saved order is kept
+    pub fn list(&self) -> Vec<PresetData> {
+        let mut presets = self.presets.clone();
+        presets.sort_by_key(|p| !p.pinned);
+        presets
+    }
+
+    pub fn first(&self) -> Option<&PresetData> {
+        self.presets.first()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&PresetData> {
+        self.presets.iter().find(|p| p.name == name)
+    }
+
+    // Whether every preset's bounds, win count and goal still hold with `global`
+    // as the range in settings.json
+    pub fn check_range(&self, global: (i32, i32)) -> Result<(), String> {
+        for preset in &self.presets {
+            validate_bounds(&preset.bounds, global)
+                .and_then(|_| preset.bounds.check("Win count", preset.win, global))
+                .and_then(|_| preset.bounds.check("Goal", preset.goal, global))
+                .map_err(|e| format!("Preset '{}': {}", preset.name, e))?;
+        }
+        Ok(())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    // Add a preset under a name that must not be taken yet
+    pub fn insert_new(&mut self, preset: PresetData) -> Result<(), String> {
+        if preset.name.trim().is_empty() {
+            return Err("Preset name cannot be empty".to_string());
+        }
+        if self.contains(&preset.name) {
+            return Err(format!("Preset '{}' already exists", preset.name));
+        }
+        self.save(preset)
+    }
+
+    // Insert a new preset or overwrite an existing one in place.
+    // Fields managed by their own commands (pin, folder, reset schedule, sounds, labels) survive a save.
+    pub fn save(&mut self, mut preset: PresetData) -> Result<(), String> {
+        if let Some(existing) = self.presets.iter_mut().find(|p| p.name == preset.name) {
+            preset.pinned = existing.pinned;
+            preset.folder = existing.folder.take();
+            preset.reset_schedule = existing.reset_schedule.take();
+            preset.last_reset = existing.last_reset;
+            preset.sounds = std::mem::take(&mut existing.sounds);
+            preset.labels = std::mem::take(&mut existing.labels);
+            preset.bounds = std::mem::take(&mut existing.bounds);
+            *existing = preset;
+        } else if self.presets.len() >= MAX_PRESETS {
+            return Err(format!("PRESET_LIMIT_REACHED: cannot save more than {} presets, delete one first", MAX_PRESETS));
+        } else {
+            self.presets.push(preset);
+        }
+        self.persist()
+    }
+
+    // Apply `f` to the named preset and persist the result
+    pub fn update<F>(&mut self, name: &str, f: F) -> Result<(), String>
+    where
+        F: FnOnce(&mut PresetData),
+    {
+        let preset = self.presets.iter_mut()
+            .find(|p| p.name == name)
+            .ok_or_else(|| format!("Preset '{}' not found", name))?;
+        f(preset);
+        self.persist()
+    }
+
+    // Apply `f` to every preset; persists once if any call reported a change
+    pub fn update_each<F>(&mut self, mut f: F) -> Result<(), String>
+    where
+        F: FnMut(&mut PresetData) -> bool,
+    {
+        let mut changed = false;
+        for preset in self.presets.iter_mut() {
+            changed |= f(preset);
+        }
+        if changed {
+            self.persist()?;
+        }
+        Ok(())
+    }
+
+    // Copy the live counter values into the active preset.
+    // Returns false when the preset does not exist and `create_missing` is off.
+    pub fn sync_from_state(&mut self, state: &WinState, create_missing: bool) -> Result<bool, String> {
+        if let Some(preset) = self.presets.iter_mut().find(|p| p.name == state.current_preset) {
+            preset.win = state.win;
+            preset.goal = state.goal;
+            preset.show_goal = state.show_goal;
+            preset.show_crown = state.show_crown;
+        } else if create_missing {
+            if self.presets.len() >= MAX_PRESETS {
+                return Err(format!("PRESET_LIMIT_REACHED: cannot save more than {} presets, delete one first", MAX_PRESETS));
+            }
+            self.presets.push(PresetData {
+                name: state.current_preset.clone(),
+                win: state.win,
+                goal: state.goal,
+                show_goal: state.show_goal,
+                show_crown: state.show_crown,
+                ..Default::default()
+            });
+        } else {
+            return Ok(false);
+        }
+        self.persist()?;
+        Ok(true)
+    }
+
+    // Remove a preset. Deleting the last one recreates Default.
+    pub fn delete(&mut self, name: &str) -> Result<(), String> {
+        if !self.contains(name) {
+            return Err(format!("Preset '{}' not found", name));
+        }
+        self.presets.retain(|p| p.name != name);
+        if self.presets.is_empty() {
+            self.presets.push(PresetData::default());
+            println!("🔄 Created new Default preset after deletion");
+        }
+        self.persist()
+    }
+
+    pub fn rename(&mut self, old_name: &str, new_name: &str) -> Result<(), String> {
+        let new_name = new_name.trim();
+        if new_name.is_empty() {
+            return Err("Preset name cannot be empty".to_string());
+        }
+        if new_name != old_name && self.contains(new_name) {
+            return Err(format!("Preset '{}' already exists", new_name));
+        }
+        self.update(old_name, |p| p.name = new_name.to_string())
+    }
+
+    // Replace the stored order. `names` must list every preset exactly once.
+    pub fn reorder(&mut self, names: &[String]) -> Result<(), String> {
+        let mut requested: Vec<&String> = names.iter().collect();
+        requested.sort();
+        requested.dedup();
+        let mut existing: Vec<&String> = self.presets.iter().map(|p| &p.name).collect();
+        existing.sort();
+        if requested.len() != names.len() || requested != existing {
+            return Err("Preset order must contain every preset exactly once".to_string());
+        }
+
+        self.presets.sort_by_key(|p| names.iter().position(|n| n == &p.name));
+        self.persist()
+    }
+}
+
+// Template file format, matching presets/default.json
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TemplateFile {
+    preset_name: String,
+    #[serde(default)]
+    description: String,
+    win: i32,
+    goal: i32,
+    show_crown: bool,
+    show_goal: bool,
+    hotkeys: TemplateHotkeys,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct TemplateHotkeys {
+    increment: String,
+    decrement: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PresetTemplate {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub goal: i32,
+    pub show_goal: bool,
+    pub show_crown: bool,
+}
+
+fn read_template(id: &str) -> Option<TemplateFile> {
+    let file = PresetTemplates::get(&format!("{}.json", id))?;
+    match serde_json::from_slice::<TemplateFile>(file.data.as_ref()) {
+        Ok(template) => Some(template),
+        Err(e) => {
+            println!("❌ Invalid preset template '{}': {}", id, e);
+            None
+        }
+    }
+}
+
+pub fn list_templates() -> Vec<PresetTemplate> {
+    let mut ids: Vec<String> = PresetTemplates::iter()
+        .filter_map(|path| path.strip_suffix(".json").map(|id| id.to_string()))
+        .collect();
+    ids.sort();
+
+    ids.into_iter()
+        .filter_map(|id| {
+            let template = read_template(&id)?;
+            Some(PresetTemplate {
+                id,
+                name: template.preset_name,
+                description: template.description,
+                goal: template.goal,
+                show_goal: template.show_goal,
+                show_crown: template.show_crown,
+            })
+        })
+        .collect()
+}
+
+// Build a new preset from a template; `name` overrides the template's own name
+pub fn instantiate_template(id: &str, name: Option<String>) -> Result<PresetData, String> {
+    let template = read_template(id).ok_or_else(|| format!("Preset template '{}' not found", id))?;
+    Ok(PresetData {
+        name: name
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty())
+            .unwrap_or(template.preset_name),
+        win: template.win,
+        goal: template.goal,
+        show_goal: template.show_goal,
+        show_crown: template.show_crown,
+        hotkeys: HotkeyConfig {
+            increase: template.hotkeys.increment,
+            decrease: template.hotkeys.decrement,
+            step_size: 1,
+        },
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GLOBAL: (i32, i32) = (-10000, 10000);
+
+    // A store on its own file in the temp folder, holding Default plus `names`
+    fn temp_store(test: &str, names: &[&str]) -> PresetStore {
+        let path = std::env::temp_dir().join(format!("wincount_presets_test_{}_{}.json", test, std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut store = PresetStore::open(path);
+        for name in names {
+            store.insert_new(PresetData { name: name.to_string(), ..Default::default() }).unwrap();
+        }
+        store
+    }
+
+    fn names(store: &PresetStore) -> Vec<String> {
+        store.presets.iter().map(|p| p.name.clone()).collect()
+    }
+
+    #[test]
+    fn reorder_needs_every_preset_once() {
+        let mut store = temp_store("reorder", &["A", "B"]);
+        let order = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        assert!(store.reorder(&order(&["B", "A"])).is_err());
+        assert!(store.reorder(&order(&["B", "A", "A"])).is_err());
+        assert!(store.reorder(&order(&["B", "A", "Default", "C"])).is_err());
+        store.reorder(&order(&["B", "Default", "A"])).unwrap();
+        assert_eq!(names(&store), ["B", "Default", "A"]);
+
+        // Pinned presets are listed first, the rest keep the saved order
+        store.update("A", |p| p.pinned = true).unwrap();
+        assert_eq!(store.list().iter().map(|p| p.name.as_str()).collect::<Vec<_>>(), ["A", "B", "Default"]);
+        let _ = fs::remove_file(&store.path);
+    }
+
+    #[test]
+    fn saving_past_the_limit_is_an_error() {
+        let mut store = temp_store("limit", &[]);
+        for i in 1..MAX_PRESETS {
+            store.save(PresetData { name: format!("P{}", i), ..Default::default() }).unwrap();
+        }
+        let err = store.save(PresetData { name: "One too many".to_string(), ..Default::default() }).unwrap_err();
+        assert!(err.starts_with("PRESET_LIMIT_REACHED"));
+        assert_eq!(store.presets.len(), MAX_PRESETS);
+        // Overwriting an existing preset still works at the limit
+        store.save(PresetData { name: "P1".to_string(), win: 4, ..Default::default() }).unwrap();
+        assert_eq!(store.get("P1").map(|p| p.win), Some(4));
+        let _ = fs::remove_file(&store.path);
+    }
+
+    #[test]
+    fn save_keeps_fields_managed_elsewhere() {
+        let mut store = temp_store("save", &["A"]);
+        let bounds = CounterBounds { max: Some(50), ..Default::default() };
+        store.update("A", |p| {
+            p.pinned = true;
+            p.folder = Some("Ranked".to_string());
+            p.reset_schedule = Some(ResetSchedule::OnAppStart);
+            p.sounds.insert(crate::sounds::SoundSlot::Increase, crate::sounds::EventSoundSettings { muted: true, ..Default::default() });
+            p.labels.insert("main".to_string(), "{win}".to_string());
+            p.bounds = bounds.clone();
+        }).unwrap();
+
+        store.save(PresetData { name: "A".to_string(), win: 7, goal: 20, ..Default::default() }).unwrap();
+        let saved = store.get("A").unwrap();
+        assert_eq!((saved.win, saved.goal), (7, 20));
+        assert!(saved.pinned);
+        assert_eq!(saved.folder.as_deref(), Some("Ranked"));
+        assert_eq!(saved.reset_schedule, Some(ResetSchedule::OnAppStart));
+        assert!(saved.sounds[&crate::sounds::SoundSlot::Increase].muted);
+        assert_eq!(saved.labels.get("main").map(String::as_str), Some("{win}"));
+        assert_eq!(saved.bounds, bounds);
+
+        // The file holds the same
+        let reopened = PresetStore::open(store.path.clone());
+        assert!(reopened.get("A").is_some_and(|p| p.pinned && p.win == 7));
+        let _ = fs::remove_file(&store.path);
+    }
+
+    #[test]
+    fn rename_trims_and_refuses_taken_names() {
+        let mut store = temp_store("rename", &["A", "B"]);
+        store.rename("A", "  Ranked  ").unwrap();
+        assert!(store.contains("Ranked") && !store.contains("A"));
+        assert!(store.rename("Ranked", "B").is_err());
+        assert!(store.rename("Ranked", " B ").is_err());
+        assert!(store.rename("Ranked", "   ").is_err());
+        assert!(store.rename("Missing", "C").is_err());
+        // Same name again is allowed
+        store.rename("B", "B").unwrap();
+        assert_eq!(names(&store), ["Default", "Ranked", "B"]);
+        let _ = fs::remove_file(&store.path);
+    }
+
+    #[test]
+    fn deleting_the_last_preset_recreates_default() {
+        let mut store = temp_store("delete", &["A"]);
+        store.delete("Default").unwrap();
+        assert_eq!(names(&store), ["A"]);
+        assert!(store.delete("Default").is_err());
+        store.delete("A").unwrap();
+        assert_eq!(names(&store), ["Default"]);
+        assert_eq!(names(&PresetStore::open(store.path.clone())), ["Default"]);
+        let _ = fs::remove_file(&store.path);
+    }
+
+    #[test]
+    fn a_new_range_must_fit_every_preset() {
+        let mut store = temp_store("range", &["Ranked", "Endless"]);
+        store.presets[1].win = 500;
+        store.presets[2].bounds.max = Some(100_000);
+        store.presets[2].win = 50_000;
+        assert!(store.check_range((-10000, 10000)).is_ok());
+        let e = store.check_range((-100, 100)).unwrap_err();
+        assert!(e.contains("'Ranked'") && e.contains("500"), "{}", e);
+
+        store.presets[1].win = 50;
+        store.presets[1].goal = 80;
+        assert!(store.check_range((-100, 100)).is_ok());
+        store.presets[0].bounds.max = Some(0);
+        assert!(store.check_range((0, 100)).unwrap_err().contains("'Default'"));
+        let _ = fs::remove_file(&store.path);
+    }
+
+    #[test]
+    fn corrupt_files_are_moved_aside() {
+        let path = std::env::temp_dir().join(format!("wincount_presets_test_corrupt_{}.json", std::process::id()));
+        fs::write(&path, "not json").unwrap();
+        let store = PresetStore::open(path.clone());
+        assert_eq!(names(&store), ["Default"]);
+        let corrupt = path.with_extension("json.corrupt");
+        assert_eq!(fs::read_to_string(&corrupt).unwrap(), "not json");
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_file(&corrupt);
+    }
+
+    #[test]
+    fn templates_become_presets() {
+        let ids: Vec<String> = list_templates().into_iter().map(|t| t.id).collect();
+        assert!(ids.contains(&"default".to_string()) && ids.contains(&"endless".to_string()));
+
+        let preset = instantiate_template("default", None).unwrap();
+        assert_eq!((preset.name.as_str(), preset.win, preset.goal), ("ตั้งค่าเริ่มต้น", 0, 10));
+        assert_eq!((preset.hotkeys.increase.as_str(), preset.hotkeys.decrease.as_str()), ("Alt+=", "Alt+-"));
+        assert_eq!(preset.bounds, CounterBounds::default());
+        assert_eq!(instantiate_template("default", Some("  Ranked ".to_string())).unwrap().name, "Ranked");
+        assert_eq!(instantiate_template("default", Some(" ".to_string())).unwrap().name, "ตั้งค่าเริ่มต้น");
+        assert!(instantiate_template("missing", None).is_err());
+        assert!(instantiate_template("../default", None).is_err());
+    }
+
+    #[test]
+    fn bounds_fall_back_to_the_global_range() {
+        assert_eq!(CounterBounds::default().range(GLOBAL), GLOBAL);
+        let bounds = CounterBounds { max: Some(50000), no_negatives: true, ..Default::default() };
+        assert_eq!(bounds.range(GLOBAL), (0, 50000));
+        assert_eq!(bounds.check("Win count", 20000, GLOBAL), Ok(20000));
+        assert!(bounds.check("Win count", -1, GLOBAL).unwrap_err().contains("negative"));
+        assert!(bounds.check("Win count", 50001, GLOBAL).unwrap_err().contains("maximum of 50000"));
+        assert!(CounterBounds { min: Some(-5), ..Default::default() }.check("Goal", -6, GLOBAL).unwrap_err().contains("minimum of -5"));
+    }
+
+    #[test]
+    fn rejects_invalid_bounds() {
+        assert!(validate_bounds(&CounterBounds::default(), GLOBAL).is_ok());
+        assert!(validate_bounds(&CounterBounds { default_step: 0, ..Default::default() }, GLOBAL).is_err());
+        assert!(validate_bounds(&CounterBounds { min: Some(10), max: Some(5), ..Default::default() }, GLOBAL).is_err());
+        assert!(validate_bounds(&CounterBounds { min: Some(1), ..Default::default() }, GLOBAL).is_err());
+        assert!(validate_bounds(&CounterBounds { max: Some(0), no_negatives: true, ..Default::default() }, GLOBAL).is_err());
+        assert!(validate_bounds(&CounterBounds { max: Some(2_000_000), ..Default::default() }, GLOBAL).is_err());
+    }
+
+    #[test]
+    fn old_presets_get_default_bounds() {
+        let preset: PresetData = serde_json::from_str(
+            r#"{"name":"Old","win":3,"goal":10,"show_goal":true,"show_crown":true,"hotkeys":{"increase":"Alt+Equal","decrease":"Alt+Minus","step_size":1}}"#,
+        ).unwrap();
+        assert_eq!(preset.bounds, CounterBounds::default());
+    }
+}
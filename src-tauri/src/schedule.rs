@@ -0,0 +1,303 @@
+// Scheduled automatic resets. Each preset may carry a ResetSchedule; a background
+// thread checks the schedules, archives the current value to history and resets
+// the count through the normal state broadcast.
+
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, Local, NaiveTime, TimeZone, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+use tokio::sync::broadcast;
+
+use crate::history::{self, HistoryEntry};
+use crate::sounds::{self, SoundSlot};
+use crate::{get_state_path, presets, save_state, SharedWinState, WinState};
+
+// How often the scheduler thread re-evaluates the schedules
+const SCHEDULE_CHECK_INTERVAL: u64 = 30; // วินาที
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ResetSchedule {
+    // Every day at a local "HH:MM"
+    Daily { time: String },
+    // Every week on `weekday` at a local "HH:MM"
+    Weekly { weekday: Weekday, time: String },
+    // Once each time the app starts
+    OnAppStart,
+}
+
+impl ResetSchedule {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            ResetSchedule::Daily { time } | ResetSchedule::Weekly { time, .. } => parse_time(time).map(|_| ()),
+            ResetSchedule::OnAppStart => Ok(()),
+        }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self {
+            ResetSchedule::Daily { .. } => "daily",
+            ResetSchedule::Weekly { .. } => "weekly",
+            ResetSchedule::OnAppStart => "app_start",
+        }
+    }
+}
+
+fn parse_time(time: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(time.trim(), "%H:%M")
+        .map_err(|_| format!("Invalid reset time '{}', expected HH:MM", time))
+}
+
+// The latest scheduled reset at or before `now`, if the schedule is time based.
+// Generic over the zone so tests can use one with a DST change.
+fn last_occurrence<Tz: TimeZone>(schedule: &ResetSchedule, now: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+    let (time, weekday) = match schedule {
+        ResetSchedule::Daily { time } => (parse_time(time).ok()?, None),
+        ResetSchedule::Weekly { weekday, time } => (parse_time(time).ok()?, Some(*weekday)),
+        ResetSchedule::OnAppStart => return None,
+    };
+
+    // Walk back at most a week (plus a day for DST gaps) to the latest matching slot
+    let zone = now.timezone();
+    (0..8).find_map(|days_back| {
+        let date = now.date_naive() - ChronoDuration::days(days_back);
+        if weekday.is_some_and(|w| date.weekday() != w) {
+            return None;
+        }
+        let local = date.and_time(time);
+        // A time skipped by a DST change fires an hour later; a repeated one fires the first time
+        let candidate = zone.from_local_datetime(&local).earliest()
+            .or_else(|| zone.from_local_datetime(&(local + ChronoDuration::hours(1))).earliest())?;
+        (candidate <= *now).then_some(candidate)
+    })
+}
+
+// A time based schedule is due when a slot has passed since the last reset
+pub fn is_due<Tz: TimeZone>(schedule: &ResetSchedule, last_reset: DateTime<Utc>, now: &DateTime<Tz>) -> bool {
+    match last_occurrence(schedule, now) {
+        Some(slot) => slot.with_timezone(&Utc) > last_reset,
+        None => false,
+    }
+}
+
+pub fn start_reset_scheduler(app: tauri::AppHandle, state: SharedWinState, broadcast_tx: broadcast::Sender<WinState>) {
+    std::thread::spawn(move || {
+        run_due_resets(&app, &state, &broadcast_tx, true);
+        loop {
+            std::thread::sleep(std::time::Duration::from_secs(SCHEDULE_CHECK_INTERVAL));
+            run_due_resets(&app, &state, &broadcast_tx, false);
+        }
+    });
+}
+
+fn run_due_resets(app: &tauri::AppHandle, state: &SharedWinState, broadcast_tx: &broadcast::Sender<WinState>, app_start: bool) {
+    let now = Local::now();
+    let now_utc = now.with_timezone(&Utc);
+
+    let mut s = state.lock().unwrap();
+    let mut store = presets::store();
+    let mut archived = Vec::new();
+    let mut first_seen = Vec::new();
+
+    for preset in store.list() {
+        let Some(schedule) = preset.reset_schedule.clone() else {
+            continue;
+        };
+        let Some(last_reset) = preset.last_reset else {
+            // First evaluation after upgrading: start counting from now
+            first_seen.push(preset.name);
+            continue;
+        };
+        let due = match schedule {
+            ResetSchedule::OnAppStart => app_start,
+            _ => is_due(&schedule, last_reset, &now),
+        };
+        if !due {
+            continue;
+        }
+
+        let is_active = preset.name == s.current_preset;
+        archived.push(HistoryEntry {
+            preset: preset.name.clone(),
+            win: if is_active { s.win } else { preset.win },
+            goal: if is_active { s.goal } else { preset.goal },
+            reason: schedule.reason().to_string(),
+            archived_at: now_utc,
+        });
+    }
+    if archived.is_empty() && first_seen.is_empty() {
+        return;
+    }
+
+    // Archive first: a reset whose history entry could not be written is retried later
+    if !archived.is_empty() {
+        if let Err(e) = history::append(&archived) {
+            println!("⚠️ Failed to archive wins, skipping reset: {}", e);
+            archived.clear();
+        }
+    }
+    let result = store.update_each(|preset| {
+        if first_seen.contains(&preset.name) {
+            preset.last_reset = Some(now_utc);
+            return true;
+        }
+        if !archived.iter().any(|entry| entry.preset == preset.name) {
+            return false;
+        }
+        preset.win = 0;
+        preset.last_reset = Some(now_utc);
+        true
+    });
+    drop(store);
+    if let Err(e) = result {
+        println!("⚠️ Failed to save scheduled reset: {}", e);
+        return;
+    }
+
+    for entry in &archived {
+        println!("⏰ Scheduled {} reset of '{}' (archived win: {})", entry.reason, entry.preset, entry.win);
+    }
+    let active_reset = archived.iter().any(|entry| entry.preset == s.current_preset);
+    if active_reset {
+        s.win = 0;
+        let path = get_state_path();
+        save_state(&path, &s);
+        let _ = app.emit("state-updated", s.clone());
+        let _ = broadcast_tx.send(s.clone());
+        drop(s);
+        sounds::emit_cue(app, SoundSlot::Reset);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{FixedOffset, LocalResult, NaiveDate, NaiveDateTime};
+
+    // UTC+1, moving to UTC+2 at 02:00 local on 2026-03-29; 02:00-02:59 that day does not exist
+    #[derive(Debug, Clone, Copy)]
+    struct SpringForward;
+
+    impl SpringForward {
+        fn switch_utc() -> NaiveDateTime {
+            NaiveDate::from_ymd_opt(2026, 3, 29).unwrap().and_hms_opt(1, 0, 0).unwrap()
+        }
+
+        fn offset(summer: bool) -> FixedOffset {
+            FixedOffset::east_opt(if summer { 2 * 3600 } else { 3600 }).unwrap()
+        }
+    }
+
+    impl TimeZone for SpringForward {
+        type Offset = FixedOffset;
+
+        fn from_offset(_: &FixedOffset) -> Self {
+            SpringForward
+        }
+
+        fn offset_from_local_date(&self, local: &NaiveDate) -> LocalResult<FixedOffset> {
+            self.offset_from_local_datetime(&local.and_hms_opt(0, 0, 0).unwrap())
+        }
+
+        fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> LocalResult<FixedOffset> {
+            let switch = Self::switch_utc();
+            let winter = *local - ChronoDuration::hours(1);
+            let summer = *local - ChronoDuration::hours(2);
+            match (winter < switch, summer >= switch) {
+                (true, _) => LocalResult::Single(Self::offset(false)),
+                (false, true) => LocalResult::Single(Self::offset(true)),
+                (false, false) => LocalResult::None,
+            }
+        }
+
+        fn offset_from_utc_date(&self, utc: &NaiveDate) -> FixedOffset {
+            self.offset_from_utc_datetime(&utc.and_hms_opt(0, 0, 0).unwrap())
+        }
+
+        fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> FixedOffset {
+            Self::offset(*utc >= Self::switch_utc())
+        }
+    }
+
+    // March 2026 in `zone`
+    fn at<Tz: TimeZone>(zone: &Tz, day: u32, hour: u32, minute: u32) -> DateTime<Tz> {
+        let local = NaiveDate::from_ymd_opt(2026, 3, day).unwrap().and_hms_opt(hour, minute, 0).unwrap();
+        zone.from_local_datetime(&local).single().unwrap()
+    }
+
+    fn utc<Tz: TimeZone>(time: DateTime<Tz>) -> DateTime<Utc> {
+        time.with_timezone(&Utc)
+    }
+
+    fn daily(time: &str) -> ResetSchedule {
+        ResetSchedule::Daily { time: time.to_string() }
+    }
+
+    #[test]
+    fn daily_resets_once_the_time_has_passed() {
+        let zone = FixedOffset::east_opt(7 * 3600).unwrap();
+        let schedule = daily("06:00");
+        let yesterday_reset = utc(at(&zone, 9, 6, 0));
+        assert!(!is_due(&schedule, yesterday_reset, &at(&zone, 10, 5, 59)));
+        assert!(is_due(&schedule, yesterday_reset, &at(&zone, 10, 6, 0)));
+        assert!(is_due(&schedule, yesterday_reset, &at(&zone, 10, 23, 0)));
+        // Already reset today
+        assert!(!is_due(&schedule, utc(at(&zone, 10, 6, 1)), &at(&zone, 10, 23, 0)));
+        // Several missed days still reset only once
+        assert!(is_due(&schedule, utc(at(&zone, 2, 6, 0)), &at(&zone, 10, 7, 0)));
+        assert!(!is_due(&daily("25:00"), yesterday_reset, &at(&zone, 10, 7, 0)));
+    }
+
+    #[test]
+    fn weekly_resets_on_its_weekday() {
+        let zone = FixedOffset::east_opt(0).unwrap();
+        // 2026-03-16 and 2026-03-23 are Mondays
+        let schedule = ResetSchedule::Weekly { weekday: Weekday::Mon, time: "20:00".to_string() };
+        let last_reset = utc(at(&zone, 16, 20, 0));
+        assert!(!is_due(&schedule, last_reset, &at(&zone, 22, 23, 0)));
+        assert!(!is_due(&schedule, last_reset, &at(&zone, 23, 19, 59)));
+        assert!(is_due(&schedule, last_reset, &at(&zone, 23, 20, 0)));
+        assert!(is_due(&schedule, last_reset, &at(&zone, 25, 12, 0)));
+        assert_eq!(last_occurrence(&schedule, &at(&zone, 23, 19, 0)), Some(at(&zone, 16, 20, 0)));
+    }
+
+    #[test]
+    fn app_start_resets_are_not_time_based() {
+        let zone = FixedOffset::east_opt(0).unwrap();
+        let long_ago = utc(at(&zone, 1, 0, 0));
+        assert!(!is_due(&ResetSchedule::OnAppStart, long_ago, &at(&zone, 20, 0, 0)));
+        assert_eq!(ResetSchedule::OnAppStart.reason(), "app_start");
+    }
+
+    #[test]
+    fn first_run_without_a_last_reset_only_starts_counting() {
+        // run_due_resets records `now` for a preset it has never seen; nothing is due right after
+        let zone = FixedOffset::east_opt(0).unwrap();
+        let now = at(&zone, 20, 12, 0);
+        assert!(!is_due(&daily("06:00"), utc(now), &now));
+        assert!(is_due(&daily("06:00"), utc(now), &at(&zone, 21, 6, 0)));
+    }
+
+    #[test]
+    fn dst_changes_neither_skip_nor_repeat_resets() {
+        let zone = SpringForward;
+        // 02:30 does not exist on the 29th; the reset happens at 03:30 instead
+        let schedule = daily("02:30");
+        let last_reset = utc(at(&zone, 28, 2, 30));
+        assert!(!is_due(&schedule, last_reset, &at(&zone, 29, 3, 29)));
+        assert!(is_due(&schedule, last_reset, &at(&zone, 29, 3, 30)));
+        // A time that exists keeps its local wall-clock time across the change
+        let schedule = daily("06:00");
+        let last_reset = utc(at(&zone, 28, 6, 0));
+        assert!(!is_due(&schedule, last_reset, &at(&zone, 29, 5, 59)));
+        assert!(is_due(&schedule, last_reset, &at(&zone, 29, 6, 0)));
+        assert!(!is_due(&schedule, utc(at(&zone, 29, 6, 0)), &at(&zone, 29, 23, 0)));
+    }
+
+    #[test]
+    fn reset_times_are_validated() {
+        assert!(daily("07:30").validate().is_ok());
+        assert!(daily("7:30").validate().is_ok());
+        assert!(daily("24:00").validate().is_err());
+        assert!(daily("noon").validate().is_err());
+    }
+}
@@ -0,0 +1,439 @@
+// App-wide settings in one versioned settings.json: overlay server ports, license
+// server and heartbeat timing, the counter range and global hotkeys. Environment
+// variables (LICENSE_SERVER_URL, LICENSE_CRYPTO_SECRET) and startup flags (--port,
+// --ws-port, --no-hotkeys) still win over the file. Integrations keep their own files.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::watch;
+
+pub use win_count_by_artywoof::data_dir::{DEFAULT_HTTP_PORT, DEFAULT_WS_PORT};
+use win_count_by_artywoof::data_dir::{Ports, PORTS_FILE};
+
+use crate::backup::DataFile;
+use crate::integration::mask_secret;
+use crate::{get_app_data_file, launch, write_atomic};
+
+pub const SETTINGS_FILE: &str = "settings.json";
+// The crypto secret stays out of backups, which also hold the license it encrypts
+pub const DATA_FILE: DataFile = DataFile::checked(SETTINGS_FILE, |data| {
+    parse(&String::from_utf8_lossy(data)).and_then(|s| validate(&s))
+}).with_secrets(backup_copy, restored_copy);
+pub const SETTINGS_VERSION: u32 = 1;
+
+const DEFAULT_LICENSE_SERVER_URL: &str = "http://127.0.0.1:8765";
+const DEFAULT_CRYPTO_SECRET: &str = "WINCOUNT_DEFAULT_SECRET";
+
+// Loaded on first use
+static SETTINGS: Mutex<Option<Settings>> = Mutex::new(None);
+static CHANGES: OnceLock<watch::Sender<Settings>> = OnceLock::new();
+// Serializes writes of the ports file from the two servers
+static PORTS_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerSettings {
+    pub http_port: u16,
+    pub ws_port: u16,
+}
+
+impl Default for ServerSettings {
+    fn default() -> Self {
+        Self { http_port: DEFAULT_HTTP_PORT, ws_port: DEFAULT_WS_PORT }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LicenseSettings {
+    pub server_url: String,
+    // Key material for the stored license; changing it re-encrypts the license file
+    pub crypto_secret: String,
+    pub heartbeat_interval_secs: u64,
+    pub grace_period_secs: i64,
+}
+
+impl Default for LicenseSettings {
+    fn default() -> Self {
+        Self {
+            server_url: DEFAULT_LICENSE_SERVER_URL.to_string(),
+            crypto_secret: DEFAULT_CRYPTO_SECRET.to_string(),
+            heartbeat_interval_secs: 30,
+            grace_period_secs: 5 * 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CounterSettings {
+    // Wins and goals outside this range are rejected; presets may set their own
+    pub min: i32,
+    pub max: i32,
+}
+
+impl Default for CounterSettings {
+    fn default() -> Self {
+        Self { min: -10000, max: 10000 }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HotkeySettings {
+    pub enabled: bool,
+}
+
+impl Default for HotkeySettings {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub version: u32,
+    pub server: ServerSettings,
+    pub license: LicenseSettings,
+    pub counter: CounterSettings,
+    pub hotkeys: HotkeySettings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            server: ServerSettings::default(),
+            license: LicenseSettings::default(),
+            counter: CounterSettings::default(),
+            hotkeys: HotkeySettings::default(),
+        }
+    }
+}
+
+pub fn validate(settings: &Settings) -> Result<(), String> {
+    let server = &settings.server;
+    if server.http_port == 0 || server.ws_port == 0 {
+        return Err("Ports must be between 1 and 65535".to_string());
+    }
+    if server.http_port == server.ws_port {
+        return Err("The HTTP and WebSocket ports must differ".to_string());
+    }
+    let license = &settings.license;
+    if !license.server_url.starts_with("http://") && !license.server_url.starts_with("https://") {
+        return Err("License server URL must start with http:// or https://".to_string());
+    }
+    if license.crypto_secret.trim().is_empty() {
+        return Err("License crypto secret cannot be empty".to_string());
+    }
+    if !(5..=3600).contains(&license.heartbeat_interval_secs) {
+        return Err("Heartbeat interval must be between 5 and 3600 seconds".to_string());
+    }
+    if !(0..=24 * 60 * 60).contains(&license.grace_period_secs) {
+        return Err("Grace period must be between 0 and 86400 seconds".to_string());
+    }
+    let counter = &settings.counter;
+    if counter.min >= counter.max {
+        return Err("Counter minimum must be below the maximum".to_string());
+    }
+    if counter.min < -1_000_000 || counter.max > 1_000_000 {
+        return Err("Counter range must stay within -1000000 and 1000000".to_string());
+    }
+    // Resets always go to 0
+    if counter.min > 0 || counter.max < 0 {
+        return Err("Counter range must include 0".to_string());
+    }
+    Ok(())
+}
+
+// Release builds only talk to an https license server, so a newly entered plain
+// http URL is refused there; an unchanged one (e.g. the default) is left alone
+pub fn check_license_url(old: &str, new: &str, release: bool) -> Result<(), String> {
+    if release && new != old && !new.starts_with("https://") {
+        return Err("License server URL must use https://".to_string());
+    }
+    Ok(())
+}
+
+// Settings as shown to the UI: the crypto secret never leaves the backend
+pub fn redacted(mut settings: Settings) -> Settings {
+    settings.license.crypto_secret = mask_secret(&settings.license.crypto_secret);
+    settings
+}
+
+// The settings file as stored in backups: the crypto secret is left out
+fn backup_copy(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut settings = parse(&String::from_utf8_lossy(data))?;
+    settings.license.crypto_secret.clear();
+    serde_json::to_vec_pretty(&settings).map_err(|e| format!("Failed to serialize settings: {}", e))
+}
+
+// A backed-up settings file about to be restored, given back the current secret
+fn restored_copy(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut settings = parse(&String::from_utf8_lossy(data))?;
+    if settings.license.crypto_secret.is_empty() {
+        settings.license.crypto_secret = get().license.crypto_secret;
+    }
+    serde_json::to_vec_pretty(&settings).map_err(|e| format!("Failed to serialize settings: {}", e))
+}
+
+// Read a settings file of any version; fields it lacks keep their defaults
+pub fn parse(text: &str) -> Result<Settings, String> {
+    let value: serde_json::Value = serde_json::from_str(text).map_err(|e| format!("Invalid settings: {}", e))?;
+    let version = value.get("version").and_then(|v| v.as_u64()).unwrap_or(0);
+    if version > SETTINGS_VERSION as u64 {
+        println!("⚠️ settings.json is version {} (this app knows {}), unknown fields are ignored", version, SETTINGS_VERSION);
+    }
+    let mut settings: Settings = serde_json::from_value(value).map_err(|e| format!("Invalid settings: {}", e))?;
+    // Version 0 had no file; later migrations go here, oldest first
+    settings.version = SETTINGS_VERSION;
+    Ok(settings)
+}
+
+fn load() -> Settings {
+    match get_app_data_file(SETTINGS_FILE) {
+        Ok(path) => load_from(&path),
+        Err(_) => Settings::default(),
+    }
+}
+
+// A file that cannot be used is moved aside (.json.corrupt) so the next save
+// does not overwrite it
+fn load_from(path: &Path) -> Settings {
+    let Ok(text) = fs::read_to_string(path) else {
+        return Settings::default();
+    };
+    parse(&text).and_then(|s| validate(&s).map(|_| s)).unwrap_or_else(|e| {
+        let corrupt_path = path.with_extension("json.corrupt");
+        let _ = fs::rename(path, &corrupt_path);
+        println!("⚠️ {}, moved to {:?}, using defaults", e, corrupt_path);
+        Settings::default()
+    })
+}
+
+pub fn get() -> Settings {
+    let mut cached = SETTINGS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    cached.get_or_insert_with(load).clone()
+}
+
+// Changes are published here; the overlay servers follow the ports
+pub fn subscribe() -> watch::Receiver<Settings> {
+    CHANGES.get_or_init(|| watch::channel(get()).0).subscribe()
+}
+
+fn publish(settings: &Settings) {
+    if let Some(tx) = CHANGES.get() {
+        let _ = tx.send(settings.clone());
+    }
+}
+
+// Validate, save and publish new settings
+pub fn update(mut settings: Settings) -> Result<Settings, String> {
+    validate(&settings)?;
+    settings.version = SETTINGS_VERSION;
+    let path = get_app_data_file(SETTINGS_FILE)?;
+    let json = serde_json::to_string_pretty(&settings).map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    write_atomic(&path, json.as_bytes()).map_err(|e| format!("Failed to save settings: {}", e))?;
+    *SETTINGS.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(settings.clone());
+    publish(&settings);
+    println!("⚙️ Settings saved");
+    Ok(settings)
+}
+
+// Re-read the file, e.g. after a backup restore
+pub fn reload() {
+    let settings = load();
+    *SETTINGS.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(settings.clone());
+    publish(&settings);
+}
+
+pub fn http_port() -> u16 {
+    launch::startup().http_port.unwrap_or_else(|| get().server.http_port)
+}
+
+pub fn ws_port() -> u16 {
+    launch::startup().ws_port.unwrap_or_else(|| get().server.ws_port)
+}
+
+pub fn hotkeys_enabled() -> bool {
+    !launch::startup().no_hotkeys && get().hotkeys.enabled
+}
+
+pub fn counter_range() -> (i32, i32) {
+    let counter = get().counter;
+    (counter.min, counter.max)
+}
+
+pub fn license_server_url() -> String {
+    std::env::var("LICENSE_SERVER_URL").unwrap_or_else(|_| get().license.server_url)
+}
+
+pub fn crypto_secret() -> String {
+    crypto_secret_of(&get())
+}
+
+// The secret in effect with `settings`; the environment variable wins
+pub fn crypto_secret_of(settings: &Settings) -> String {
+    std::env::var("LICENSE_CRYPTO_SECRET").unwrap_or_else(|_| settings.license.crypto_secret.clone())
+}
+
+// Tell wincount-cli where the servers listen
+fn write_ports_file() {
+    let _guard = PORTS_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let ports = Ports { http_port: http_port(), ws_port: ws_port() };
+    let written = get_app_data_file(PORTS_FILE).and_then(|path| {
+        let json = serde_json::to_string_pretty(&ports).map_err(|e| e.to_string())?;
+        write_atomic(&path, json.as_bytes()).map_err(|e| e.to_string())
+    });
+    if let Err(e) = written {
+        println!("⚠️ Failed to write {}: {}", PORTS_FILE, e);
+    }
+}
+
+// Check that a port can be bound before switching a server to it
+pub fn check_port_free(port: u16) -> Result<(), String> {
+    std::net::TcpListener::bind(("127.0.0.1", port))
+        .map(drop)
+        .map_err(|e| format!("Port {} is not available: {}", port, e))
+}
+
+// A listener that follows a port setting: it re-binds whenever the port changes.
+// Connections already accepted keep running on the old port until they close.
+pub struct ServerListener {
+    name: &'static str,
+    port_of: fn() -> u16,
+    port: u16,
+    listener: Option<TcpListener>,
+    changes: watch::Receiver<Settings>,
+}
+
+impl ServerListener {
+    pub async fn bind(name: &'static str, port_of: fn() -> u16) -> Self {
+        let mut server = Self { name, port_of, port: port_of(), listener: None, changes: subscribe() };
+        server.rebind().await;
+        server
+    }
+
+    async fn rebind(&mut self) {
+        self.listener = None;
+        let addr = format!("127.0.0.1:{}", self.port);
+        match TcpListener::bind(&addr).await {
+            Ok(listener) => {
+                println!("🌐 {} server listening on {}", self.name, addr);
+                self.listener = Some(listener);
+                write_ports_file();
+            }
+            // Stay idle until the port setting changes
+            Err(e) => println!("❌ Failed to bind {} server on {}: {}", self.name, addr, e),
+        }
+    }
+
+    pub async fn accept(&mut self) -> std::io::Result<(TcpStream, SocketAddr)> {
+        loop {
+            let Self { listener, changes, .. } = self;
+            let changed = match listener {
+                Some(listener) => tokio::select! {
+                    accepted = listener.accept() => return accepted,
+                    changed = changes.changed() => changed,
+                },
+                None => changes.changed().await,
+            };
+            if changed.is_err() {
+                // No more settings changes; keep serving on the current port
+                return match &self.listener {
+                    Some(listener) => listener.accept().await,
+                    None => std::future::pending().await,
+                };
+            }
+            let port = (self.port_of)();
+            if port != self.port {
+                println!("🔁 {} server moving from port {} to {}", self.name, self.port, port);
+                self.port = port;
+                self.rebind().await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_match_the_old_constants() {
+        let settings = Settings::default();
+        assert_eq!((settings.server.http_port, settings.server.ws_port), (777, 779));
+        assert_eq!((settings.counter.min, settings.counter.max), (-10000, 10000));
+        assert_eq!((settings.license.heartbeat_interval_secs, settings.license.grace_period_secs), (30, 300));
+        assert!(validate(&settings).is_ok());
+    }
+
+    #[test]
+    fn parses_partial_and_unversioned_files() {
+        let settings = parse(r#"{"server": {"http_port": 8777}, "counter": {"max": 500}}"#).unwrap();
+        assert_eq!(settings.version, SETTINGS_VERSION);
+        assert_eq!((settings.server.http_port, settings.server.ws_port), (8777, DEFAULT_WS_PORT));
+        assert_eq!((settings.counter.min, settings.counter.max), (-10000, 500));
+        assert!(settings.hotkeys.enabled);
+
+        let newer = parse(r#"{"version": 99, "future": true, "hotkeys": {"enabled": false}}"#).unwrap();
+        assert!(!newer.hotkeys.enabled);
+        assert!(parse("not json").is_err());
+    }
+
+    #[test]
+    fn unusable_files_are_moved_aside() {
+        let path = std::env::temp_dir().join(format!("wincount_settings_test_corrupt_{}.json", std::process::id()));
+        fs::write(&path, r#"{"server": {"http_port": 0}}"#).unwrap();
+        assert_eq!(load_from(&path), Settings::default());
+        assert!(!path.exists());
+        let corrupt = path.with_extension("json.corrupt");
+        assert_eq!(fs::read_to_string(&corrupt).unwrap(), r#"{"server": {"http_port": 0}}"#);
+        let _ = fs::remove_file(&corrupt);
+    }
+
+    #[test]
+    fn rejects_invalid_settings() {
+        let mut settings = Settings::default();
+        settings.server.ws_port = settings.server.http_port;
+        assert!(validate(&settings).is_err());
+
+        let mut settings = Settings::default();
+        settings.counter.min = 10;
+        settings.counter.max = 10;
+        assert!(validate(&settings).is_err());
+
+        let mut settings = Settings::default();
+        settings.counter.min = 5;
+        assert!(validate(&settings).is_err());
+
+        let mut settings = Settings::default();
+        settings.license.heartbeat_interval_secs = 1;
+        assert!(validate(&settings).is_err());
+
+        let mut settings = Settings::default();
+        settings.license.server_url = "ftp://example.com".to_string();
+        assert!(validate(&settings).is_err());
+    }
+
+    #[test]
+    fn release_builds_refuse_new_http_license_urls() {
+        let default = LicenseSettings::default().server_url;
+        assert!(check_license_url(&default, &default, true).is_ok());
+        assert!(check_license_url(&default, "http://example.com", true).is_err());
+        assert!(check_license_url(&default, "https://example.com", true).is_ok());
+        assert!(check_license_url(&default, "http://example.com", false).is_ok());
+    }
+
+    #[test]
+    fn redaction_hides_the_crypto_secret() {
+        let shown = redacted(Settings::default());
+        assert_eq!(shown.license.crypto_secret, crate::integration::MASKED_SECRET);
+        assert_eq!(shown.server, Settings::default().server);
+    }
+}
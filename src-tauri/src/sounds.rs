@@ -0,0 +1,770 @@
+// Custom sound library. Sound files live in <app data>/sounds under content-addressed
+// names (sha256 of the file + extension). sound_library.json records, for every
+// event, the clips that may play, their weights and how the next one is picked.
+// Nothing outside the sounds directory is ever read or written.
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use tauri::Emitter;
+use tokio::sync::broadcast;
+
+use crate::audio::{self, AudioAnalysis, AudioImportError};
+use crate::backup::DataFile;
+use crate::{get_app_data_dir, get_app_data_file, write_atomic};
+
+const SOUNDS_DIR: &str = "sounds";
+const LIBRARY_FILE: &str = "sound_library.json";
+pub const DATA_FILE: DataFile = DataFile::json::<SoundLibrary>(LIBRARY_FILE);
+
+pub const MAX_CLIPS_PER_EVENT: usize = 20;
+pub const MAX_CLIP_WEIGHT: u32 = 100;
+const DEFAULT_MILESTONE_EVERY: u32 = 10;
+
+// In-memory copy of the library file, loaded on first use. Its lock also
+// serializes read-modify-write of the file.
+static LIBRARY: Mutex<Option<SoundLibrary>> = Mutex::new(None);
+
+// Cues are also broadcast so the overlay WebSocket can forward them
+static CUE_TX: OnceLock<broadcast::Sender<SoundCue>> = OnceLock::new();
+
+// Sound settings of the active preset, applied when a preset is loaded
+static ACTIVE_PRESET_SOUNDS: Mutex<PresetSounds> = Mutex::new(BTreeMap::new());
+
+// Round-robin position per event. Kept in memory; a restart starts from the first clip.
+static ROUND_ROBIN: Mutex<Option<HashMap<SoundSlot, u64>>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SoundSlot {
+    Increase,
+    Decrease,
+    GoalReached,
+    Milestone,
+    Reset,
+}
+
+impl SoundSlot {
+    pub const ALL: [SoundSlot; 5] = [
+        SoundSlot::Increase,
+        SoundSlot::Decrease,
+        SoundSlot::GoalReached,
+        SoundSlot::Milestone,
+        SoundSlot::Reset,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SoundSlot::Increase => "increase",
+            SoundSlot::Decrease => "decrease",
+            SoundSlot::GoalReached => "goal_reached",
+            SoundSlot::Milestone => "milestone",
+            SoundSlot::Reset => "reset",
+        }
+    }
+
+    // Tauri event the frontend listens to, e.g. "play-goal-reached-sound"
+    pub fn event_name(&self) -> String {
+        format!("play-{}-sound", self.as_str().replace('_', "-"))
+    }
+
+    // Pre-library storage: one sound_<slot>_metadata.json per slot
+    fn legacy_metadata_file(&self) -> String {
+        format!("sound_{}_metadata.json", self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaybackPolicy {
+    #[default]
+    Random,
+    RoundRobin,
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+fn default_milestone_every() -> u32 {
+    DEFAULT_MILESTONE_EVERY
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SoundClip {
+    // Content-addressed name inside the sounds directory; also identifies the clip
+    pub filename: String,
+    // Name of the file the user picked, for display only
+    #[serde(default)]
+    pub original_name: String,
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+    pub saved_at: String,
+    // Format, duration and loudness measured on import
+    #[serde(default)]
+    pub analysis: Option<AudioAnalysis>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EventSounds {
+    #[serde(default)]
+    pub policy: PlaybackPolicy,
+    #[serde(default)]
+    pub clips: Vec<SoundClip>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SoundLibrary {
+    #[serde(default)]
+    pub events: BTreeMap<SoundSlot, EventSounds>,
+    // A milestone sound plays each time the count climbs past a multiple of this (0 = off)
+    #[serde(default = "default_milestone_every")]
+    pub milestone_every: u32,
+}
+
+impl Default for SoundLibrary {
+    fn default() -> Self {
+        Self {
+            events: BTreeMap::new(),
+            milestone_every: DEFAULT_MILESTONE_EVERY,
+        }
+    }
+}
+
+impl SoundLibrary {
+    fn clips(&self, slot: SoundSlot) -> &[SoundClip] {
+        self.events.get(&slot).map(|e| e.clips.as_slice()).unwrap_or(&[])
+    }
+
+    pub fn references(&self, filename: &str) -> bool {
+        self.events.values().any(|e| e.clips.iter().any(|c| c.filename == filename))
+    }
+}
+
+fn default_volume() -> f32 {
+    1.0
+}
+
+// Per-preset sound settings for one event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventSoundSettings {
+    // Library clips (stored filenames) this preset plays; empty uses every clip of the event
+    #[serde(default)]
+    pub clips: Vec<String>,
+    // 0.0 - 1.0, multiplied with the clip's loudness normalization
+    #[serde(default = "default_volume")]
+    pub volume: f32,
+    #[serde(default)]
+    pub muted: bool,
+}
+
+impl Default for EventSoundSettings {
+    fn default() -> Self {
+        Self {
+            clips: Vec::new(),
+            volume: default_volume(),
+            muted: false,
+        }
+    }
+}
+
+pub type PresetSounds = BTreeMap<SoundSlot, EventSoundSettings>;
+
+// The clip chosen for one event, sent as the payload of the play event
+#[derive(Debug, Clone, Serialize)]
+pub struct SoundCue {
+    pub event: SoundSlot,
+    // None means no custom clip: play the built-in sound
+    pub clip: Option<CueClip>,
+    // From the active preset; a muted event plays nothing, not even the built-in sound
+    pub volume: f32,
+    pub muted: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CueClip {
+    pub filename: String,
+    // Overlay HTTP route serving the file (needs the overlay token)
+    pub url: String,
+    pub original_name: String,
+    pub gain_db: f32,
+}
+
+// Pre-library metadata format, only read for migration
+#[derive(Deserialize)]
+struct LegacySoundMetadata {
+    filename: String,
+    #[serde(default)]
+    original_name: String,
+    saved_at: String,
+    #[serde(default)]
+    analysis: Option<AudioAnalysis>,
+}
+
+pub fn sounds_dir() -> Result<PathBuf, String> {
+    let dir = get_app_data_dir()?.join(SOUNDS_DIR);
+    if !dir.exists() {
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create sounds directory: {}", e))?;
+    }
+    Ok(dir)
+}
+
+// A stored file name must be a single plain component, never a path
+fn is_plain_file_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.contains(['/', '\\', ':'])
+        && name != "."
+        && name != ".."
+}
+
+// Join a stored file name onto the sounds directory, refusing anything that could escape it
+pub fn stored_sound_path(dir: &Path, filename: &str) -> Result<PathBuf, String> {
+    if !is_plain_file_name(filename) {
+        return Err(format!("Invalid sound file name '{}'", filename));
+    }
+    Ok(dir.join(filename))
+}
+
+// Resolve a caller-supplied path and make sure it points at a file inside `dir`
+pub fn resolve_sound_path(dir: &Path, requested: &Path) -> Result<PathBuf, String> {
+    let canonical_dir = dir.canonicalize()
+        .map_err(|e| format!("Failed to resolve sounds directory: {}", e))?;
+    let candidate = if requested.is_absolute() { requested.to_path_buf() } else { dir.join(requested) };
+    let canonical = candidate.canonicalize()
+        .map_err(|_| "Sound file not found".to_string())?;
+    if !canonical.starts_with(&canonical_dir) || !canonical.is_file() {
+        return Err("Access denied: sound files can only be read from the sounds directory".to_string());
+    }
+    Ok(canonical)
+}
+
+// The extension comes from the detected format, never from the uploaded name
+fn content_addressed_name(data: &[u8], analysis: &AudioAnalysis) -> String {
+    format!("{:x}.{}", Sha256::digest(data), analysis.format.extension())
+}
+
+// Build a library from the old one-file-per-slot metadata
+fn migrate_legacy(app_dir: &Path) -> SoundLibrary {
+    let mut library = SoundLibrary::default();
+    for slot in SoundSlot::ALL {
+        let Ok(json) = fs::read_to_string(app_dir.join(slot.legacy_metadata_file())) else {
+            continue;
+        };
+        let Ok(legacy) = serde_json::from_str::<LegacySoundMetadata>(&json) else {
+            continue;
+        };
+        library.events.entry(slot).or_default().clips.push(SoundClip {
+            filename: legacy.filename,
+            original_name: legacy.original_name,
+            weight: default_weight(),
+            saved_at: legacy.saved_at,
+            analysis: legacy.analysis,
+        });
+    }
+    library
+}
+
+fn read_library() -> Result<SoundLibrary, String> {
+    let path = get_app_data_file(LIBRARY_FILE)?;
+    if path.exists() {
+        let json = fs::read_to_string(&path)
+            .map_err(|_| "Failed to read sound library".to_string())?;
+        return serde_json::from_str(&json)
+            .map_err(|_| "Invalid sound library format".to_string());
+    }
+
+    let app_dir = get_app_data_dir()?;
+    let library = migrate_legacy(&app_dir);
+    if !library.events.is_empty() {
+        write_library(&library)?;
+        for slot in SoundSlot::ALL {
+            let _ = fs::remove_file(app_dir.join(slot.legacy_metadata_file()));
+        }
+        println!("🔊 Migrated custom sounds into {}", LIBRARY_FILE);
+    }
+    Ok(library)
+}
+
+fn write_library(library: &SoundLibrary) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(library)
+        .map_err(|e| format!("Failed to serialize sound library: {}", e))?;
+    write_atomic(&get_app_data_file(LIBRARY_FILE)?, json.as_bytes())
+        .map_err(|e| format!("Failed to save sound library: {}", e))
+}
+
+fn cached(cache: &mut Option<SoundLibrary>) -> Result<&mut SoundLibrary, String> {
+    if cache.is_none() {
+        *cache = Some(read_library()?);
+    }
+    Ok(cache.as_mut().expect("library just loaded"))
+}
+
+// Run `f` on the library under the lock and save the result. Files no longer
+// referenced afterwards are removed from the sounds directory.
+fn update_library<T>(f: impl FnOnce(&mut SoundLibrary) -> Result<T, String>) -> Result<T, String> {
+    let mut cache = LIBRARY.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let mut library = cached(&mut cache)?.clone();
+    let before = clip_files(&library);
+    let result = f(&mut library)?;
+    if let Err(e) = write_library(&library) {
+        // Files `f` stored for a change that was not saved go again
+        remove_unused(&clip_files(&library), cached(&mut cache)?);
+        return Err(e);
+    }
+    remove_unused(&before, &library);
+    *cache = Some(library);
+    Ok(result)
+}
+
+fn clip_files(library: &SoundLibrary) -> Vec<String> {
+    library.events.values()
+        .flat_map(|e| e.clips.iter().map(|c| c.filename.clone()))
+        .collect()
+}
+
+// Delete the stored files among `filenames` that `library` does not use
+fn remove_unused(filenames: &[String], library: &SoundLibrary) {
+    if let Ok(dir) = sounds_dir() {
+        for filename in filenames.iter().filter(|name| !library.references(name)) {
+            if let Ok(path) = stored_sound_path(&dir, filename) {
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+}
+
+// Read the cached library without copying it
+fn with_library<T>(f: impl FnOnce(&SoundLibrary) -> T) -> Result<T, String> {
+    let mut cache = LIBRARY.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    cached(&mut cache).map(|library| f(library))
+}
+
+pub fn library() -> Result<SoundLibrary, String> {
+    with_library(SoundLibrary::clone)
+}
+
+// Drop the cached library so the next use reads the file again (after a restore)
+pub fn reload() {
+    *LIBRARY.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+}
+
+pub fn milestone_every() -> u32 {
+    with_library(|l| l.milestone_every).unwrap_or(DEFAULT_MILESTONE_EVERY)
+}
+
+// Validate the file and describe it as a clip; the file is stored by store_clip_file
+fn import_clip(data: &[u8], original_name: &str, weight: u32) -> Result<SoundClip, AudioImportError> {
+    let analysis = audio::analyze(data)?;
+    Ok(SoundClip {
+        filename: content_addressed_name(data, &analysis),
+        original_name: Path::new(original_name)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        weight,
+        saved_at: chrono::Utc::now().to_rfc3339(),
+        analysis: Some(analysis),
+    })
+}
+
+// Called inside update_library once the clip is accepted, so a rejected clip
+// never leaves a file behind
+fn store_clip_file(data: &[u8], clip: &SoundClip) -> Result<(), String> {
+    let file_path = stored_sound_path(&sounds_dir()?, &clip.filename)?;
+    if !file_path.exists() {
+        write_atomic(&file_path, data)
+            .map_err(|e| format!("Failed to save sound file: {}", e))?;
+    }
+    Ok(())
+}
+
+fn validate_weight(weight: u32) -> Result<(), String> {
+    if weight == 0 || weight > MAX_CLIP_WEIGHT {
+        return Err(format!("Clip weight must be between 1 and {}", MAX_CLIP_WEIGHT));
+    }
+    Ok(())
+}
+
+// Replace every clip of the event with this one sound (single-sound upload)
+pub fn save_sound(slot: SoundSlot, data: &[u8], original_name: &str) -> Result<SoundClip, AudioImportError> {
+    let clip = import_clip(data, original_name, default_weight())?;
+    update_library(|library| {
+        store_clip_file(data, &clip)?;
+        library.events.entry(slot).or_default().clips = vec![clip.clone()];
+        Ok(())
+    })?;
+    Ok(clip)
+}
+
+pub fn add_clip(slot: SoundSlot, data: &[u8], original_name: &str, weight: u32) -> Result<SoundClip, AudioImportError> {
+    validate_weight(weight)?;
+    let clip = import_clip(data, original_name, weight)?;
+    update_library(|library| {
+        let clips = &mut library.events.entry(slot).or_default().clips;
+        if clips.iter().any(|c| c.filename == clip.filename) {
+            return Err("This sound is already in the list, change its weight instead".to_string());
+        }
+        if clips.len() >= MAX_CLIPS_PER_EVENT {
+            return Err(format!("An event can have at most {} sounds", MAX_CLIPS_PER_EVENT));
+        }
+        store_clip_file(data, &clip)?;
+        clips.push(clip.clone());
+        Ok(())
+    })?;
+    Ok(clip)
+}
+
+pub fn remove_clip(slot: SoundSlot, filename: &str) -> Result<(), String> {
+    update_library(|library| {
+        let clips = &mut library.events.entry(slot).or_default().clips;
+        let before = clips.len();
+        clips.retain(|c| c.filename != filename);
+        if clips.len() == before {
+            return Err(format!("Sound '{}' not found for {}", filename, slot.as_str()));
+        }
+        Ok(())
+    })
+}
+
+pub fn set_clip_weight(slot: SoundSlot, filename: &str, weight: u32) -> Result<(), String> {
+    validate_weight(weight)?;
+    update_library(|library| {
+        let clip = library.events.entry(slot).or_default().clips
+            .iter_mut()
+            .find(|c| c.filename == filename)
+            .ok_or_else(|| format!("Sound '{}' not found for {}", filename, slot.as_str()))?;
+        clip.weight = weight;
+        Ok(())
+    })
+}
+
+pub fn set_policy(slot: SoundSlot, policy: PlaybackPolicy) -> Result<(), String> {
+    update_library(|library| {
+        library.events.entry(slot).or_default().policy = policy;
+        Ok(())
+    })
+}
+
+pub fn set_milestone_every(every: u32) -> Result<(), String> {
+    update_library(|library| {
+        library.milestone_every = every;
+        Ok(())
+    })
+}
+
+// First clip of the event, for the single-sound UI
+pub fn read_metadata(slot: SoundSlot) -> Result<Option<SoundClip>, String> {
+    Ok(library()?.clips(slot).first().cloned())
+}
+
+pub fn sound_path(slot: SoundSlot) -> Result<PathBuf, String> {
+    let clip = read_metadata(slot)?.ok_or("No custom sound metadata found".to_string())?;
+    let path = stored_sound_path(&sounds_dir()?, &clip.filename)?;
+    if path.exists() {
+        Ok(path)
+    } else {
+        Err("Sound file not found".to_string())
+    }
+}
+
+// Remove every clip of the event
+pub fn delete_sound(slot: SoundSlot) -> Result<(), String> {
+    update_library(|library| {
+        library.events.remove(&slot);
+        Ok(())
+    })
+}
+
+pub fn read_sound(requested: &str) -> Result<Vec<u8>, String> {
+    let path = resolve_sound_path(&sounds_dir()?, Path::new(requested))?;
+    fs::read(&path).map_err(|e| format!("Failed to read sound file: {}", e))
+}
+
+// The clip covering `ticket` when each clip owns `weight` consecutive tickets
+fn clip_for_ticket(clips: &[SoundClip], ticket: u64) -> Option<&SoundClip> {
+    let mut remaining = ticket;
+    for clip in clips {
+        let weight = clip.weight.max(1) as u64;
+        if remaining < weight {
+            return Some(clip);
+        }
+        remaining -= weight;
+    }
+    None
+}
+
+// Weighted pick: Random draws a ticket, RoundRobin walks them in order, so a
+// clip with weight 3 plays three times per cycle
+fn choose(slot: SoundSlot, sounds: &EventSounds) -> Option<&SoundClip> {
+    let total: u64 = sounds.clips.iter().map(|c| c.weight.max(1) as u64).sum();
+    if total == 0 {
+        return None;
+    }
+    let ticket = match sounds.policy {
+        PlaybackPolicy::Random => rand::thread_rng().gen_range(0..total),
+        PlaybackPolicy::RoundRobin => {
+            let mut cursors = ROUND_ROBIN.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            let cursor = cursors.get_or_insert_with(HashMap::new).entry(slot).or_insert(0);
+            let ticket = *cursor % total;
+            *cursor = cursor.wrapping_add(1);
+            ticket
+        }
+    };
+    clip_for_ticket(&sounds.clips, ticket)
+}
+
+// Check preset sound settings against the library before they are stored
+pub fn validate_preset_sounds(settings: &PresetSounds) -> Result<(), String> {
+    let library = library()?;
+    for (slot, event) in settings {
+        if !(0.0..=1.0).contains(&event.volume) {
+            return Err(format!("Volume for {} must be between 0 and 1", slot.as_str()));
+        }
+        if let Some(missing) = event.clips.iter().find(|name| !library.clips(*slot).iter().any(|c| &c.filename == *name)) {
+            return Err(format!("Sound '{}' is not in the {} library", missing, slot.as_str()));
+        }
+    }
+    Ok(())
+}
+
+pub fn apply_preset_sounds(settings: &PresetSounds) {
+    *ACTIVE_PRESET_SOUNDS.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = settings.clone();
+}
+
+// The event's library clips narrowed to the preset's selection. Falls back to the
+// whole list when the preset picks none, or none of its picks are left in the library.
+fn preset_clips(sounds: &EventSounds, settings: &EventSoundSettings) -> EventSounds {
+    let selected: Vec<SoundClip> = sounds.clips.iter()
+        .filter(|c| settings.clips.contains(&c.filename))
+        .cloned()
+        .collect();
+    EventSounds {
+        policy: sounds.policy,
+        clips: if selected.is_empty() { sounds.clips.clone() } else { selected },
+    }
+}
+
+pub fn pick(slot: SoundSlot) -> SoundCue {
+    let settings = ACTIVE_PRESET_SOUNDS.lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .get(&slot)
+        .cloned()
+        .unwrap_or_default();
+    if settings.muted {
+        return SoundCue { event: slot, clip: None, volume: settings.volume, muted: true };
+    }
+
+    let sounds = with_library(|library| library.events.get(&slot).map(|sounds| preset_clips(sounds, &settings)))
+        .unwrap_or_default();
+    let clip = sounds.as_ref()
+        .and_then(|sounds| choose(slot, sounds))
+        .map(|clip| CueClip {
+            filename: clip.filename.clone(),
+            url: format!("/sounds/{}", clip.filename),
+            original_name: clip.original_name.clone(),
+            gain_db: clip.analysis.as_ref().map(|a| a.normalization_gain_db).unwrap_or(0.0),
+        });
+    SoundCue { event: slot, clip, volume: settings.volume, muted: false }
+}
+
+pub fn cue_channel() -> &'static broadcast::Sender<SoundCue> {
+    CUE_TX.get_or_init(|| broadcast::channel(32).0)
+}
+
+// Pick a clip for the event and tell the frontend and overlays which one to play
+pub fn emit_cue(app: &tauri::AppHandle, slot: SoundSlot) -> SoundCue {
+    let cue = pick(slot);
+    let _ = app.emit(&slot.event_name(), cue.clone());
+    let _ = cue_channel().send(cue.clone());
+    cue
+}
+
+// Which event a change of the count from `old` to `new` should sound like
+pub fn slot_for_change(old: i32, new: i32, goal: i32, milestone_every: u32) -> Option<SoundSlot> {
+    if new > old {
+        // A goal of 0 or below means no goal is set
+        if goal > 0 && old < goal && new >= goal {
+            return Some(SoundSlot::GoalReached);
+        }
+        let every = milestone_every as i32;
+        if every > 0 && new > 0 && new.div_euclid(every) > old.div_euclid(every) {
+            return Some(SoundSlot::Milestone);
+        }
+        Some(SoundSlot::Increase)
+    } else if new < old {
+        Some(SoundSlot::Decrease)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sandbox(name: &str) -> (PathBuf, PathBuf) {
+        let root = std::env::temp_dir().join(format!("wincount_sound_test_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let dir = root.join(SOUNDS_DIR);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("inside.mp3"), b"inside").unwrap();
+        fs::write(root.join("secret.txt"), b"secret").unwrap();
+        (root, dir)
+    }
+
+    #[test]
+    fn reads_files_inside_the_sounds_dir() {
+        let (root, dir) = sandbox("inside");
+        assert!(resolve_sound_path(&dir, Path::new("inside.mp3")).is_ok());
+        assert!(resolve_sound_path(&dir, &dir.join("inside.mp3")).is_ok());
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn rejects_relative_escape() {
+        let (root, dir) = sandbox("relative");
+        assert!(resolve_sound_path(&dir, Path::new("../secret.txt")).is_err());
+        assert!(resolve_sound_path(&dir, Path::new("sub/../../secret.txt")).is_err());
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn rejects_absolute_paths_outside() {
+        let (root, dir) = sandbox("absolute");
+        assert!(resolve_sound_path(&dir, &root.join("secret.txt")).is_err());
+        assert!(resolve_sound_path(&dir, &dir.join("..").join("secret.txt")).is_err());
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn rejects_the_directory_itself() {
+        let (root, dir) = sandbox("dir");
+        assert!(resolve_sound_path(&dir, &dir).is_err());
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rejects_symlink_escape() {
+        let (root, dir) = sandbox("symlink");
+        std::os::unix::fs::symlink(root.join("secret.txt"), dir.join("link.mp3")).unwrap();
+        assert!(resolve_sound_path(&dir, Path::new("link.mp3")).is_err());
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn stored_names_must_be_plain() {
+        let dir = Path::new("sounds");
+        assert!(stored_sound_path(dir, "abc.mp3").is_ok());
+        for bad in ["../evil.mp3", "..\\evil.mp3", "a/b.mp3", "C:evil.mp3", "..", ""] {
+            assert!(stored_sound_path(dir, bad).is_err(), "{} should be rejected", bad);
+        }
+    }
+
+    #[test]
+    fn stored_names_are_content_addressed() {
+        let analysis = AudioAnalysis {
+            format: audio::AudioFormat::Mp3,
+            duration_ms: 1000,
+            peak_dbfs: -1.0,
+            rms_dbfs: -16.0,
+            normalization_gain_db: 0.0,
+        };
+        let a = content_addressed_name(b"same", &analysis);
+        assert_eq!(a, content_addressed_name(b"same", &analysis));
+        assert_ne!(a, content_addressed_name(b"other", &analysis));
+        assert!(a.ends_with(".mp3"));
+        assert!(is_plain_file_name(&a));
+    }
+
+    fn clip(name: &str, weight: u32) -> SoundClip {
+        SoundClip {
+            filename: name.to_string(),
+            original_name: String::new(),
+            weight,
+            saved_at: String::new(),
+            analysis: None,
+        }
+    }
+
+    #[test]
+    fn tickets_follow_weights() {
+        let clips = vec![clip("a", 1), clip("b", 3)];
+        let picked: Vec<&str> = (0..4).map(|t| clip_for_ticket(&clips, t).unwrap().filename.as_str()).collect();
+        assert_eq!(picked, ["a", "b", "b", "b"]);
+        assert!(clip_for_ticket(&clips, 4).is_none());
+    }
+
+    #[test]
+    fn round_robin_cycles_through_weighted_clips() {
+        let sounds = EventSounds {
+            policy: PlaybackPolicy::RoundRobin,
+            clips: vec![clip("a", 2), clip("b", 1)],
+        };
+        let picked: Vec<String> = (0..6).map(|_| choose(SoundSlot::Reset, &sounds).unwrap().filename.clone()).collect();
+        assert_eq!(picked, ["a", "a", "b", "a", "a", "b"]);
+    }
+
+    #[test]
+    fn random_only_picks_existing_clips() {
+        let sounds = EventSounds {
+            policy: PlaybackPolicy::Random,
+            clips: vec![clip("a", 1), clip("b", 5)],
+        };
+        for _ in 0..100 {
+            let name = &choose(SoundSlot::Increase, &sounds).unwrap().filename;
+            assert!(name == "a" || name == "b");
+        }
+        assert!(choose(SoundSlot::Increase, &EventSounds::default()).is_none());
+    }
+
+    #[test]
+    fn changes_map_to_events() {
+        assert_eq!(slot_for_change(3, 4, 10, 10), Some(SoundSlot::Increase));
+        assert_eq!(slot_for_change(4, 3, 10, 10), Some(SoundSlot::Decrease));
+        assert_eq!(slot_for_change(9, 10, 10, 5), Some(SoundSlot::GoalReached));
+        assert_eq!(slot_for_change(13, 15, 10, 5), Some(SoundSlot::Milestone));
+        assert_eq!(slot_for_change(13, 15, 10, 0), Some(SoundSlot::Increase));
+        assert_eq!(slot_for_change(-6, -5, 10, 5), Some(SoundSlot::Increase));
+        assert_eq!(slot_for_change(5, 5, 10, 5), None);
+        // No goal set
+        assert_eq!(slot_for_change(-1, 0, 0, 5), Some(SoundSlot::Increase));
+        assert_eq!(slot_for_change(4, 5, 0, 5), Some(SoundSlot::Milestone));
+    }
+
+    #[test]
+    fn legacy_metadata_is_migrated() {
+        let (root, _dir) = sandbox("legacy");
+        fs::write(
+            root.join("sound_increase_metadata.json"),
+            r#"{"filename":"abc.mp3","original_name":"win.mp3","type":"increase","saved_at":"2024-01-01T00:00:00Z"}"#,
+        ).unwrap();
+        let library = migrate_legacy(&root);
+        assert_eq!(library.clips(SoundSlot::Increase).len(), 1);
+        assert_eq!(library.clips(SoundSlot::Increase)[0].original_name, "win.mp3");
+        assert!(library.clips(SoundSlot::Decrease).is_empty());
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn preset_selection_falls_back_to_the_library() {
+        let sounds = EventSounds {
+            policy: PlaybackPolicy::Random,
+            clips: vec![clip("a", 1), clip("b", 1)],
+        };
+        let mut settings = EventSoundSettings { clips: vec!["b".to_string()], ..Default::default() };
+        let narrowed = preset_clips(&sounds, &settings);
+        assert_eq!(narrowed.clips.len(), 1);
+        assert_eq!(narrowed.clips[0].filename, "b");
+
+        settings.clips = vec!["gone".to_string()];
+        assert_eq!(preset_clips(&sounds, &settings).clips.len(), 2);
+        settings.clips.clear();
+        assert_eq!(preset_clips(&sounds, &settings).clips.len(), 2);
+    }
+}
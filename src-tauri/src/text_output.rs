@@ -0,0 +1,171 @@
+// Plain-text output for OBS Text (GDI+/FreeType) sources. When enabled, one small
+// file per field is rewritten in the chosen folder on every state broadcast.
+// Runs on its own thread off the state channel, independent of the overlay server.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+
+use crate::backup::DataFile;
+use crate::{get_app_data_file, write_atomic, SharedWinState, WinState};
+
+const CONFIG_FILE: &str = "win_count_text_output.json";
+pub const DATA_FILE: DataFile = DataFile::json::<TextOutputConfig>(CONFIG_FILE);
+
+static CONFIG: Mutex<Option<TextOutputConfig>> = Mutex::new(None);
+
+// Last content written per file, so unchanged files are not rewritten
+static LAST_WRITTEN: Mutex<Option<HashMap<PathBuf, String>>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TextOutputConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub folder: Option<String>,
+}
+
+// File name -> content for one state
+fn files_for(state: &WinState) -> [(&'static str, String); 4] {
+    [
+        ("win.txt", state.win.to_string()),
+        ("goal.txt", state.goal.to_string()),
+        ("win_goal.txt", format!("{}/{}", state.win, state.goal)),
+        ("preset.txt", state.current_preset.clone()),
+    ]
+}
+
+fn read_config() -> TextOutputConfig {
+    get_app_data_file(CONFIG_FILE)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+pub fn config() -> TextOutputConfig {
+    let mut config = CONFIG.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    config.get_or_insert_with(read_config).clone()
+}
+
+// Drop the cached settings so they are read from disk again (after a restore)
+pub fn reload_config() {
+    CONFIG.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take();
+    LAST_WRITTEN.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take();
+}
+
+pub fn set_config(new_config: TextOutputConfig, state: &WinState) -> Result<(), String> {
+    if new_config.enabled {
+        let folder = new_config.folder.as_deref().filter(|f| !f.trim().is_empty())
+            .ok_or_else(|| "Choose a folder for the text files".to_string())?;
+        if !Path::new(folder).is_dir() {
+            return Err(format!("Folder '{}' does not exist", folder));
+        }
+    }
+
+    let json = serde_json::to_string_pretty(&new_config)
+        .map_err(|e| format!("Failed to serialize text output settings: {}", e))?;
+    write_atomic(&get_app_data_file(CONFIG_FILE)?, json.as_bytes())
+        .map_err(|e| format!("Failed to save text output settings: {}", e))?;
+    *CONFIG.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(new_config);
+
+    // Fill a newly chosen folder right away instead of waiting for the next change
+    LAST_WRITTEN.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take();
+    write_state(state)
+}
+
+// Write the files for `state` if the sink is enabled
+pub fn write_state(state: &WinState) -> Result<(), String> {
+    let config = config();
+    let Some(folder) = config.folder.filter(|_| config.enabled) else {
+        return Ok(());
+    };
+    let mut last = LAST_WRITTEN.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    write_files(Path::new(&folder), state, last.get_or_insert_with(HashMap::new)).map(|_| ())
+}
+
+// Write the files whose content differs from `last`; returns how many were written
+fn write_files(folder: &Path, state: &WinState, last: &mut HashMap<PathBuf, String>) -> Result<usize, String> {
+    let mut written = 0;
+    for (name, content) in files_for(state) {
+        let path = folder.join(name);
+        if last.get(&path) == Some(&content) {
+            continue;
+        }
+        write_atomic(&path, content.as_bytes())
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        last.insert(path, content);
+        written += 1;
+    }
+    Ok(written)
+}
+
+pub fn start(shared_state: SharedWinState, mut rx: broadcast::Receiver<WinState>) {
+    std::thread::spawn(move || {
+        let initial = shared_state.lock().unwrap().clone();
+        if let Err(e) = write_state(&initial) {
+            println!("⚠️ Text output: {}", e);
+        }
+        loop {
+            match rx.blocking_recv() {
+                Ok(state) => {
+                    if let Err(e) = write_state(&state) {
+                        println!("⚠️ Text output: {}", e);
+                    }
+                }
+                // Only the latest state matters
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_file_per_field() {
+        let state = WinState::sample(-3, 20);
+        let files = files_for(&state);
+        assert_eq!(files[0], ("win.txt", "-3".to_string()));
+        assert_eq!(files[1], ("goal.txt", "20".to_string()));
+        assert_eq!(files[2], ("win_goal.txt", "-3/20".to_string()));
+        assert_eq!(files[3], ("preset.txt", "Ranked".to_string()));
+    }
+
+    #[test]
+    fn writes_only_changed_files() {
+        let folder = std::env::temp_dir().join(format!("wincount_text_output_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&folder);
+        fs::create_dir_all(&folder).unwrap();
+        let mut last = HashMap::new();
+
+        assert_eq!(write_files(&folder, &WinState::sample(3, 10), &mut last).unwrap(), 4);
+        assert_eq!(fs::read_to_string(folder.join("win_goal.txt")).unwrap(), "3/10");
+        assert_eq!(fs::read_to_string(folder.join("preset.txt")).unwrap(), "Ranked");
+
+        // Same state: nothing is touched, even a file edited behind our back
+        fs::write(folder.join("goal.txt"), "edited").unwrap();
+        assert_eq!(write_files(&folder, &WinState::sample(3, 10), &mut last).unwrap(), 0);
+        assert_eq!(fs::read_to_string(folder.join("goal.txt")).unwrap(), "edited");
+
+        // A new win rewrites win.txt and win_goal.txt only
+        assert_eq!(write_files(&folder, &WinState::sample(4, 10), &mut last).unwrap(), 2);
+        assert_eq!(fs::read_to_string(folder.join("win.txt")).unwrap(), "4");
+        assert_eq!(fs::read_to_string(folder.join("goal.txt")).unwrap(), "edited");
+        let _ = fs::remove_dir_all(&folder);
+    }
+
+    #[test]
+    fn missing_folder_is_an_error() {
+        let folder = std::env::temp_dir().join(format!("wincount_text_output_missing_{}", std::process::id()));
+        let mut last = HashMap::new();
+        assert!(write_files(&folder, &WinState::sample(1, 2), &mut last).is_err());
+        assert!(last.is_empty());
+    }
+}
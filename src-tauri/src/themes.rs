@@ -0,0 +1,450 @@
+// Overlay theme packs. Each theme is a folder under <app data>/themes holding a
+// theme.json manifest plus its html/css/images/fonts, served at /themes/<id>/.
+// Files a theme does not provide fall back to the embedded overlay assets.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{Cursor, Read};
+use std::path::{Component, Path, PathBuf};
+
+use crate::overlay::HttpReply;
+use crate::{get_app_data_dir, EmbeddedAssets};
+
+const THEMES_DIR: &str = "themes";
+const MANIFEST_FILE: &str = "theme.json";
+const DEFAULT_ENTRY: &str = "overlay.html";
+
+// The embedded overlay is listed as this theme and cannot be replaced
+pub const BUILTIN_THEME: &str = "default";
+
+// Limits for installed theme archives
+const MAX_THEME_FILES: usize = 500;
+const MAX_THEME_BYTES: u64 = 50 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThemeManifest {
+    // Folder name and URL segment; derived from `name` when left out
+    #[serde(default)]
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub author: String,
+    #[serde(default)]
+    pub description: String,
+    // Page served at /themes/<id>/
+    #[serde(default)]
+    pub entry: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ThemeInfo {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    pub author: String,
+    pub description: String,
+    pub entry: String,
+    pub builtin: bool,
+    pub url: String,
+}
+
+impl ThemeInfo {
+    fn new(id: String, manifest: ThemeManifest, builtin: bool) -> Self {
+        let entry = manifest.entry.unwrap_or_else(|| DEFAULT_ENTRY.to_string());
+        Self {
+            url: format!("/themes/{}/", id),
+            id,
+            name: manifest.name,
+            version: manifest.version,
+            author: manifest.author,
+            description: manifest.description,
+            entry,
+            builtin,
+        }
+    }
+}
+
+fn themes_dir() -> Result<PathBuf, String> {
+    let dir = get_app_data_dir()?.join(THEMES_DIR);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create themes directory: {}", e))?;
+    Ok(dir)
+}
+
+fn is_valid_theme_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= 64
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+// "My Neon Theme!" -> "my-neon-theme"
+fn slugify(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.trim().to_lowercase().chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if !slug.ends_with('-') && !slug.is_empty() {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').chars().take(64).collect()
+}
+
+fn manifest_id(manifest: &ThemeManifest) -> Result<String, String> {
+    let id = match &manifest.id {
+        Some(id) => id.trim().to_string(),
+        None => slugify(&manifest.name),
+    };
+    if !is_valid_theme_id(&id) {
+        return Err(format!("Invalid theme id '{}': use letters, digits, '-' and '_'", id));
+    }
+    if id == BUILTIN_THEME {
+        return Err(format!("Theme id '{}' is reserved for the built-in overlay", BUILTIN_THEME));
+    }
+    Ok(id)
+}
+
+fn read_manifest(dir: &Path) -> Option<ThemeManifest> {
+    let json = fs::read_to_string(dir.join(MANIFEST_FILE)).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+fn builtin_theme() -> ThemeInfo {
+    ThemeInfo::new(
+        BUILTIN_THEME.to_string(),
+        ThemeManifest {
+            id: None,
+            name: "Default".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            author: "ArtYWoof".to_string(),
+            description: "Built-in overlay".to_string(),
+            entry: None,
+        },
+        true,
+    )
+}
+
+// Built-in theme first, then installed themes by name
+pub fn list_themes() -> Result<Vec<ThemeInfo>, String> {
+    let mut themes: Vec<ThemeInfo> = fs::read_dir(themes_dir()?)
+        .map_err(|e| format!("Failed to read themes directory: {}", e))?
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| {
+            let id = entry.file_name().to_string_lossy().to_string();
+            if !is_valid_theme_id(&id) || id == BUILTIN_THEME {
+                return None;
+            }
+            read_manifest(&entry.path()).map(|manifest| ThemeInfo::new(id, manifest, false))
+        })
+        .collect();
+    themes.sort_by_key(|t| t.name.to_lowercase());
+    themes.insert(0, builtin_theme());
+    Ok(themes)
+}
+
+// A relative path from a URL or archive, as plain components only
+fn safe_relative_path(path: &str) -> Option<PathBuf> {
+    if path.contains(['\\', ':', '%', '\0']) {
+        return None;
+    }
+    let relative = Path::new(path);
+    let mut clean = PathBuf::new();
+    for component in relative.components() {
+        match component {
+            Component::Normal(part) => clean.push(part),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    (!clean.as_os_str().is_empty()).then_some(clean)
+}
+
+// Resolve a file inside a theme folder, refusing anything that lands outside it
+pub fn resolve_theme_file(theme_root: &Path, relative: &str) -> Option<PathBuf> {
+    let relative = safe_relative_path(relative)?;
+    let canonical_root = theme_root.canonicalize().ok()?;
+    let canonical = theme_root.join(relative).canonicalize().ok()?;
+    (canonical.starts_with(&canonical_root) && canonical.is_file()).then_some(canonical)
+}
+
+pub fn content_type_for(path: &str) -> &'static str {
+    let extension = path.rsplit_once('.').map(|(_, ext)| ext.to_ascii_lowercase()).unwrap_or_default();
+    match extension.as_str() {
+        "html" | "htm" => "text/html; charset=utf-8",
+        "css" => "text/css",
+        "js" | "mjs" => "application/javascript",
+        "json" => "application/json",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "ico" => "image/x-icon",
+        "ttf" => "font/ttf",
+        "otf" => "font/otf",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "ogg" => "audio/ogg",
+        "webm" => "video/webm",
+        "mp4" => "video/mp4",
+        _ => "application/octet-stream",
+    }
+}
+
+fn embedded_reply(relative: &str) -> Option<HttpReply> {
+    let file = EmbeddedAssets::get(relative)?;
+    Some(HttpReply::ok(content_type_for(relative), file.data.into_owned()))
+}
+
+// Handle "GET /themes/<id>/<path>"
+pub fn theme_response(target: &str) -> HttpReply {
+    let path = target.split(['?', '#']).next().unwrap_or("");
+    let rest = path.strip_prefix("/themes/").unwrap_or("");
+    let Some((id, relative)) = rest.split_once('/') else {
+        // Relative links in the theme only work below "/themes/<id>/"
+        return if is_valid_theme_id(rest) {
+            HttpReply::redirect(format!("/themes/{}/", rest))
+        } else {
+            HttpReply::error("404 Not Found", "404 - Theme not found")
+        };
+    };
+    if !is_valid_theme_id(id) {
+        return HttpReply::error("404 Not Found", "404 - Theme not found");
+    }
+
+    let theme_root = themes_dir().ok()
+        .map(|dir| dir.join(id))
+        .filter(|root| id != BUILTIN_THEME && root.is_dir());
+    let manifest = theme_root.as_deref().and_then(read_manifest);
+    let relative = if relative.is_empty() {
+        manifest.as_ref().and_then(|m| m.entry.clone()).unwrap_or_else(|| DEFAULT_ENTRY.to_string())
+    } else {
+        relative.to_string()
+    };
+    if safe_relative_path(&relative).is_none() {
+        return HttpReply::error("400 Bad Request", "400 - Invalid path");
+    }
+
+    if let Some(file) = theme_root.as_deref().and_then(|root| resolve_theme_file(root, &relative)) {
+        return match fs::read(&file) {
+            Ok(body) => HttpReply::ok(content_type_for(&relative), body),
+            Err(e) => {
+                println!("❌ Failed to read theme file {:?}: {}", file, e);
+                HttpReply::error("500 Internal Server Error", "500 - Failed to read theme file")
+            }
+        };
+    }
+
+    // Not in the theme (or no such theme): use the built-in overlay files
+    embedded_reply(&relative)
+        .or_else(|| (relative == DEFAULT_ENTRY || relative.ends_with(".html")).then(|| embedded_reply(DEFAULT_ENTRY)).flatten())
+        .unwrap_or_else(|| HttpReply::error("404 Not Found", "404 - File not found"))
+}
+
+// Locate theme.json in the archive: at the root or inside one top-level folder
+fn archive_prefix(names: &[String]) -> Result<String, String> {
+    if names.iter().any(|n| n == MANIFEST_FILE) {
+        return Ok(String::new());
+    }
+    names.iter()
+        .filter_map(|n| n.strip_suffix(MANIFEST_FILE))
+        .find(|prefix| prefix.ends_with('/') && prefix.matches('/').count() == 1)
+        .map(|prefix| prefix.to_string())
+        .ok_or_else(|| format!("Theme archive has no {}", MANIFEST_FILE))
+}
+
+// Install (or replace) a theme from a zip archive
+pub fn install_theme(data: &[u8]) -> Result<ThemeInfo, String> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(data))
+        .map_err(|e| format!("Invalid theme archive: {}", e))?;
+    if archive.len() > MAX_THEME_FILES {
+        return Err(format!("Theme archive has too many files (max {})", MAX_THEME_FILES));
+    }
+    let names: Vec<String> = archive.file_names().map(|n| n.to_string()).collect();
+    let prefix = archive_prefix(&names)?;
+
+    let mut manifest_json = String::new();
+    archive.by_name(&format!("{}{}", prefix, MANIFEST_FILE))
+        .map_err(|e| format!("Failed to read {}: {}", MANIFEST_FILE, e))?
+        .take(64 * 1024)
+        .read_to_string(&mut manifest_json)
+        .map_err(|e| format!("Failed to read {}: {}", MANIFEST_FILE, e))?;
+    let manifest: ThemeManifest = serde_json::from_str(&manifest_json)
+        .map_err(|e| format!("Invalid {}: {}", MANIFEST_FILE, e))?;
+    let id = manifest_id(&manifest)?;
+
+    // Unpack into a staging folder, then swap it in
+    let dir = themes_dir()?;
+    let staging = dir.join(format!(".install-{}-{}", id, std::process::id()));
+    let _ = fs::remove_dir_all(&staging);
+    fs::create_dir_all(&staging).map_err(|e| format!("Failed to create theme folder: {}", e))?;
+    if let Err(e) = extract(&mut archive, &prefix, &staging) {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(e);
+    }
+
+    let target = dir.join(&id);
+    let backup = dir.join(format!(".old-{}-{}", id, std::process::id()));
+    if target.exists() {
+        fs::rename(&target, &backup).map_err(|e| format!("Failed to replace theme '{}': {}", id, e))?;
+    }
+    if let Err(e) = fs::rename(&staging, &target) {
+        let _ = fs::rename(&backup, &target);
+        let _ = fs::remove_dir_all(&staging);
+        return Err(format!("Failed to install theme '{}': {}", id, e));
+    }
+    let _ = fs::remove_dir_all(&backup);
+
+    Ok(ThemeInfo::new(id, manifest, false))
+}
+
+fn extract<R: Read + std::io::Seek>(archive: &mut zip::ZipArchive<R>, prefix: &str, staging: &Path) -> Result<(), String> {
+    let mut total: u64 = 0;
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i).map_err(|e| format!("Invalid theme archive: {}", e))?;
+        let name = file.name().to_string();
+        let Some(inside) = name.strip_prefix(prefix) else {
+            continue;
+        };
+        if file.is_dir() || inside.is_empty() {
+            continue;
+        }
+        // Symlinks could point anywhere once unpacked
+        if file.unix_mode().map(|mode| mode & 0o170000 == 0o120000).unwrap_or(false) {
+            return Err(format!("Theme archive entry '{}' is a symlink", name));
+        }
+        let relative = safe_relative_path(inside)
+            .ok_or_else(|| format!("Theme archive entry '{}' points outside the theme folder", name))?;
+
+        let declared = file.size();
+        total += declared;
+        if total > MAX_THEME_BYTES {
+            return Err(format!("Theme is too large (max {} MB)", MAX_THEME_BYTES / 1024 / 1024));
+        }
+        let out_path = staging.join(relative);
+        if let Some(parent) = out_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create theme folder: {}", e))?;
+        }
+        let mut contents = Vec::new();
+        // Bound the read by the declared size so a lying header cannot inflate past the limit
+        (&mut file)
+            .take(declared + 1)
+            .read_to_end(&mut contents)
+            .map_err(|e| format!("Failed to unpack '{}': {}", name, e))?;
+        if contents.len() as u64 > declared {
+            return Err(format!("Theme archive entry '{}' is larger than declared", name));
+        }
+        fs::write(&out_path, contents).map_err(|e| format!("Failed to write '{}': {}", name, e))?;
+    }
+    Ok(())
+}
+
+pub fn delete_theme(id: &str) -> Result<(), String> {
+    if !is_valid_theme_id(id) || id == BUILTIN_THEME {
+        return Err(format!("Theme '{}' cannot be deleted", id));
+    }
+    let target = themes_dir()?.join(id);
+    if !target.is_dir() {
+        return Err(format!("Theme '{}' not found", id));
+    }
+    fs::remove_dir_all(&target).map_err(|e| format!("Failed to delete theme '{}': {}", id, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relative_paths_stay_inside() {
+        assert_eq!(safe_relative_path("css/style.css"), Some(PathBuf::from("css/style.css")));
+        assert_eq!(safe_relative_path("./overlay.html"), Some(PathBuf::from("overlay.html")));
+        for bad in ["../secret", "a/../../b", "/etc/passwd", "..\\x", "C:/x", "%2e%2e/x", ""] {
+            assert!(safe_relative_path(bad).is_none(), "{} should be rejected", bad);
+        }
+    }
+
+    #[test]
+    fn theme_files_resolve_only_inside_the_root() {
+        let root = std::env::temp_dir().join(format!("wincount_theme_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let theme = root.join("neon");
+        fs::create_dir_all(theme.join("css")).unwrap();
+        fs::write(theme.join("css/style.css"), b"body{}").unwrap();
+        fs::write(root.join("secret.txt"), b"secret").unwrap();
+
+        assert!(resolve_theme_file(&theme, "css/style.css").is_some());
+        assert!(resolve_theme_file(&theme, "../secret.txt").is_none());
+        assert!(resolve_theme_file(&theme, "css").is_none());
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(root.join("secret.txt"), theme.join("link.txt")).unwrap();
+            assert!(resolve_theme_file(&theme, "link.txt").is_none());
+        }
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn manifest_ids() {
+        let manifest = |id: Option<&str>, name: &str| ThemeManifest {
+            id: id.map(|s| s.to_string()),
+            name: name.to_string(),
+            version: String::new(),
+            author: String::new(),
+            description: String::new(),
+            entry: None,
+        };
+        assert_eq!(manifest_id(&manifest(None, "My Neon Theme!")).unwrap(), "my-neon-theme");
+        assert_eq!(manifest_id(&manifest(Some("retro_1"), "x")).unwrap(), "retro_1");
+        assert!(manifest_id(&manifest(Some("../evil"), "x")).is_err());
+        assert!(manifest_id(&manifest(None, "Default")).is_err());
+        assert!(manifest_id(&manifest(None, "!!!")).is_err());
+    }
+
+    #[test]
+    fn manifest_may_sit_in_one_top_level_folder() {
+        let names = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(archive_prefix(&names(&["theme.json", "overlay.html"])).unwrap(), "");
+        assert_eq!(archive_prefix(&names(&["neon/theme.json", "neon/overlay.html"])).unwrap(), "neon/");
+        assert!(archive_prefix(&names(&["a/b/theme.json"])).is_err());
+        assert!(archive_prefix(&names(&["overlay.html"])).is_err());
+    }
+
+    #[test]
+    fn content_types() {
+        assert_eq!(content_type_for("overlay.html"), "text/html; charset=utf-8");
+        assert_eq!(content_type_for("fonts/a.WOFF2"), "font/woff2");
+        assert_eq!(content_type_for("noext"), "application/octet-stream");
+    }
+
+    fn zip_of(files: &[(&str, &[u8])]) -> Vec<u8> {
+        use std::io::Write;
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, data) in files {
+            writer.start_file(*name, zip::write::SimpleFileOptions::default()).unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn extract_refuses_entries_outside_the_theme() {
+        let staging = std::env::temp_dir().join(format!("wincount_theme_extract_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&staging);
+        fs::create_dir_all(&staging).unwrap();
+
+        let good = zip_of(&[("neon/theme.json", b"{}"), ("neon/css/a.css", b"body{}")]);
+        let mut archive = zip::ZipArchive::new(Cursor::new(good)).unwrap();
+        extract(&mut archive, "neon/", &staging).unwrap();
+        assert!(staging.join("css/a.css").is_file());
+
+        let evil = zip_of(&[("theme.json", b"{}"), ("../evil.txt", b"x")]);
+        let mut archive = zip::ZipArchive::new(Cursor::new(evil)).unwrap();
+        assert!(extract(&mut archive, "", &staging).is_err());
+        assert!(!staging.parent().unwrap().join("evil.txt").exists());
+        let _ = fs::remove_dir_all(staging);
+    }
+}
@@ -0,0 +1,489 @@
+// Outgoing HTTP webhooks (Discord, custom backends) fired from the state broadcast.
+// Each webhook picks the events it wants and may shape its JSON body with a template
+// whose strings use the label placeholders plus {event}. Failed deliveries are
+// retried with backoff; the latest attempts are kept in an in-memory delivery log.
+
+use chrono::{DateTime, Utc};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+use crate::backup::DataFile;
+use crate::integration::{self, ConfigFile};
+use crate::{labels, sounds, SharedWinState, WinState};
+
+const WEBHOOKS_FILE: &str = "win_count_webhooks.json";
+// URLs carry their access tokens, so they stay out of backups
+pub const DATA_FILE: DataFile = DataFile::json::<Vec<Webhook>>(WEBHOOKS_FILE).with_secrets(backup_copy, restored_copy);
+pub const MAX_WEBHOOKS: usize = 20;
+pub const MAX_TEMPLATE_BYTES: usize = 4096;
+const MAX_LOG_ENTRIES: usize = 200;
+const MAX_ATTEMPTS: u32 = 4;
+const RETRY_BASE: Duration = Duration::from_secs(2);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Placeholders that become JSON numbers when a template string is only that placeholder
+const NUMERIC_PLACEHOLDERS: [&str; 4] = ["win", "goal", "remaining", "percent"];
+
+static WEBHOOKS: ConfigFile<Vec<Webhook>> = ConfigFile::new(WEBHOOKS_FILE, "webhooks");
+
+static DELIVERY_LOG: Mutex<VecDeque<DeliveryRecord>> = Mutex::new(VecDeque::new());
+
+// Counter events that webhooks and OBS rules can react to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CounterEvent {
+    // Any change of the win count or goal
+    Change,
+    GoalReached,
+    Milestone,
+    Reset,
+    // Another preset was loaded; carries the final count of the one that was left
+    SessionEnd,
+}
+
+impl CounterEvent {
+    pub fn name(self) -> &'static str {
+        match self {
+            CounterEvent::Change => "change",
+            CounterEvent::GoalReached => "goal_reached",
+            CounterEvent::Milestone => "milestone",
+            CounterEvent::Reset => "reset",
+            CounterEvent::SessionEnd => "session_end",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Webhook {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub events: Vec<CounterEvent>,
+    // JSON body template; the default body is used when empty
+    #[serde(default)]
+    pub template: Option<String>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DeliveryRecord {
+    pub webhook_id: String,
+    pub event: CounterEvent,
+    pub sent_at: DateTime<Utc>,
+    pub attempts: u32,
+    pub status: Option<u16>,
+    pub error: Option<String>,
+    pub success: bool,
+}
+
+const DEFAULT_TEMPLATE: &str = r#"{"event":"{event}","win":"{win}","goal":"{goal}","preset":"{preset}"}"#;
+
+// Events a change from `old` to `new` stands for
+pub fn events_for(old: &WinState, new: &WinState, milestone_every: u32) -> Vec<CounterEvent> {
+    let mut events = Vec::new();
+    if old.win != new.win || old.goal != new.goal {
+        events.push(CounterEvent::Change);
+    }
+    // Switching presets loads another count; that is not progress
+    if old.current_preset != new.current_preset {
+        events.push(CounterEvent::SessionEnd);
+        return events;
+    }
+    if old.win == new.win {
+        return events;
+    }
+    match sounds::slot_for_change(old.win, new.win, new.goal, milestone_every) {
+        Some(sounds::SoundSlot::GoalReached) => events.push(CounterEvent::GoalReached),
+        Some(sounds::SoundSlot::Milestone) => events.push(CounterEvent::Milestone),
+        _ => {}
+    }
+    if new.win == 0 && old.win != 0 {
+        events.push(CounterEvent::Reset);
+    }
+    events
+}
+
+fn render_value(value: &Value, state: &WinState, event: CounterEvent) -> Value {
+    match value {
+        Value::String(template) => {
+            let rendered = labels::render_with(template, state, &[("event", event.name())]);
+            let whole = template.strip_prefix('{').and_then(|t| t.strip_suffix('}')).map(str::trim);
+            match whole.filter(|name| NUMERIC_PLACEHOLDERS.contains(name)).and_then(|_| rendered.parse::<i64>().ok()) {
+                Some(number) => Value::from(number),
+                None => Value::String(rendered),
+            }
+        }
+        Value::Array(items) => Value::Array(items.iter().map(|v| render_value(v, state, event)).collect()),
+        Value::Object(fields) => Value::Object(fields.iter().map(|(k, v)| (k.clone(), render_value(v, state, event))).collect()),
+        other => other.clone(),
+    }
+}
+
+// Request body for one event. Placeholders are filled inside JSON strings only,
+// so values can never break the JSON structure.
+pub fn render_body(template: Option<&str>, state: &WinState, event: CounterEvent) -> Result<Value, String> {
+    let template = template.filter(|t| !t.trim().is_empty()).unwrap_or(DEFAULT_TEMPLATE);
+    let parsed: Value = serde_json::from_str(template).map_err(|e| format!("Template is not valid JSON: {}", e))?;
+    Ok(render_value(&parsed, state, event))
+}
+
+fn validate_strings(value: &Value) -> Result<(), String> {
+    match value {
+        Value::String(s) => labels::validate_placeholders(s, &["event"]),
+        Value::Array(items) => items.iter().try_for_each(validate_strings),
+        Value::Object(fields) => fields.values().try_for_each(validate_strings),
+        _ => Ok(()),
+    }
+}
+
+pub fn validate(webhook: &Webhook) -> Result<(), String> {
+    if webhook.name.trim().is_empty() {
+        return Err("Webhook name cannot be empty".to_string());
+    }
+    let label = webhook.name.trim();
+    let url = reqwest::Url::parse(&webhook.url).map_err(|_| format!("{}: invalid URL", label))?;
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return Err(format!("{}: URL must start with http:// or https://", label));
+    }
+    if webhook.events.is_empty() {
+        return Err(format!("{}: choose at least one event", label));
+    }
+    if let Some(template) = webhook.template.as_deref().filter(|t| !t.trim().is_empty()) {
+        if template.len() > MAX_TEMPLATE_BYTES {
+            return Err(format!("{}: template is too long (max {} bytes)", label, MAX_TEMPLATE_BYTES));
+        }
+        let parsed: Value = serde_json::from_str(template).map_err(|e| format!("{}: template is not valid JSON: {}", label, e))?;
+        validate_strings(&parsed).map_err(|e| format!("{}: {}", label, e))?;
+    }
+    Ok(())
+}
+
+// Webhook URLs carry their credentials (Discord puts the token in the path), so
+// the UI only sees the host
+fn masked_url(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(parsed) => format!("{}://{}/{}", parsed.scheme(), parsed.host_str().unwrap_or_default(), integration::MASKED_SECRET),
+        Err(_) => integration::MASKED_SECRET.to_string(),
+    }
+}
+
+// The webhooks as shown in the UI
+pub fn list() -> Vec<Webhook> {
+    WEBHOOKS.get()
+        .into_iter()
+        .map(|webhook| Webhook { url: masked_url(&webhook.url), ..webhook })
+        .collect()
+}
+
+// The webhooks file as stored in backups: every URL is left out
+fn backup_copy(data: &[u8]) -> Result<Vec<u8>, String> {
+    let webhooks: Vec<Webhook> = serde_json::from_slice(data).map_err(|e| format!("Invalid webhooks: {}", e))?;
+    let stripped: Vec<Webhook> = webhooks.into_iter().map(|webhook| Webhook { url: String::new(), ..webhook }).collect();
+    serde_json::to_vec_pretty(&stripped).map_err(|e| format!("Failed to serialize webhooks: {}", e))
+}
+
+// A backed-up webhooks file about to be restored: URLs come back from the current
+// webhooks by id, and a webhook this machine has no URL for is turned off
+fn restored_copy(data: &[u8]) -> Result<Vec<u8>, String> {
+    restore_urls(data, &WEBHOOKS.get())
+}
+
+fn restore_urls(data: &[u8], current: &[Webhook]) -> Result<Vec<u8>, String> {
+    let mut webhooks: Vec<Webhook> = serde_json::from_slice(data).map_err(|e| format!("Invalid webhooks: {}", e))?;
+    for webhook in webhooks.iter_mut().filter(|w| w.url.is_empty()) {
+        match current.iter().find(|w| !w.id.is_empty() && w.id == webhook.id) {
+            Some(stored) => webhook.url = stored.url.clone(),
+            None => webhook.enabled = false,
+        }
+    }
+    serde_json::to_vec_pretty(&webhooks).map_err(|e| format!("Failed to serialize webhooks: {}", e))
+}
+
+// Replace the whole list; webhooks without an id get a new one. A URL sent back
+// masked keeps the stored one.
+pub fn save(mut webhooks: Vec<Webhook>) -> Result<Vec<Webhook>, String> {
+    if webhooks.len() > MAX_WEBHOOKS {
+        return Err(format!("At most {} webhooks are allowed", MAX_WEBHOOKS));
+    }
+    let current = WEBHOOKS.get();
+    for webhook in &mut webhooks {
+        webhook.name = webhook.name.trim().to_string();
+        if let Some(stored) = current.iter().find(|w| !w.id.is_empty() && w.id == webhook.id) {
+            if webhook.url == masked_url(&stored.url) {
+                webhook.url = stored.url.clone();
+            }
+        }
+        validate(webhook)?;
+        if webhook.id.is_empty() {
+            let mut bytes = [0u8; 6];
+            rand::thread_rng().fill_bytes(&mut bytes);
+            webhook.id = hex::encode(bytes);
+        }
+    }
+    WEBHOOKS.save(webhooks)?;
+    Ok(list())
+}
+
+// Pick up the webhooks file again (after a restore)
+pub fn reload_config() {
+    WEBHOOKS.reload();
+}
+
+// Newest first
+pub fn delivery_log() -> Vec<DeliveryRecord> {
+    DELIVERY_LOG.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).iter().rev().cloned().collect()
+}
+
+fn log_delivery(record: DeliveryRecord) {
+    let mut log = DELIVERY_LOG.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if log.len() >= MAX_LOG_ENTRIES {
+        log.pop_front();
+    }
+    log.push_back(record);
+}
+
+// POST the body, retrying server errors, rate limits and network failures with
+// exponential backoff starting at `retry_base`
+pub async fn deliver(client: &reqwest::Client, webhook: &Webhook, event: CounterEvent, body: &Value, retry_base: Duration) -> DeliveryRecord {
+    let mut record = DeliveryRecord {
+        webhook_id: webhook.id.clone(),
+        event,
+        sent_at: Utc::now(),
+        attempts: 0,
+        status: None,
+        error: None,
+        success: false,
+    };
+    let mut delay = retry_base;
+    while record.attempts < MAX_ATTEMPTS {
+        if record.attempts > 0 {
+            tokio::time::sleep(delay).await;
+            delay *= 2;
+        }
+        record.attempts += 1;
+        match client.post(&webhook.url).timeout(REQUEST_TIMEOUT).json(body).send().await {
+            Ok(response) => {
+                let status = response.status();
+                record.status = Some(status.as_u16());
+                if status.is_success() {
+                    record.success = true;
+                    record.error = None;
+                    break;
+                }
+                record.error = Some(format!("HTTP {}", status));
+                // Other client errors will not fix themselves
+                if status.is_client_error() && status != reqwest::StatusCode::TOO_MANY_REQUESTS {
+                    break;
+                }
+            }
+            Err(e) => {
+                record.status = None;
+                record.error = Some(e.to_string());
+            }
+        }
+    }
+    record
+}
+
+async fn dispatch(client: reqwest::Client, webhook: Webhook, event: CounterEvent, state: WinState) {
+    let body = match render_body(webhook.template.as_deref(), &state, event) {
+        Ok(body) => body,
+        Err(e) => {
+            log_delivery(DeliveryRecord {
+                webhook_id: webhook.id.clone(),
+                event,
+                sent_at: Utc::now(),
+                attempts: 0,
+                status: None,
+                error: Some(e),
+                success: false,
+            });
+            return;
+        }
+    };
+    let record = deliver(&client, &webhook, event, &body, RETRY_BASE).await;
+    if record.success {
+        println!("🔔 Webhook '{}' delivered {} ({} attempt(s))", webhook.name, event.name(), record.attempts);
+    } else {
+        println!("⚠️ Webhook '{}' failed for {}: {}", webhook.name, event.name(), record.error.as_deref().unwrap_or("unknown error"));
+    }
+    log_delivery(record);
+}
+
+pub fn start(shared_state: SharedWinState, mut rx: broadcast::Receiver<WinState>) {
+    std::thread::spawn(move || {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async move {
+            let client = reqwest::Client::new();
+            let mut previous = shared_state.lock().unwrap().clone();
+            loop {
+                let state = match rx.recv().await {
+                    Ok(state) => state,
+                    // Missed states only matter for the comparison, which uses the newest one
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                let events = events_for(&previous, &state, sounds::milestone_every());
+                let left = std::mem::replace(&mut previous, state.clone());
+                if events.is_empty() {
+                    continue;
+                }
+                for webhook in WEBHOOKS.get().into_iter().filter(|w| w.enabled) {
+                    for event in events.iter().copied().filter(|e| webhook.events.contains(e)) {
+                        let body_state = if event == CounterEvent::SessionEnd { left.clone() } else { state.clone() };
+                        tokio::spawn(dispatch(client.clone(), webhook.clone(), event, body_state));
+                    }
+                }
+            }
+        });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn webhook(url: String) -> Webhook {
+        Webhook { id: "hook".to_string(), name: "Test".to_string(), url, enabled: true, events: vec![CounterEvent::GoalReached], template: None }
+    }
+
+    #[test]
+    fn detects_events() {
+        assert_eq!(events_for(&WinState::sample(9, 10), &WinState::sample(10, 10), 5), vec![CounterEvent::Change, CounterEvent::GoalReached]);
+        assert_eq!(events_for(&WinState::sample(4, 10), &WinState::sample(5, 10), 5), vec![CounterEvent::Change, CounterEvent::Milestone]);
+        assert_eq!(events_for(&WinState::sample(3, 10), &WinState::sample(0, 10), 5), vec![CounterEvent::Change, CounterEvent::Reset]);
+        assert_eq!(events_for(&WinState::sample(3, 10), &WinState::sample(3, 20), 5), vec![CounterEvent::Change]);
+        assert!(events_for(&WinState::sample(3, 10), &WinState::sample(3, 10), 5).is_empty());
+        let other = WinState { current_preset: "Casual".to_string(), ..WinState::sample(10, 10) };
+        assert_eq!(events_for(&WinState::sample(2, 10), &other, 5), vec![CounterEvent::Change, CounterEvent::SessionEnd]);
+        let same_count = WinState { current_preset: "Casual".to_string(), ..WinState::sample(2, 10) };
+        assert_eq!(events_for(&WinState::sample(2, 10), &same_count, 5), vec![CounterEvent::SessionEnd]);
+    }
+
+    #[test]
+    fn backups_leave_out_urls() {
+        let data = serde_json::to_vec(&vec![webhook("https://discord.com/api/webhooks/1/secret".to_string())]).unwrap();
+        let copy = backup_copy(&data).unwrap();
+        assert!(!String::from_utf8_lossy(&copy).contains("secret"));
+
+        let current = vec![webhook("https://example.com/hook".to_string())];
+        let restored: Vec<Webhook> = serde_json::from_slice(&restore_urls(&copy, &current).unwrap()).unwrap();
+        assert_eq!(restored, current);
+        let unknown: Vec<Webhook> = serde_json::from_slice(&restore_urls(&copy, &[]).unwrap()).unwrap();
+        assert!(unknown[0].url.is_empty() && !unknown[0].enabled);
+    }
+
+    #[test]
+    fn urls_are_masked_for_the_ui() {
+        assert_eq!(masked_url("https://discord.com/api/webhooks/1/secret"), format!("https://discord.com/{}", integration::MASKED_SECRET));
+        assert_eq!(masked_url("not a url"), integration::MASKED_SECRET);
+    }
+
+    #[test]
+    fn renders_templates_inside_strings() {
+        let body = render_body(None, &WinState::sample(7, 10), CounterEvent::Change).unwrap();
+        assert_eq!(body, serde_json::json!({ "event": "change", "win": 7, "goal": 10, "preset": "Ranked" }));
+
+        let discord = r#"{"content":"🏆 {preset}: {win}/{goal} ({event})","embeds":[{"value":"{remaining}"}]}"#;
+        let body = render_body(Some(discord), &WinState::sample(10, 10), CounterEvent::GoalReached).unwrap();
+        assert_eq!(body, serde_json::json!({ "content": "🏆 Ranked: 10/10 (goal_reached)", "embeds": [{ "value": 0 }] }));
+
+        // A preset name with quotes stays inside its string
+        let tricky = WinState { current_preset: "a\",\"x\":\"y".to_string(), ..WinState::sample(1, 2) };
+        let body = render_body(Some(r#"{"p":"{preset}"}"#), &tricky, CounterEvent::Change).unwrap();
+        assert_eq!(body["p"], "a\",\"x\":\"y");
+    }
+
+    #[test]
+    fn validates_webhooks() {
+        let mut hook = webhook("https://discord.com/api/webhooks/1/abc".to_string());
+        assert!(validate(&hook).is_ok());
+        hook.template = Some(r#"{"content":"{wins}"}"#.to_string());
+        assert!(validate(&hook).is_err());
+        hook.template = Some("not json".to_string());
+        assert!(validate(&hook).is_err());
+        hook.template = None;
+        hook.url = "ftp://example.com".to_string();
+        assert!(validate(&hook).is_err());
+        hook.url = "https://example.com".to_string();
+        hook.events.clear();
+        assert!(validate(&hook).is_err());
+    }
+
+    #[tokio::test]
+    async fn retries_until_the_server_accepts() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let mut bodies = Vec::new();
+            for status in ["500 Internal Server Error", "503 Service Unavailable", "204 No Content"] {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut buffer = [0u8; 4096];
+                // Read headers and the announced body
+                loop {
+                    let n = stream.read(&mut buffer).await.unwrap();
+                    request.extend_from_slice(&buffer[..n]);
+                    let text = String::from_utf8_lossy(&request).to_string();
+                    if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                        let length = head.lines()
+                            .find_map(|l| l.to_ascii_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse::<usize>().unwrap()))
+                            .unwrap_or(0);
+                        if body.len() >= length {
+                            bodies.push(body.to_string());
+                            break;
+                        }
+                    }
+                }
+                let reply = format!("HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status);
+                stream.write_all(reply.as_bytes()).await.unwrap();
+            }
+            bodies
+        });
+
+        let client = reqwest::Client::new();
+        let body = render_body(None, &WinState::sample(10, 10), CounterEvent::GoalReached).unwrap();
+        let record = deliver(&client, &webhook(url), CounterEvent::GoalReached, &body, Duration::from_millis(10)).await;
+        let bodies = server.await.unwrap();
+
+        assert!(record.success);
+        assert_eq!(record.attempts, 3);
+        assert_eq!(record.status, Some(204));
+        assert_eq!(bodies.len(), 3);
+        assert_eq!(serde_json::from_str::<Value>(&bodies[2]).unwrap()["event"], "goal_reached");
+    }
+
+    #[tokio::test]
+    async fn gives_up_on_client_errors() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buffer = [0u8; 4096];
+                let _ = stream.read(&mut buffer).await;
+                let _ = stream.write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n").await;
+            }
+        });
+
+        let client = reqwest::Client::new();
+        let record = deliver(&client, &webhook(url), CounterEvent::GoalReached, &serde_json::json!({}), Duration::from_millis(10)).await;
+        assert!(!record.success);
+        assert_eq!(record.attempts, 1);
+        assert_eq!(record.status, Some(404));
+    }
+}